@@ -0,0 +1,154 @@
+#![no_main]
+
+//! Feeds arbitrary packets to `RustasticDrone::handle_packet` on a drone with random neighbors.
+//!
+//! The drone must never panic, whatever routing header it receives, and it must never lose a
+//! fragment silently: every `MsgFragment` is either forwarded, NACKed, shortcut to the controller
+//! or reported as a protocol violation.
+
+use arbitrary::{Result, Unstructured};
+use crossbeam_channel::{unbounded, Receiver};
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{
+        Ack, FloodRequest, FloodResponse, Fragment, Nack, NackType, NodeType, Packet, PacketType,
+    },
+};
+
+use rustastic_drone::{RustasticDrone, RustasticEvent};
+
+const DRONE_ID: u8 = 1;
+const MAX_NODE_ID: u8 = 4;
+
+fn node_type(u: &mut Unstructured) -> Result<NodeType> {
+    Ok(*u.choose(&[NodeType::Client, NodeType::Drone, NodeType::Server])?)
+}
+
+fn path_trace(u: &mut Unstructured) -> Result<Vec<(u8, NodeType)>> {
+    let len = u.int_in_range(0..=8)?;
+    (0..len)
+        .map(|_| Ok((u.arbitrary()?, node_type(u)?)))
+        .collect()
+}
+
+fn pack_type(u: &mut Unstructured) -> Result<PacketType> {
+    Ok(match u.int_in_range(0..=4)? {
+        0 => PacketType::MsgFragment(Fragment {
+            fragment_index: u.arbitrary()?,
+            total_n_fragments: u.arbitrary()?,
+            length: u.arbitrary()?,
+            data: u.arbitrary()?,
+        }),
+        1 => PacketType::Ack(Ack {
+            fragment_index: u.arbitrary()?,
+        }),
+        2 => PacketType::Nack(Nack {
+            fragment_index: u.arbitrary()?,
+            nack_type: match u.int_in_range(0..=3)? {
+                0 => NackType::ErrorInRouting(u.arbitrary()?),
+                1 => NackType::DestinationIsDrone,
+                2 => NackType::Dropped,
+                _ => NackType::UnexpectedRecipient(u.arbitrary()?),
+            },
+        }),
+        3 => PacketType::FloodRequest(FloodRequest {
+            flood_id: u.arbitrary()?,
+            initiator_id: u.arbitrary()?,
+            path_trace: path_trace(u)?,
+        }),
+        _ => PacketType::FloodResponse(FloodResponse {
+            flood_id: u.arbitrary()?,
+            path_trace: path_trace(u)?,
+        }),
+    })
+}
+
+fn packet(u: &mut Unstructured) -> Result<Packet> {
+    // Keep the IDs small so that the drone and its neighbors show up in the routes
+    let len = u.int_in_range(0..=8)?;
+    let hops = (0..len)
+        .map(|_| u.int_in_range(0..=MAX_NODE_ID))
+        .collect::<Result<Vec<u8>>>()?;
+    let hop_index = if u.arbitrary()? {
+        u.arbitrary()?
+    } else {
+        u.int_in_range(0..=len)?
+    };
+
+    Ok(Packet {
+        pack_type: pack_type(u)?,
+        routing_header: SourceRoutingHeader { hop_index, hops },
+        session_id: u.arbitrary()?,
+    })
+}
+
+fn neighbors(u: &mut Unstructured) -> Result<Vec<u8>> {
+    let mut neighbors = Vec::new();
+    for id in (0..=MAX_NODE_ID).filter(|&id| id != DRONE_ID) {
+        if u.arbitrary()? {
+            neighbors.push(id);
+        }
+    }
+    Ok(neighbors)
+}
+
+fn is_handled(
+    controller_recv: &Receiver<DroneEvent>,
+    event_recv: &Receiver<RustasticEvent>,
+    neighbor_recvs: &[Receiver<Packet>],
+) -> bool {
+    let forwarded_or_nacked = neighbor_recvs.iter().any(|recv| !recv.is_empty());
+    let shortcut = controller_recv
+        .try_iter()
+        .any(|event| matches!(event, DroneEvent::ControllerShortcut(_)));
+    let reported = event_recv
+        .try_iter()
+        .any(|event| matches!(event, RustasticEvent::ProtocolViolation { .. }));
+
+    forwarded_or_nacked || shortcut || reported
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let (Ok(neighbors), Ok(pdr), Ok(packet)) =
+        (neighbors(&mut u), u.int_in_range(0..=100u8), packet(&mut u))
+    else {
+        return;
+    };
+    let is_fragment = matches!(packet.pack_type, PacketType::MsgFragment(_));
+
+    let (controller_send, controller_recv) = unbounded();
+    let (event_send, event_recv) = unbounded();
+    let mut neighbor_recvs = Vec::new();
+    let packet_send: HashMap<_, _> = neighbors
+        .into_iter()
+        .map(|id| {
+            let (send, recv) = unbounded();
+            neighbor_recvs.push(recv);
+            (id, send)
+        })
+        .collect();
+
+    let mut drone = RustasticDrone::new(
+        DRONE_ID,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        f32::from(pdr) / 100.0,
+    )
+    .with_event_send(event_send);
+
+    drone.handle_packet(packet);
+
+    if is_fragment {
+        assert!(
+            is_handled(&controller_recv, &event_recv, &neighbor_recvs),
+            "the fragment was neither forwarded, NACKed, shortcut nor reported"
+        );
+    }
+});
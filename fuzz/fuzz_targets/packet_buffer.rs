@@ -0,0 +1,128 @@
+#![no_main]
+
+//! Drives a `PacketBuffer` with random add/get/resize sequences and checks it against a
+//! reference model: a FIFO queue of keys that evicts its oldest entry when it is full.
+
+use arbitrary::{Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use std::collections::VecDeque;
+use wg_2024::{
+    network::SourceRoutingHeader,
+    packet::{Fragment, Packet, PacketType},
+};
+
+use rustastic_drone::PacketBuffer;
+
+/// Same cap as `PacketBuffer::edit_max_size_buffer`.
+const MAX_BUFFER_SIZE: usize = 1024;
+
+enum Op {
+    Add(u64, u64),
+    Get(u64, u64),
+    Resize(usize),
+}
+
+/// Keys come from a small range, so that gets hit buffered fragments and adds collide.
+fn key(u: &mut Unstructured) -> Result<(u64, u64)> {
+    Ok((u.int_in_range(0..=3)?, u.int_in_range(0..=7)?))
+}
+
+fn op(u: &mut Unstructured) -> Result<Op> {
+    Ok(match u.int_in_range(0..=4)? {
+        0 | 1 => {
+            let (session_id, fragment_index) = key(u)?;
+            Op::Add(session_id, fragment_index)
+        }
+        2 | 3 => {
+            let (session_id, fragment_index) = key(u)?;
+            Op::Get(session_id, fragment_index)
+        }
+        _ => Op::Resize(usize::from(u.arbitrary::<u16>()?)),
+    })
+}
+
+/// Builds a fragment packet tagged with `tag`, to tell apart packets stored under the same key.
+fn fragment_packet(session_id: u64, fragment_index: u64, tag: u64) -> Packet {
+    Packet {
+        pack_type: PacketType::MsgFragment(Fragment {
+            fragment_index,
+            total_n_fragments: tag,
+            length: 0,
+            data: [0; 128],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![0, 1, 2],
+        },
+        session_id,
+    }
+}
+
+fn tag_of(packet: &Packet) -> Option<u64> {
+    match &packet.pack_type {
+        PacketType::MsgFragment(fragment) => Some(fragment.total_n_fragments),
+        _ => None,
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    let Ok(initial_size) = u.int_in_range(1..=16usize) else {
+        return;
+    };
+
+    let mut buffer = PacketBuffer::new(initial_size);
+    let mut max_size = initial_size;
+    let mut model: VecDeque<((u64, u64), u64)> = VecDeque::new();
+    let mut next_tag = 0;
+
+    while !u.is_empty() {
+        let Ok(op) = op(&mut u) else {
+            break;
+        };
+        match op {
+            Op::Add(session_id, fragment_index) => {
+                let key = (session_id, fragment_index);
+                // Re-adding a buffered key replaces its packet and makes it the newest one
+                if let Some(pos) = model.iter().position(|(k, _)| *k == key) {
+                    model.remove(pos);
+                } else if model.len() >= max_size {
+                    model.pop_front();
+                }
+                model.push_back((key, next_tag));
+                buffer.add_fragment(
+                    session_id,
+                    fragment_index,
+                    fragment_packet(session_id, fragment_index, next_tag),
+                );
+                next_tag += 1;
+            }
+            Op::Get(session_id, fragment_index) => {
+                let key = (session_id, fragment_index);
+                let expected = model
+                    .iter()
+                    .position(|(k, _)| *k == key)
+                    .and_then(|pos| model.remove(pos))
+                    .map(|(_, tag)| tag);
+                let got = buffer.get_fragment(session_id, fragment_index);
+
+                assert_eq!(got.as_ref().and_then(tag_of), expected);
+                if let Some(packet) = got {
+                    assert_eq!(packet.session_id, session_id);
+                }
+            }
+            Op::Resize(new_size) => {
+                buffer.edit_max_size_buffer(new_size);
+                max_size = new_size.min(MAX_BUFFER_SIZE);
+            }
+        }
+
+        assert_eq!(buffer.len(), model.len());
+    }
+
+    for ((session_id, fragment_index), tag) in model {
+        let got = buffer.get_fragment(session_id, fragment_index);
+        assert_eq!(got.as_ref().and_then(tag_of), Some(tag));
+    }
+    assert!(buffer.is_empty());
+});
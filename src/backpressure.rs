@@ -0,0 +1,123 @@
+//! This file contains the Rustastic Drone's backpressure handling, developed by the Group Rustastic.
+//!
+//! File:   drone/backpressure.rs
+//!
+//! Brief:  File for the outbound queues where a drone keeps the packets its congested neighbors can't take yet.
+//!
+//! Author: Rustastic
+
+use std::collections::{BTreeMap, VecDeque};
+use wg_2024::{network::NodeId, packet::Packet};
+
+use crate::policy::OverflowPolicy;
+
+/// The outcome of queuing a packet for a congested neighbor.
+#[derive(Debug)]
+pub(crate) enum Backlogged {
+    /// The packet waits in the queue of the neighbor, `congested` if the neighbor just became congested.
+    Queued { congested: bool },
+    /// The queue is full: this packet was dropped according to the `OverflowPolicy`.
+    Dropped(Packet),
+}
+
+/// The packets waiting for the drone's congested neighbors.
+///
+/// # Fields
+/// - `max_len`: How many packets can wait for a neighbor.
+/// - `policy`: What happens to a packet for a neighbor whose queue is full.
+/// - `queues`: The packets waiting, keyed by the ID of the neighbor. A neighbor is congested while it has a queue.
+#[derive(Debug, Clone)]
+pub(crate) struct Backlog {
+    max_len: usize,
+    policy: OverflowPolicy,
+    queues: BTreeMap<NodeId, VecDeque<Packet>>,
+}
+
+impl Default for Backlog {
+    fn default() -> Self {
+        Self {
+            max_len: 64,
+            policy: OverflowPolicy::default(),
+            queues: BTreeMap::new(),
+        }
+    }
+}
+
+impl Backlog {
+    pub(crate) fn set_limits(&mut self, max_len: usize, policy: OverflowPolicy) {
+        self.max_len = max_len;
+        self.policy = policy;
+    }
+
+    pub(crate) fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.queues.is_empty()
+    }
+
+    pub(crate) fn is_congested(&self, node_id: NodeId) -> bool {
+        self.queues.contains_key(&node_id)
+    }
+
+    /// Returns the IDs of the congested neighbors, in increasing order.
+    pub(crate) fn congested(&self) -> Vec<NodeId> {
+        self.queues.keys().copied().collect()
+    }
+
+    /// Queues a packet behind the ones already waiting for `to`.
+    ///
+    /// With a `max_len` of 0 nothing is ever queued: the neighbor doesn't get a queue, and isn't congested.
+    pub(crate) fn push(&mut self, to: NodeId, packet: Packet) -> Backlogged {
+        let queued = self.queues.get(&to).map_or(0, VecDeque::len);
+        if queued < self.max_len {
+            let congested = queued == 0 && !self.queues.contains_key(&to);
+            self.queues.entry(to).or_default().push_back(packet);
+            return Backlogged::Queued { congested };
+        }
+
+        let Some(queue) = self.queues.get_mut(&to) else {
+            return Backlogged::Dropped(packet);
+        };
+        match self.policy {
+            OverflowPolicy::DropHead => match queue.pop_front() {
+                Some(oldest) => {
+                    queue.push_back(packet);
+                    Backlogged::Dropped(oldest)
+                }
+                None => Backlogged::Dropped(packet),
+            },
+            OverflowPolicy::DropTail | OverflowPolicy::NackUpstream => Backlogged::Dropped(packet),
+        }
+    }
+
+    pub(crate) fn pop(&mut self, to: NodeId) -> Option<Packet> {
+        self.queues.get_mut(&to)?.pop_front()
+    }
+
+    /// Puts back a packet the neighbor refused again, at the head of its queue.
+    pub(crate) fn push_front(&mut self, to: NodeId, packet: Packet) {
+        self.queues.entry(to).or_default().push_front(packet);
+    }
+
+    /// Forgets a neighbor whose queue has been emptied.
+    ///
+    /// # Returns
+    /// `true` if the neighbor was congested and is not anymore.
+    pub(crate) fn clear_if_empty(&mut self, to: NodeId) -> bool {
+        if self.queues.get(&to).is_some_and(VecDeque::is_empty) {
+            self.queues.remove(&to);
+            return true;
+        }
+        false
+    }
+
+    /// Removes every queued packet.
+    pub(crate) fn drain(&mut self) -> Vec<(NodeId, Packet)> {
+        std::mem::take(&mut self.queues)
+            .into_iter()
+            .flat_map(|(node_id, queue)| queue.into_iter().map(move |packet| (node_id, packet)))
+            .collect()
+    }
+}
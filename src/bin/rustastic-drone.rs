@@ -0,0 +1,301 @@
+//! This file contains the standalone Rustastic Drone, developed by the Group Rustastic.
+//!
+//! File:   drone/bin/rustastic-drone.rs
+//!
+//! Brief:  Executable that runs one `RustasticDrone` over a `SocketTransport`, configured by a TOML file.
+//!
+//! Author: Rustastic
+//!
+//! # Usage
+//! ```text
+//! rustastic-drone <config.toml>
+//! ```
+//!
+//! # Configuration
+//! ```toml
+//! id = 1
+//! address = "udp:127.0.0.1:9001"
+//! pdr = 0.05
+//! # Optional datagram socket receiving commands, besides stdin
+//! control = "udp:127.0.0.1:9101"
+//!
+//! [[neighbor]]
+//! id = 2
+//! address = "udp:127.0.0.1:9002"
+//!
+//! # Optional
+//! [buffer]
+//! size = 16
+//! max_local_retries = 3
+//! absorb_recovered = true
+//! duplicate_window = 0 # duplicates are only discarded with a window
+//! ```
+//!
+//! # Commands
+//! One per line, on stdin or as a datagram on the control socket:
+//! - `crash`
+//! - `pdr <value>`
+//! - `add <id> <address>`
+//! - `remove <id>`
+//!
+//! Every `DroneEvent` is written to stdout as a line of JSON.
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use serde::Deserialize;
+use serde_json::json;
+use std::io::{self, BufRead, Write};
+use std::net::UdpSocket;
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::{env, fs, process, thread};
+
+use rustastic_drone::{codec, NackRecoveryPolicy, RustasticDrone, SocketAddress, SocketTransport};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    network::NodeId,
+    packet::Packet,
+};
+
+/// The TOML configuration of the drone.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    id: NodeId,
+    address: String,
+    pdr: f32,
+    control: Option<String>,
+    #[serde(default)]
+    neighbor: Vec<NeighborConfig>,
+    #[serde(default)]
+    buffer: BufferConfig,
+}
+
+/// A neighbor of the drone and the address of its socket.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NeighborConfig {
+    id: NodeId,
+    address: String,
+}
+
+/// The settings of the drone's buffer and of the fragments it remembers.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct BufferConfig {
+    size: usize,
+    max_local_retries: u8,
+    absorb_recovered: bool,
+    duplicate_window: usize,
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        let nack_recovery = NackRecoveryPolicy::default();
+        Self {
+            size: 16,
+            max_local_retries: nack_recovery.max_local_retries,
+            absorb_recovered: nack_recovery.absorb_recovered,
+            duplicate_window: 0,
+        }
+    }
+}
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        fail("usage: rustastic-drone <config.toml>");
+    };
+    let config: Config = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| toml::from_str(&content).map_err(|e| e.to_string()))
+        .unwrap_or_else(|e| fail(&format!("can't read the configuration `{path}`: {e}")));
+
+    let mut transport = SocketTransport::bind(&parse_address(&config.address))
+        .unwrap_or_else(|e| fail(&format!("can't bind `{}`: {e}", config.address)));
+    for neighbor in &config.neighbor {
+        transport = transport.with_neighbor(neighbor.id, parse_address(&neighbor.address));
+    }
+
+    let (controller_send, controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+
+    let mut drone = RustasticDrone::with_transport(
+        config.id,
+        controller_send,
+        command_recv,
+        transport,
+        config.pdr,
+    )
+    .with_duplicate_window(config.buffer.duplicate_window)
+    .with_nack_recovery(NackRecoveryPolicy {
+        max_local_retries: config.buffer.max_local_retries,
+        absorb_recovered: config.buffer.absorb_recovered,
+    });
+    drone.buffer_mut().edit_max_size_buffer(config.buffer.size);
+
+    if let Some(control) = &config.control {
+        spawn_control_socket(&parse_address(control), command_send.clone());
+    }
+    spawn_stdin(command_send);
+    let printer = thread::spawn(move || print_events(&controller_recv));
+
+    drone.serve();
+
+    // The printer stops once the drone, and with it the controller channel, is gone
+    drop(drone);
+    let _ = printer.join();
+}
+
+/// Prints an error and exits.
+fn fail(message: &str) -> ! {
+    eprintln!("rustastic-drone: {message}");
+    process::exit(1);
+}
+
+fn parse_address(address: &str) -> SocketAddress {
+    address.parse().unwrap_or_else(|e: String| fail(&e))
+}
+
+/// Writes every `DroneEvent` to stdout as a line of JSON.
+fn print_events(controller_recv: &Receiver<DroneEvent>) {
+    let stdout = io::stdout();
+    for event in controller_recv {
+        let (name, packet) = match event {
+            DroneEvent::PacketSent(packet) => ("PacketSent", packet),
+            DroneEvent::PacketDropped(packet) => ("PacketDropped", packet),
+            DroneEvent::ControllerShortcut(packet) => ("ControllerShortcut", packet),
+        };
+        let line = json!({ "event": name, "packet": packet });
+
+        let mut stdout = stdout.lock();
+        if writeln!(stdout, "{line}")
+            .and_then(|()| stdout.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Reads commands from stdin, one per line.
+fn spawn_stdin(command_send: Sender<DroneCommand>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if !forward_command(&line, &command_send) {
+                return;
+            }
+        }
+    });
+}
+
+/// Reads commands from a datagram socket, one per datagram.
+fn spawn_control_socket(address: &SocketAddress, command_send: Sender<DroneCommand>) {
+    match address {
+        SocketAddress::Udp(addr) => {
+            let socket = UdpSocket::bind(addr)
+                .unwrap_or_else(|e| fail(&format!("can't bind `{address}`: {e}")));
+            thread::spawn(move || receive_commands(|buf| socket.recv(buf), &command_send));
+        }
+        #[cfg(unix)]
+        SocketAddress::Unix(path) => {
+            let socket = UnixDatagram::bind(path)
+                .unwrap_or_else(|e| fail(&format!("can't bind `{address}`: {e}")));
+            thread::spawn(move || receive_commands(|buf| socket.recv(buf), &command_send));
+        }
+    }
+}
+
+/// Forwards the commands received with `recv` until the socket fails or the drone is gone.
+fn receive_commands(
+    mut recv: impl FnMut(&mut [u8]) -> io::Result<usize>,
+    command_send: &Sender<DroneCommand>,
+) {
+    let mut buf = [0; 512];
+    while let Ok(len) = recv(&mut buf) {
+        let line = String::from_utf8_lossy(&buf[..len]);
+        if !forward_command(line.trim_end(), command_send) {
+            return;
+        }
+    }
+}
+
+/// Parses a command and sends it to the drone.
+///
+/// # Returns
+/// `false` once the drone is gone, `true` otherwise.
+fn forward_command(line: &str, command_send: &Sender<DroneCommand>) -> bool {
+    let command = match parse_command(line) {
+        Ok(Some(command)) => command,
+        Ok(None) => return true,
+        Err(e) => {
+            eprintln!("rustastic-drone: {e}");
+            return true;
+        }
+    };
+    command_send.send(command).is_ok()
+}
+
+/// Parses a command line, empty lines are ignored.
+fn parse_command(line: &str) -> Result<Option<DroneCommand>, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let command = match words.as_slice() {
+        [] => return Ok(None),
+        ["crash"] => DroneCommand::Crash,
+        ["pdr", pdr] => DroneCommand::SetPacketDropRate(
+            pdr.parse()
+                .map_err(|e| format!("invalid pdr `{pdr}`: {e}"))?,
+        ),
+        ["remove", id] => DroneCommand::RemoveSender(parse_id(id)?),
+        ["add", id, address] => {
+            DroneCommand::AddSender(parse_id(id)?, bridge(&address.parse::<SocketAddress>()?)?)
+        }
+        _ => return Err(format!("unknown command `{line}`")),
+    };
+    Ok(Some(command))
+}
+
+fn parse_id(id: &str) -> Result<NodeId, String> {
+    id.parse()
+        .map_err(|e| format!("invalid node ID `{id}`: {e}"))
+}
+
+/// Creates the channel of a `DroneCommand::AddSender` for a neighbor in another process: a thread encodes
+/// the packets sent on it and sends them to the neighbor's socket, until the drone drops the channel.
+fn bridge(address: &SocketAddress) -> Result<Sender<Packet>, String> {
+    match address.clone() {
+        SocketAddress::Udp(addr) => {
+            let local = if addr.is_ipv4() {
+                "0.0.0.0:0"
+            } else {
+                "[::]:0"
+            };
+            let socket = UdpSocket::bind(local).map_err(|e| e.to_string())?;
+            Ok(spawn_bridge(move |frame| socket.send_to(frame, addr)))
+        }
+        #[cfg(unix)]
+        SocketAddress::Unix(path) => {
+            let socket = UnixDatagram::unbound().map_err(|e| e.to_string())?;
+            Ok(spawn_bridge(move |frame| socket.send_to(frame, &path)))
+        }
+    }
+}
+
+/// Spawns the thread of a `bridge`, which sends the encoded frames with `send`.
+fn spawn_bridge(send: impl Fn(&[u8]) -> io::Result<usize> + Send + 'static) -> Sender<Packet> {
+    let (packet_send, packet_recv) = unbounded::<Packet>();
+    thread::spawn(move || {
+        for packet in packet_recv {
+            match codec::encode(&packet) {
+                Ok(frame) => {
+                    if let Err(e) = send(&frame) {
+                        eprintln!("rustastic-drone: can't send the packet: {e}");
+                    }
+                }
+                Err(e) => eprintln!("rustastic-drone: can't encode the packet: {e}"),
+            }
+        }
+    });
+    packet_send
+}
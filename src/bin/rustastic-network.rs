@@ -0,0 +1,124 @@
+//! This file contains the Rustastic network launcher, developed by the Group Rustastic.
+//!
+//! File:   drone/bin/rustastic-network.rs
+//!
+//! Brief:  Executable that launches a whole `Network` of `RustasticDrone`s from a network initialization file.
+//!
+//! Author: Rustastic
+//!
+//! # Usage
+//! ```text
+//! rustastic-network <network.toml>
+//! ```
+//!
+//! The file follows the format of `wg_2024::config::Config`, with `[[drone]]`, `[[client]]` and `[[server]]`
+//! tables.
+//!
+//! # Commands
+//! One per line, on stdin:
+//! - `crash <drone>`
+//! - `pdr <drone> <value>`
+//! - `add <node> <node>`
+//! - `remove <node> <node>`
+//!
+//! Every `DroneEvent` is written to stdout as a line of JSON. At the end of stdin, every drone is crashed.
+
+use serde_json::json;
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use std::{env, process, thread};
+
+use rustastic_drone::{Network, NetworkError};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    network::NodeId,
+};
+
+/// How often the printer checks whether stdin has ended.
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn main() {
+    let Some(path) = env::args().nth(1) else {
+        fail("usage: rustastic-network <network.toml>");
+    };
+    let network = Network::launch_file(&path).unwrap_or_else(|e| fail(&format!("`{path}`: {e}")));
+    let done = AtomicBool::new(false);
+
+    thread::scope(|scope| {
+        scope.spawn(|| print_events(&network, &done));
+
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if let Err(e) = run_command(&network, &line) {
+                eprintln!("rustastic-network: {e}");
+            }
+        }
+
+        for drone_id in network.drone_ids() {
+            // The drones that have already crashed are skipped
+            let _ = network.send_command(drone_id, DroneCommand::Crash);
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+/// Prints an error and exits.
+fn fail(message: &str) -> ! {
+    eprintln!("rustastic-network: {message}");
+    process::exit(1);
+}
+
+/// Writes every `DroneEvent` to stdout as a line of JSON, until `done` is set and no event is left.
+fn print_events(network: &Network, done: &AtomicBool) {
+    let stdout = io::stdout();
+    loop {
+        let Some((drone_id, event)) = network.recv_event(EVENT_POLL_INTERVAL) else {
+            if done.load(Ordering::Relaxed) {
+                return;
+            }
+            continue;
+        };
+        let (name, packet) = match event {
+            DroneEvent::PacketSent(packet) => ("PacketSent", packet),
+            DroneEvent::PacketDropped(packet) => ("PacketDropped", packet),
+            DroneEvent::ControllerShortcut(packet) => ("ControllerShortcut", packet),
+        };
+        let line = json!({ "drone": drone_id, "event": name, "packet": packet });
+
+        let mut stdout = stdout.lock();
+        if writeln!(stdout, "{line}")
+            .and_then(|()| stdout.flush())
+            .is_err()
+        {
+            return;
+        }
+    }
+}
+
+/// Parses a command line and runs it on the network, empty lines are ignored.
+fn run_command(network: &Network, line: &str) -> Result<(), String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let result = match words.as_slice() {
+        [] => Ok(()),
+        ["crash", id] => network.send_command(parse_id(id)?, DroneCommand::Crash),
+        ["pdr", id, pdr] => network.send_command(
+            parse_id(id)?,
+            DroneCommand::SetPacketDropRate(
+                pdr.parse()
+                    .map_err(|e| format!("invalid pdr `{pdr}`: {e}"))?,
+            ),
+        ),
+        ["add", a, b] => network.connect(parse_id(a)?, parse_id(b)?),
+        ["remove", a, b] => network.disconnect(parse_id(a)?, parse_id(b)?),
+        _ => return Err(format!("unknown command `{line}`")),
+    };
+    result.map_err(|e: NetworkError| e.to_string())
+}
+
+fn parse_id(id: &str) -> Result<NodeId, String> {
+    id.parse()
+        .map_err(|e| format!("invalid node ID `{id}`: {e}"))
+}
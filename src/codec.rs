@@ -0,0 +1,147 @@
+//! This file contains the Rustastic Drone's wire codec, developed by the Group Rustastic.
+//!
+//! File:   drone/codec.rs
+//!
+//! Brief:  File for the binary framing of `Packet`s exchanged between drones in different processes.
+//!
+//! Author: Rustastic
+
+use bincode::Options;
+use std::fmt;
+use wg_2024::packet::Packet;
+
+/// Version of the frame layout written by `encode`. Frames with any other version are refused.
+pub const CODEC_VERSION: u8 = 1;
+
+/// Length of the frame header: version (1 byte), payload length (4 bytes) and checksum (4 bytes).
+pub const HEADER_LEN: usize = 9;
+
+/// Maximum length of a payload, so that a frame always fits in a single UDP datagram.
+pub const MAX_PAYLOAD_LEN: usize = 65_000;
+
+/// An error raised while encoding or decoding a frame.
+#[derive(Debug)]
+pub enum CodecError {
+    /// The frame was written with a layout this drone doesn't understand.
+    UnsupportedVersion(u8),
+    /// The bytes end before the frame does.
+    Truncated { needed: usize, available: usize },
+    /// The payload is longer than `MAX_PAYLOAD_LEN`.
+    TooLong(usize),
+    /// The payload doesn't match its checksum, it was corrupted on the way.
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// The payload is not a valid `Packet`.
+    Malformed(String),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::UnsupportedVersion(version) => {
+                write!(f, "unsupported frame version {version}")
+            }
+            CodecError::Truncated { needed, available } => {
+                write!(
+                    f,
+                    "truncated frame: {needed} bytes needed, {available} available"
+                )
+            }
+            CodecError::TooLong(len) => {
+                write!(f, "payload of {len} bytes exceeds {MAX_PAYLOAD_LEN} bytes")
+            }
+            CodecError::ChecksumMismatch { expected, found } => {
+                write!(
+                    f,
+                    "checksum mismatch: expected {expected:#010x}, found {found:#010x}"
+                )
+            }
+            CodecError::Malformed(reason) => write!(f, "malformed packet: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+/// The serialization options of the payload, with a size limit so that a malformed payload can't
+/// make the decoder allocate more than a frame can carry.
+fn payload_options() -> impl Options {
+    bincode::DefaultOptions::new().with_limit(MAX_PAYLOAD_LEN as u64)
+}
+
+/// Encodes a packet into a frame.
+///
+/// # Frame Layout
+/// | Bytes | Content                                     |
+/// |-------|---------------------------------------------|
+/// | 0     | `CODEC_VERSION`                             |
+/// | 1..5  | Payload length, big-endian `u32`            |
+/// | 5..9  | CRC-32 of the payload, big-endian `u32`     |
+/// | 9..   | Payload: the `Packet` serialized by bincode |
+///
+/// # Arguments
+/// - `packet`: The `Packet` to encode.
+///
+/// # Errors
+/// - `CodecError::TooLong` if the serialized packet doesn't fit in a frame.
+/// - `CodecError::Malformed` if the packet can't be serialized.
+pub fn encode(packet: &Packet) -> Result<Vec<u8>, CodecError> {
+    let payload = payload_options().serialize(packet).map_err(|e| match *e {
+        bincode::ErrorKind::SizeLimit => CodecError::TooLong(MAX_PAYLOAD_LEN + 1),
+        e => CodecError::Malformed(e.to_string()),
+    })?;
+    let len = u32::try_from(payload.len()).map_err(|_| CodecError::TooLong(payload.len()))?;
+
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.push(CODEC_VERSION);
+    frame.extend_from_slice(&len.to_be_bytes());
+    frame.extend_from_slice(&crc32fast::hash(&payload).to_be_bytes());
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Decodes the frame at the start of `bytes`.
+///
+/// # Arguments
+/// - `bytes`: The bytes to decode, starting with a frame header.
+///
+/// # Returns
+/// The decoded `Packet` and the length of its frame, so that a stream of frames can be decoded one after
+/// the other.
+///
+/// # Errors
+/// Returns a `CodecError` if `bytes` don't start with a complete and valid frame.
+pub fn decode(bytes: &[u8]) -> Result<(Packet, usize), CodecError> {
+    let Some(header) = bytes.get(..HEADER_LEN) else {
+        return Err(CodecError::Truncated {
+            needed: HEADER_LEN,
+            available: bytes.len(),
+        });
+    };
+
+    if header[0] != CODEC_VERSION {
+        return Err(CodecError::UnsupportedVersion(header[0]));
+    }
+
+    let len = u32::from_be_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_PAYLOAD_LEN {
+        return Err(CodecError::TooLong(len));
+    }
+
+    let Some(payload) = bytes.get(HEADER_LEN..HEADER_LEN + len) else {
+        return Err(CodecError::Truncated {
+            needed: HEADER_LEN + len,
+            available: bytes.len(),
+        });
+    };
+
+    let expected = u32::from_be_bytes([header[5], header[6], header[7], header[8]]);
+    let found = crc32fast::hash(payload);
+    if expected != found {
+        return Err(CodecError::ChecksumMismatch { expected, found });
+    }
+
+    let packet = payload_options()
+        .deserialize(payload)
+        .map_err(|e| CodecError::Malformed(e.to_string()))?;
+    Ok((packet, HEADER_LEN + len))
+}
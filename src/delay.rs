@@ -0,0 +1,140 @@
+//! This file contains the Rustastic Drone's link delay emulation, developed by the Group Rustastic.
+//!
+//! File:   drone/delay.rs
+//!
+//! Brief:  File for the `LinkDelay` distributions, and for the queue that holds the packets a drone sends
+//!         until their delay has elapsed.
+//!
+//! Author: Rustastic
+
+use rand::Rng;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::f64::consts::TAU;
+use std::time::{Duration, Instant};
+use wg_2024::{network::NodeId, packet::Packet};
+
+/// The distribution of the delay a packet waits before being sent on a link.
+///
+/// Every packet draws its own delay, so with a jitter the packets of a link may be sent in a different
+/// order than the one they were forwarded in, as on a real link.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum LinkDelay {
+    /// Packets are sent as soon as they are forwarded.
+    #[default]
+    None,
+    /// Every packet waits the same delay.
+    Fixed(Duration),
+    /// Every packet waits a delay drawn uniformly between `min` and `max`, both included.
+    Uniform { min: Duration, max: Duration },
+    /// Every packet waits a delay drawn from a normal distribution, negative delays being cut to zero.
+    Normal { mean: Duration, std_dev: Duration },
+}
+
+impl LinkDelay {
+    /// Draws the delay of a packet.
+    ///
+    /// # Arguments
+    /// - `rng`: The random number generator to draw the delay with.
+    ///
+    /// # Returns
+    /// The delay the packet has to wait, `Duration::ZERO` for `LinkDelay::None`.
+    pub fn sample(&self, rng: &mut impl Rng) -> Duration {
+        match *self {
+            LinkDelay::None => Duration::ZERO,
+            LinkDelay::Fixed(delay) => delay,
+            LinkDelay::Uniform { min, max } if min < max => rng.gen_range(min..=max),
+            LinkDelay::Uniform { min, .. } => min,
+            LinkDelay::Normal { mean, std_dev } => {
+                // Box-Muller transform, `1.0 - gen()` keeps the logarithm away from zero
+                let u1: f64 = 1.0 - rng.gen::<f64>();
+                let u2: f64 = rng.gen();
+                let z = (-2.0 * u1.ln()).sqrt() * (TAU * u2).cos();
+                Duration::from_secs_f64((mean.as_secs_f64() + z * std_dev.as_secs_f64()).max(0.0))
+            }
+        }
+    }
+}
+
+/// A packet waiting in a `DelayQueue`.
+#[derive(Debug, Clone)]
+struct DelayedPacket {
+    due: Instant,
+    seq: u64,
+    to: NodeId,
+    packet: Packet,
+}
+
+impl DelayedPacket {
+    fn key(&self) -> (Instant, u64) {
+        (self.due, self.seq)
+    }
+}
+
+impl PartialEq for DelayedPacket {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for DelayedPacket {}
+
+impl PartialOrd for DelayedPacket {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DelayedPacket {
+    // Reversed, so that the `BinaryHeap` pops the earliest packet first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key().cmp(&self.key())
+    }
+}
+
+/// The packets a drone has forwarded, waiting for their delay to elapse before being sent.
+///
+/// Packets due at the same instant leave in the order they were pushed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DelayQueue {
+    queue: BinaryHeap<DelayedPacket>,
+    next_seq: u64,
+}
+
+impl DelayQueue {
+    /// Adds a packet to send to `to` at the instant `due`.
+    pub(crate) fn push(&mut self, due: Instant, to: NodeId, packet: Packet) {
+        self.queue.push(DelayedPacket {
+            due,
+            seq: self.next_seq,
+            to,
+            packet,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Returns the instant at which the next packet is due, if any.
+    pub(crate) fn next_due(&self) -> Option<Instant> {
+        self.queue.peek().map(|delayed| delayed.due)
+    }
+
+    /// Removes the next packet if it is due at `now`.
+    ///
+    /// # Returns
+    /// The ID of the neighbor and the packet to send, or `None` if no packet is due yet.
+    pub(crate) fn pop_due(&mut self, now: Instant) -> Option<(NodeId, Packet)> {
+        if self.next_due()? > now {
+            return None;
+        }
+        self.queue.pop().map(|delayed| (delayed.to, delayed.packet))
+    }
+
+    /// Removes every packet, due or not, in the order they would have been sent.
+    pub(crate) fn drain(&mut self) -> Vec<(NodeId, Packet)> {
+        let mut packets = Vec::with_capacity(self.queue.len());
+        while let Some(delayed) = self.queue.pop() {
+            packets.push((delayed.to, delayed.packet));
+        }
+        packets
+    }
+}
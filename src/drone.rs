@@ -2,55 +2,109 @@
 //!
 //! File:   drone/drone.rs
 //!
-//! Brief:  Main file for the Rustastic Drone, containing the core drone logic.
+//! Brief:  Main file for the Rustastic Drone, running the `DroneCore` over a `PacketTransport`.
 //!
 //! Author: Rustastic (Andrea Carzeri, Alessandro Busola, Andrea Denina, Giulio Bosio)
 
 use colored::Colorize;
-use crossbeam_channel::{select_biased, Receiver, Sender};
+use crossbeam_channel::{at, never, select_biased, Receiver, Sender};
 use log::{error, info, warn};
-use rand::Rng;
-use std::collections::{HashMap, HashSet};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use wg_2024::{
     controller::{DroneCommand, DroneEvent},
     drone::Drone,
-    network::{NodeId, SourceRoutingHeader},
-    packet::{FloodRequest, FloodResponse, Fragment, Nack, NackType, NodeType, Packet, PacketType},
+    network::NodeId,
+    packet::{Packet, PacketType},
 };
 
-use crate::packet_buffer;
+use crate::backpressure::{Backlog, Backlogged};
+use crate::delay::{DelayQueue, LinkDelay};
+use crate::drone_core::{DroneAction, DroneCore, FloodStats};
+use crate::event::RustasticEvent;
+use crate::fair_queue::FlowStats;
+use crate::flood_limit::FloodRateLimit;
+use crate::packet_buffer::PacketBuffer;
+use crate::policy::{
+    FairQueuing, LivenessPolicy, LoopPolicy, NackRecoveryPolicy, OverflowPolicy, PriorityWeights,
+    SchedulingPolicy,
+};
+use crate::priority::PriorityQueue;
+use crate::scheduler::{Scheduler, Turn};
+use crate::shaping::{LinkShaping, Shaped, Shaper};
+use crate::topology::Topology;
+use crate::transport::{ChannelTransport, PacketTransport, TransportError};
+
+/// How often the drone retries to send the packets queued for its congested neighbors.
+const BACKLOG_RETRY: Duration = Duration::from_millis(1);
+
+/// How many received packets at most wait in the inbox, the others are left in the transport channel.
+const INBOX_CAPACITY: usize = 64;
 
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone)]
 /// A Rustastic drone entity.
 ///
-/// The `RustasticDrone` struct represents a single drone with a unique ID,
-/// communication channels for sending and receiving commands and packets,
-/// and state for handling packet delivery and flood detection. It includes
-/// functionality for packet routing, handling network events, and managing
-/// the drone's internal packet buffer.
+/// The `RustasticDrone` struct connects a `DroneCore`, which takes all the protocol decisions, to the
+/// simulation controller and to a `PacketTransport`: it feeds the core with the commands and packets it
+/// receives, and performs the `DroneAction`s the core returns.
+///
+/// The transport defaults to a `ChannelTransport`, over crossbeam channels, which is the one built by
+/// `Drone::new`. Drones over other transports are built with `RustasticDrone::with_transport`.
+///
+/// The packets received are not handled strictly in arrival order: the ones waiting are queued by `TrafficClass`,
+/// and control packets go ahead of fragments according to the drone's `PriorityWeights`. Fragments can be
+/// shared fairly between sessions or sources, see `RustasticDrone::with_fair_queuing`.
+///
+/// Packets sent to a neighbor with a `LinkDelay` wait in a delay queue, which the main loop services, until
+/// their delay has elapsed. Then, on the links with a `LinkShaping`, they wait for their token bucket.
+///
+/// Sending never blocks: when a neighbor's channel is full, the packets for it wait in an outbound queue,
+/// retried by the main loop, see `RustasticDrone::with_outbound_queue`. A neighbor that can't be reached at all
+/// anymore is declared dead after a few failed sends, see `RustasticDrone::with_liveness`.
+///
+/// The drone can learn the network from the `path_trace`s of the floods that cross it, see
+/// `RustasticDrone::with_topology`.
+///
+/// The buffer of the fragments forwarded by the drone is kept by its `DroneCore`, and can be inspected and tuned
+/// from the drone itself, see `RustasticDrone::buffer` and `RustasticDrone::buffer_mut`.
 ///
 /// # Fields
-/// - `id`: The unique identifier of the drone.
+/// - `core`: The `DroneCore` holding the protocol state of the drone.
 /// - `controller_send`: A channel for sending events to the controller.
 /// - `controller_recv`: A channel for receiving commands from the controller.
-/// - `packet_recv`: A channel for receiving incoming packets from other drones.
-/// - `pdr`: The Packet Drop Rate (PDR), a float representing the probability
-///   that a packet will be dropped during transmission.
-/// - `packet_send`: A map that associates neighboring drone IDs to their packet-sending channels.
-/// - `flood_id_received`: A set that caches flood IDs already processed, used to prevent
-///   duplicate packet processing in the context of flood-based protocols.
-/// - `buffer`: A packet buffer to temporarily store packets that pass through the drone.
-pub struct RustasticDrone {
-    id: NodeId,
+/// - `transport`: The `PacketTransport` that exchanges packets with the neighbors.
+/// - `event_send`: An optional channel for the `RustasticEvent`s that don't fit in a `DroneEvent`.
+/// - `link_delays`: The `LinkDelay` of the links to some neighbors, keyed by their IDs.
+/// - `default_delay`: The `LinkDelay` of the links to the other neighbors.
+/// - `delay_queue`: The packets waiting for their delay to elapse.
+/// - `rng`: The random number generator drawing the delays.
+/// - `shaper`: The token buckets of the shaped links, and the packets waiting for them.
+/// - `inbox`: The packets received and waiting to be handled, by class.
+/// - `backlog`: The packets waiting for the congested neighbors.
+/// - `scheduler`: Decides whether a command or a batch of packets is handled next.
+/// - `topology`: The `Topology` learned from the floods, if the drone learns one.
+/// - `started`: When the drone was created, the origin of the clock of its core.
+pub struct RustasticDrone<T: PacketTransport = ChannelTransport> {
+    core: DroneCore,
     controller_send: Sender<DroneEvent>,
     controller_recv: Receiver<DroneCommand>,
-    packet_recv: Receiver<Packet>,
-    pdr: f32,
-    packet_send: HashMap<NodeId, Sender<Packet>>,
-    flood_id_received: HashSet<(u64, NodeId)>, // Caching received flood_id
-    pub buffer: packet_buffer::PacketBuffer,   // Packet buffer
+    transport: T,
+    event_send: Option<Sender<RustasticEvent>>,
+    link_delays: HashMap<NodeId, LinkDelay>,
+    default_delay: LinkDelay,
+    delay_queue: DelayQueue,
+    rng: StdRng,
+    shaper: Shaper,
+    inbox: PriorityQueue,
+    backlog: Backlog,
+    scheduler: Scheduler,
+    topology: Option<Arc<Mutex<Topology>>>,
+    started: Instant,
 }
 
 impl Drone for RustasticDrone {
@@ -64,8 +118,7 @@ impl Drone for RustasticDrone {
     /// - `packet_send`: A map of packet-sending channels to other drones, keyed by their IDs.
     /// - `pdr`: The Packet Drop Rate of the drone, which affects transmission reliability.
     ///
-    /// The field `flood_id_received` is initialized to an empty`HashSet`
-    /// The field buffer is initialized with a `PacketBuffer` with default size of 16 packet
+    /// The drone exchanges packets over a `ChannelTransport` built from `packet_recv` and `packet_send`.
     ///
     /// # Returns
     /// A new instance of `RustasticDrone`.
@@ -77,15 +130,64 @@ impl Drone for RustasticDrone {
         packet_send: HashMap<NodeId, Sender<Packet>>,
         pdr: f32,
     ) -> Self {
-        Self {
+        Self::with_transport(
             id,
             controller_send,
             controller_recv,
-            packet_recv,
-            packet_send,
+            ChannelTransport::new(packet_recv, packet_send),
             pdr,
-            flood_id_received: HashSet::new(),
-            buffer: packet_buffer::PacketBuffer::new(16),
+        )
+    }
+
+    /// Runs the main loop of the drone, see `RustasticDrone::serve`.
+    fn run(&mut self) {
+        self.serve();
+    }
+}
+
+impl<T: PacketTransport> RustasticDrone<T> {
+    /// Creates a new `RustasticDrone` that exchanges packets over the given `PacketTransport`.
+    ///
+    /// # Arguments
+    /// - `id`: The unique identifier of the drone.
+    /// - `controller_send`: The channel to send events to the controller.
+    /// - `controller_recv`: The channel to receive commands from the controller.
+    /// - `transport`: The `PacketTransport` connecting the drone to its neighbors.
+    /// - `pdr`: The Packet Drop Rate of the drone, which affects transmission reliability.
+    ///
+    /// The field `core` is initialized with `DroneCore::new`, the neighbors being the ones of the transport
+    /// The fields `link_delays` and `default_delay` are initialized so that packets are sent without delay
+    /// The field `shaper` is initialized without any shaped link
+    /// The field `inbox` is initialized with `PriorityWeights::default()`
+    /// The field `backlog` is initialized with queues of 64 packets and `OverflowPolicy::default()`
+    /// The field `scheduler` is initialized with `SchedulingPolicy::default()`, one packet per turn
+    /// The field `topology` is initialized to `None`, the drone doesn't learn the network
+    ///
+    /// # Returns
+    /// A new instance of `RustasticDrone`.
+    pub fn with_transport(
+        id: NodeId,
+        controller_send: Sender<DroneEvent>,
+        controller_recv: Receiver<DroneCommand>,
+        transport: T,
+        pdr: f32,
+    ) -> Self {
+        Self {
+            core: DroneCore::new(id, pdr, transport.neighbors()),
+            controller_send,
+            controller_recv,
+            transport,
+            event_send: None,
+            link_delays: HashMap::new(),
+            default_delay: LinkDelay::None,
+            delay_queue: DelayQueue::default(),
+            rng: StdRng::from_entropy(),
+            shaper: Shaper::default(),
+            inbox: PriorityQueue::new(PriorityWeights::default()),
+            backlog: Backlog::default(),
+            scheduler: Scheduler::default(),
+            topology: None,
+            started: Instant::now(),
         }
     }
 
@@ -93,1128 +195,845 @@ impl Drone for RustasticDrone {
     ///
     /// This function enters an infinite loop, constantly monitoring two channels:
     /// - `self.controller_recv`: Receives commands from the drone controller.
-    /// - `self.packet_recv`: Receives raw data packets.
+    /// - The `packet_recv` of the transport: Receives raw data packets.
     ///
-    /// The loop uses `select_biased!` to efficiently handle incoming data from both channels.
+    /// The packets pending on the transport are moved to the `inbox`, which hands them out in priority order.
+    /// The inbox holds a bounded number of packets, at least a batch: the others wait in the transport channel.
+    /// While both commands and packets are waiting, the `SchedulingPolicy` decides whether the next iteration
+    /// handles a command or a batch of packets: by default commands go first, one packet per iteration, so that
+    /// a command is never stuck behind a batch of packets.
     ///
-    /// Command from simulation controller are prioritized over data packets
+    /// When there is nothing to do, the loop uses `select_biased!` to wait on both channels, and wakes up
+    /// when the next packet of the delay queue is due, the next shaped packet can be sent or a congested
+    /// neighbor must be retried.
     ///
     /// # Behavior:
-    /// - If a `DroneCommand::Crash` is received, the loop terminates and a warning message is logged.
-    /// - Other commands are passed to the `handle_command` function for further processing.
+    /// - Commands are passed to the `handle_command` function, the loop terminates on a `DroneCommand::Crash`.
     /// - Received packets are passed to the `handle_packet` function for handling.
-    fn run(&mut self) {
+    /// - Delayed packets are sent once they are due, shaped packets once their link has room, queued packets
+    ///   once their neighbor takes them.
+    /// - A channel that gets disconnected, e.g. because the simulation controller is gone, is not waited on
+    ///   anymore: the drone keeps serving the other one.
+    pub fn serve(&mut self) {
+        let mut packet_recv = self.transport.packet_recv().clone();
+        let mut controller_recv = self.controller_recv.clone();
         loop {
-            select_biased! {
-                recv(self.controller_recv) -> command => {
-                    if let Ok(command) = command {
-                        match command {
-                            DroneCommand::Crash => {
-                                warn!("{} [ Drone {} ]: Has crashed", "!!!".yellow(), self.id);
-                                break;
-                            },
-                            _ => self.handle_command(command)
+            self.send_backlog();
+            self.send_due_packets();
+            self.send_shaped_packets();
+
+            let room = INBOX_CAPACITY
+                .max(self.scheduler.batch())
+                .saturating_sub(self.inbox.len());
+            for packet in packet_recv.try_iter().take(room) {
+                self.inbox.push(packet);
+            }
+            let commands_waiting = !self.controller_recv.is_empty();
+            match self
+                .scheduler
+                .next(commands_waiting, !self.inbox.is_empty())
+            {
+                Some(Turn::Command) => {
+                    if let Ok(command) = self.controller_recv.try_recv() {
+                        if !self.handle_command(command) {
+                            break;
                         }
                     }
+                    continue;
                 }
-                recv(self.packet_recv) -> packet => {
-                    if let Ok(packet) = packet {
+                Some(Turn::Packets) => {
+                    for _ in 0..self.scheduler.batch() {
+                        let Some(packet) = self.inbox.pop() else {
+                            break;
+                        };
                         self.handle_packet(packet);
                     }
+                    continue;
+                }
+                None => {}
+            }
+
+            let retry = (!self.backlog.is_empty()).then(|| Instant::now() + BACKLOG_RETRY);
+            let wake_up = [self.delay_queue.next_due(), self.shaper.next_ready(), retry]
+                .into_iter()
+                .flatten()
+                .min();
+            let timer = wake_up.map_or_else(never, at);
+            // A disconnected channel is always ready: it is not waited on anymore, or the loop would spin
+            select_biased! {
+                recv(controller_recv) -> command => match command {
+                    Ok(command) => {
+                        if !self.handle_command(command) {
+                            break;
+                        }
+                    }
+                    Err(_) => {
+                        warn!(
+                            "{} [ Drone {} ]: The controller channel is closed, no more commands will arrive",
+                            "!!!".yellow(),
+                            self.core.id()
+                        );
+                        controller_recv = never();
+                    }
+                },
+                recv(packet_recv) -> packet => match packet {
+                    Ok(packet) => self.inbox.push(packet),
+                    Err(_) => {
+                        warn!(
+                            "{} [ Drone {} ]: The packet channel is closed, no more packets will arrive",
+                            "!!!".yellow(),
+                            self.core.id()
+                        );
+                        packet_recv = never();
+                    }
                 },
+                recv(timer) -> _ => {},
             }
         }
     }
-}
 
-impl RustasticDrone {
-    /// Handles incoming packets for the drone.
-    ///
-    /// This method is responsible for processing incoming packets. It checks the type of packet received and
-    /// handles it accordingly. The packet could be a flood request, a normal message fragment, or an acknowledgment
-    /// (either `Nack` or `Ack`). The method performs several checks to ensure that the packet is valid and
-    /// can be forwarded to the correct next hop. If any errors are found, appropriate `Nack` packets are sent.
-    ///
-    /// # Packet Handling Logic
-    /// - **`FloodRequest`**: If the packet is a flood request, it handles the request by calling `handle_flood_request`,
-    ///   and then adds the flood ID to the `flood_id_received` set to prevent duplicate processing of the same flood.
-    /// - **Correct Packet ID**: If the packet has the correct ID and is routable, it continues with routing and hop management.
-    /// - **Destination Check**: If the destination of the packet is not a valid destination (e.g., a drone instead of a client/server),
-    ///   it sends a `Nack` with an error message (`DestinationIsDrone`).
-    /// - **Neighbor Check**: If the packet cannot be forwarded to a neighbor (i.e., the next hop is not in the drone's neighbor list),
-    ///   it sends a `Nack` indicating an error in routing.
-    /// - **Packet Type Handling**: Depending on the packet type, the method delegates the handling to the appropriate sub-methods:
-    ///   - `Nack` and `Ack` packets are processed by `handle_ack_nack`.
-    ///   - Fragmented messages are processed by `handle_fragment`.
-    ///   - Flood responses are handled by `handle_flood_response`.
+    /// Sets the channel on which the drone reports its `RustasticEvent`s.
     ///
-    /// # Arguments
-    /// - `packet`: The incoming `Packet` that needs to be processed.
+    /// Without this channel the events are only logged.
     ///
-    /// # Behavior
-    /// - The method first checks if the packet is a flood request, and handles it accordingly.
-    /// - If the packet is a normal message, it checks the validity of the destination and the neighbors, then forwards it or
-    ///   sends a `Nack` if needed.
+    /// # Arguments
+    /// - `event_send`: The channel to send `RustasticEvent`s to.
     ///
-    /// # Example
-    /// ```rust
-    /// // Assuming `packet` is a received packet to handle
-    /// drone.handle_packet(packet);
-    /// ```
-    fn handle_packet(&mut self, mut packet: Packet) {
-        info!(
-            "{} [ Drone {} ]: has received the packet {:?}",
-            "✓".green(),
-            self.id,
-            packet
-        );
-
-        if let PacketType::FloodRequest(flood_request) = packet.clone().pack_type {
-            let flood_id = flood_request.flood_id;
-            let flood_initiator = flood_request.initiator_id;
-            self.handle_flood_request(flood_request, &packet);
-            self.flood_id_received.insert((flood_id, flood_initiator));
-        } else if self.check_packet_correct_id(packet.clone()) {
-            // Increase hop_index
-            packet.routing_header.increase_hop_index();
-
-            // If the destination has been reached, and it is a Drone (invalid destination)
-            if packet.routing_header.hop_index == packet.routing_header.hops.len() {
-                error!(
-                    "{} The selected destination in the RoutingHeader of [ Drone {} ] is a Drone",
-                    "✗".red(),
-                    self.id
-                );
-                if let PacketType::MsgFragment(frag) = packet.clone().pack_type {
-                    self.send_nack(packet, Some(frag), NackType::DestinationIsDrone);
-                } else {
-                    self.send_nack(packet, None, NackType::DestinationIsDrone);
-                }
-
-                return;
-            }
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_event_send(mut self, event_send: Sender<RustasticEvent>) -> Self {
+        self.event_send = Some(event_send);
+        self
+    }
 
-            // Check if the next hop is a valid neighbor
-            if !self.check_neighbor(&packet) {
-                //Step4
-                let neighbor = packet.routing_header.hops[packet.routing_header.hop_index];
-                error!(
-                    "{} [ Drone {} ]: can't send packet to Drone {} because it is not its neighbor",
-                    "✗".red(),
-                    self.id,
-                    neighbor
-                );
-                //problematico
-                match packet.clone().pack_type {
-                    PacketType::MsgFragment(fragment) => {
-                        self.send_nack(packet, Some(fragment), NackType::ErrorInRouting(neighbor))
-                    }
-                    PacketType::FloodRequest(_flood_request) => unreachable!(),
-                    _ => self
-                        .controller_send
-                        .send(DroneEvent::ControllerShortcut(packet))
-                        .unwrap(),
-                }
+    /// Sets how the drone reacts to routing headers that contain a loop.
+    ///
+    /// See `DroneCore::with_loop_policy`.
+    #[must_use]
+    pub fn with_loop_policy(mut self, loop_policy: LoopPolicy) -> Self {
+        self.core = self.core.with_loop_policy(loop_policy);
+        self
+    }
 
-                return;
-            }
+    /// Sets how many recently forwarded fragments the drone remembers to discard duplicates.
+    ///
+    /// See `DroneCore::with_duplicate_window`.
+    #[must_use]
+    pub fn with_duplicate_window(mut self, window: usize) -> Self {
+        self.core = self.core.with_duplicate_window(window);
+        self
+    }
 
-            info!(
-                "{} Packet with [ session_id: {} ] is being handled from [ Drone {} ]",
-                "✓".green(),
-                packet.session_id,
-                self.id
-            );
-
-            // Handle packet types: Nack, Ack, MsgFragment, FloodResponse
-            match packet.clone().pack_type {
-                PacketType::Nack(_nack) => self.handle_ack_nack(packet),
-                PacketType::Ack(_ack) => self.handle_ack_nack(packet),
-                PacketType::MsgFragment(fragment) => self.handle_fragment(packet, fragment),
-                PacketType::FloodRequest(_) => unreachable!(),
-                PacketType::FloodResponse(flood_response) => {
-                    self.handle_flood_response(&flood_response, &packet);
-                }
-            }
-        }
+    /// Sets how the drone recovers fragments dropped downstream from its buffer.
+    ///
+    /// See `DroneCore::with_nack_recovery`.
+    #[must_use]
+    pub fn with_nack_recovery(mut self, nack_recovery: NackRecoveryPolicy) -> Self {
+        self.core = self.core.with_nack_recovery(nack_recovery);
+        self
     }
 
-    /// Sends a message packet to the destination drone, or forwards it to the simulation controller if an error occurs.
+    /// Seeds the random number generators that decide which fragments the drone drops and how long its
+    /// packets are delayed.
     ///
-    /// This method is responsible for sending a `Packet` to the next drone in the routing path. It checks if the drone has a
-    /// valid connection (through its `packet_send` map) to the destination. If the packet is successfully sent, it logs the
-    /// event and returns `true`. If the destination is unreachable or an error occurs during sending, it logs the error
-    /// and forwards the packet to the simulation controller, returning `false` to indicate failure.
+    /// See `DroneCore::with_rng_seed`.
+    #[must_use]
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        let mut seeds = StdRng::seed_from_u64(seed);
+        self.core = self.core.with_rng_seed(seeds.gen());
+        self.rng = StdRng::seed_from_u64(seeds.gen());
+        self
+    }
+
+    /// Sets the delay of the packets sent to a neighbor.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` to be sent. It contains the routing information and the packet type.
-    ///
-    /// # Return Value
-    /// Returns a `bool`:
-    /// - `true`: if the packet was successfully sent to the destination drone.
-    /// - `false`: if there was an error in sending the packet, either due to an unreachable destination or a failure in the
-    ///           communication channel.
-    /// # Behavior
-    /// - The method extracts the next hop in the routing path (`destination`), checks if the destination is available in
-    ///   `packet_send`, and attempts to send the packet to that destination.
-    /// - If the destination is unreachable, it checks if the packet is a `MsgFragment`. If so, it logs the failure without
-    ///   attempting to send to the simulation controller. For other packet types, it forwards the packet to the simulation
-    ///   controller with a `PacketDropped` event.
+    /// - `node_id`: The ID of the neighbor.
+    /// - `delay`: The `LinkDelay` every packet sent to the neighbor waits.
     ///
-    /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// let sent = drone.send_message(packet);
-    /// if sent {
-    ///     println!("Message sent successfully!");
-    /// } else {
-    ///     println!("Failed to send the message.");
-    /// }
-    /// ```
-    fn send_message(&self, packet: Packet) -> bool {
-        let destination = packet.routing_header.hops[packet.routing_header.hop_index];
-        let packet_type = packet.pack_type.clone();
-
-        // Try sending to the destination drone
-        if let Some(sender) = self.packet_send.get(&destination) {
-            match sender.send(packet.clone()) {
-                Ok(()) => {
-                    info!(
-                        "{} [ Drone {} ]: was sent a {} packet to [ Drone {} ]",
-                        "✓".green(),
-                        self.id,
-                        packet_type,
-                        destination
-                    );
-                    self.controller_send
-                        .send(DroneEvent::PacketSent(packet))
-                        .unwrap();
-                    true
-                }
-                Err(e) => {
-                    // In case of an error, forward the packet to the simulation controller
-                    error!(
-                        "{} [ Drone {} ]: Failed to send the {} to [ Drone {} ]: {}",
-                        "✗".red(),
-                        self.id,
-                        packet_type,
-                        destination,
-                        e
-                    );
-
-                    warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
-
-                    self.controller_send
-                        .send(DroneEvent::ControllerShortcut(packet))
-                        .unwrap();
-
-                    warn!(
-                        "└─>{} [ Drone {} ]: {} sent to Simulation Controller",
-                        "!!!".yellow(),
-                        self.id,
-                        packet_type,
-                    );
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_link_delay(mut self, node_id: NodeId, delay: LinkDelay) -> Self {
+        self.link_delays.insert(node_id, delay);
+        self
+    }
 
-                    false
-                }
-            }
-        } else {
-            // Handle case where there is no connection to the destination drone
-            if let PacketType::MsgFragment(fragment) = packet_type {
-                error!(
-                    "{} [ Drone {} ]: does not exist in the path",
-                    "✗".red(),
-                    destination
-                );
-                self.send_nack(
-                    packet,
-                    Some(fragment),
-                    NackType::ErrorInRouting(destination),
-                );
-            } else {
-                error!(
-                    "{} [ Drone {} ]: Failed to send the {}: No connection to [ Drone {} ]",
-                    "✗".red(),
-                    self.id,
-                    packet_type,
-                    destination
-                );
+    /// Sets the delay of the packets sent to the neighbors without a delay of their own.
+    ///
+    /// # Arguments
+    /// - `delay`: The `LinkDelay` every packet waits, `LinkDelay::None` by default.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_default_delay(mut self, delay: LinkDelay) -> Self {
+        self.default_delay = delay;
+        self
+    }
 
-                warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+    /// Sets how the drone shares its time between control packets and fragments waiting to be handled.
+    ///
+    /// # Arguments
+    /// - `weights`: The `PriorityWeights` of the traffic classes.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_priority_weights(mut self, weights: PriorityWeights) -> Self {
+        self.inbox.set_weights(weights);
+        self
+    }
 
-                self.controller_send
-                    .send(DroneEvent::ControllerShortcut(packet))
-                    .unwrap();
+    /// Shares the drone's forwarding capacity between the flows of fragments with deficit round robin.
+    ///
+    /// # Arguments
+    /// - `fair_queuing`: How fragments are grouped in flows, `FairQueuing::Off` by default.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_fair_queuing(mut self, fair_queuing: FairQueuing) -> Self {
+        self.inbox.set_fair_queuing(fair_queuing);
+        self
+    }
 
-                warn!(
-                    "└─>{} [ Drone {} ]: {} sent to Simulation Controller",
-                    "!!!".yellow(),
-                    self.id,
-                    packet_type,
-                );
-            }
+    /// Returns the statistics of the flows of fragments, when fair queuing is on.
+    ///
+    /// # Returns
+    /// The queue depth and the service share of the flows waiting and of the 256 most recently served ones,
+    /// in increasing order of key: the flows idle for the longest are forgotten.
+    #[must_use]
+    pub fn flow_stats(&self) -> Vec<FlowStats> {
+        self.inbox.flow_stats()
+    }
 
-            false
-        }
+    /// Limits the packets or bytes per second the drone sends to a neighbor.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    /// - `shaping`: The `LinkShaping` of the link to the neighbor.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_link_shaping(mut self, node_id: NodeId, shaping: LinkShaping) -> Self {
+        self.shaper.set_link_shaping(node_id, shaping);
+        self
     }
 
-    /// Checks if the drone's ID matches the expected recipient ID in the packet's routing header.
+    /// Limits the packets or bytes per second the drone sends to each neighbor without a shaping of its own.
     ///
-    /// This method compares the current drone's ID with the ID specified in the packet's routing header at the
-    /// current hop index. If the IDs match, the method returns `true`, indicating that the packet is addressed to
-    /// this drone. If the IDs do not match, the method returns `false`, sends a NACK to the previous drone indicating
-    /// an unexpected recipient, and logs an error.
+    /// # Arguments
+    /// - `shaping`: The `LinkShaping` of every other link, which are not shaped by default.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_default_shaping(mut self, shaping: LinkShaping) -> Self {
+        self.shaper.set_default_shaping(shaping);
+        self
+    }
+
+    /// Sets how the drone shares its time between the commands of the simulation controller and the packets.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` that contains the routing header, which includes a list of hops and the current hop index.
+    /// - `policy`: The `SchedulingPolicy` applied while both are waiting, `SchedulingPolicy::Strict` by default.
+    /// - `batch`: How many packets the drone handles in a turn at most, 1 by default.
     ///
-    /// # Return Value
-    /// Returns a `bool`:
-    /// - `true`: if the current drone's ID matches the recipient ID in the packet's routing header.
-    /// - `false`: if the current drone's ID does not match the recipient ID, indicating an incorrect recipient.
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_scheduling(mut self, policy: SchedulingPolicy, batch: usize) -> Self {
+        self.scheduler = Scheduler::new(policy, batch);
+        self
+    }
+
+    /// Makes the drone learn the network from the `path_trace`s of the `FloodRequest`s and `FloodResponse`s
+    /// that cross it.
     ///
-    /// # Behavior
-    /// - The method compares the drone's ID (`self.id`) with the destination ID in the packet's routing header,
-    ///   at the position indicated by `hop_index`.
-    /// - If the IDs match, the method returns `true`, confirming the packet is intended for this drone.
-    /// - If the IDs do not match, it sends a NACK with the error type `UnexpectedRecipient`, logs the mismatch error,
-    ///   and returns `false`.
+    /// The drone also records its own neighbors in the topology, and the drops and NACKs it observes on each
+    /// link, for `Topology::to_dot` and `Topology::to_json`.
     ///
-    /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// let is_correct = drone.check_packet_correct_id(packet);
-    /// if is_correct {
-    ///     println!("Packet addressed correctly to the drone.");
-    /// } else {
-    ///     println!("Packet addressed to the wrong drone.");
-    /// }
-    /// ```
-    fn check_packet_correct_id(&self, packet: Packet) -> bool {
-        if self.id == packet.routing_header.hops[packet.routing_header.hop_index] {
-            true
-        } else {
-            error!(
-                "{} [ Drone {} ]: does not correspond to the Drone indicated by the `hop_index`",
-                "✗".red(),
-                self.id
-            );
-
-            if let PacketType::MsgFragment(frag) = packet.clone().pack_type {
-                self.send_nack(packet, Some(frag), NackType::UnexpectedRecipient(self.id));
-            } else {
-                //self.send_nack(packet.clone(), None, NackType::UnexpectedRecipient(self.id));
-                self.controller_send
-                    .send(DroneEvent::ControllerShortcut(packet))
-                    .unwrap();
-            }
+    /// # Arguments
+    /// - `topology`: The `Topology` to update. The caller keeps a clone of the `Arc` to query it while the
+    ///   drone runs.
+    ///
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_topology(mut self, topology: Arc<Mutex<Topology>>) -> Self {
+        self.topology = Some(topology);
+        self.update_topology(|topology, drone_id, neighbors| {
+            topology.set_local_links(drone_id, neighbors);
+        });
+        self
+    }
 
-            false
-        }
+    /// Limits how many new floods per second the drone forwards for each initiator.
+    ///
+    /// See `DroneCore::with_flood_rate_limit`.
+    #[must_use]
+    pub fn with_flood_rate_limit(mut self, limit: FloodRateLimit) -> Self {
+        self.core = self.core.with_flood_rate_limit(limit);
+        self
     }
 
-    /// Handles the reception of ACK and NACK packets, managing fragment retransmissions and routing updates.
+    /// Limits how far floods travel.
     ///
-    /// This method processes incoming NACK or ACK packets, depending on the packet type. If a NACK is received, it attempts
-    /// to find the corresponding fragment in the drone's buffer and resends it. If the fragment is not found, a NACK is sent
-    /// back to the previous hop. For ACK packets, the method ensures the correct routing and either forwards the packet or
-    /// updates the routing header accordingly.
+    /// See `DroneCore::with_max_flood_hops`.
+    #[must_use]
+    pub fn with_max_flood_hops(mut self, max_hops: usize) -> Self {
+        self.core = self.core.with_max_flood_hops(max_hops);
+        self
+    }
+
+    /// Sets how many packets can wait for a congested neighbor, and what happens to the others.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` that contains the routing header and packet type (NACK, ACK, etc.) to be processed.
-    ///
-    /// # Behavior
-    /// - **If the packet type is a NACK**:
-    ///   - It checks the drone's buffer for the requested fragment using the `session_id` and `fragment_index`.
-    ///   - If the fragment is found, it is resent by reversing the routing path and creating a new packet with the fragment.
-    ///   - If the fragment is not found, a NACK is sent to the previous node, indicating that the fragment could not be found.
-    /// - **If the packet type is an ACK**:
-    ///   - It increments the hop index and forwards the packet to the next hop, if the index is valid.
-    ///   - If the hop index is at the end of the route, it logs an error.
+    /// - `max_len`: The length of the outbound queue of each neighbor, 64 by default. With 0, a packet a
+    ///   neighbor can't take is dropped right away.
+    /// - `policy`: The `OverflowPolicy` applied when the queue is full.
     ///
-    /// # Panic
-    /// - If the hop index is greater than or equal to the number of hops, it causes a panic with the message "Source is not a client!".
+    /// # Returns
+    /// The same `RustasticDrone`, for chaining after `RustasticDrone::new`.
+    #[must_use]
+    pub fn with_outbound_queue(mut self, max_len: usize, policy: OverflowPolicy) -> Self {
+        self.backlog.set_limits(max_len, policy);
+        self
+    }
+
+    /// Sets when the drone gives up on a neighbor it can't send to anymore, e.g. because its receiver was dropped.
     ///
-    /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// drone.handle_ack_nack(packet);
-    /// ```
-    fn handle_ack_nack(&mut self, packet: Packet) {
-        if packet.routing_header.hop_index >= packet.routing_header.hops.len() {
-            error!(
-                "{} Invalid hop index increment detected in [ Drone: {} ] for header of Packet [ session_id: {} ]",
-                "✗".red(),
-                self.id,
-                packet.session_id
-            );
-            return;
-        }
-        if let PacketType::Nack(nack) = packet.clone().pack_type {
-            warn!(
-                "{} [ Drone {} ]: received a {}",
-                "!!!".yellow(),
-                self.id,
-                packet.pack_type,
-            );
+    /// See `DroneCore::with_liveness`. A dead neighbor is removed from the transport as well, and reported with a
+    /// `NeighborDead` event.
+    #[must_use]
+    pub fn with_liveness(mut self, policy: LivenessPolicy) -> Self {
+        self.core = self.core.with_liveness(policy);
+        self
+    }
 
-            warn!(
-                "\n├─>{} Checking [ Drone {} ] buffer...",
-                "!!!".yellow(),
-                self.id
-            );
+    /// Returns the ID of the drone.
+    #[must_use]
+    pub fn id(&self) -> NodeId {
+        self.core.id()
+    }
 
-            // Check if the fragment is in the buffer
-            if let Some(new_packet) = self
-                .buffer
-                .get_fragment(packet.clone().session_id, nack.fragment_index)
-            {
-                info!(
-                    "├─>{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ]  was found in the buffer",
-                    "✓".green(),
-                    nack.fragment_index,
-                    packet.session_id
-                );
+    /// Returns the statistics of the floods handled by the drone, see `DroneCore::flood_stats`.
+    #[must_use]
+    pub fn flood_stats(&self) -> FloodStats {
+        self.core.flood_stats()
+    }
 
-                self.send_message(new_packet);
+    /// Returns the buffer of the fragments forwarded by the drone, kept by its `DroneCore`.
+    #[must_use]
+    pub fn buffer(&self) -> &PacketBuffer {
+        &self.core.buffer
+    }
 
-                info!("└─>{} The Packet was sent", "✓".green());
-            } else {
-                // Send a nack to the previous node
-                self.send_message(packet);
-            }
-        } else {
-            info!(
-                "{} [ Drone {} ]: received a {}",
-                "i".blue(),
-                self.id,
-                packet.pack_type,
-            );
-            self.send_message(packet);
-        }
+    /// Returns the buffer of the fragments forwarded by the drone, to edit it or its maximum size.
+    pub fn buffer_mut(&mut self) -> &mut PacketBuffer {
+        &mut self.core.buffer
     }
 
-    /// Handles the reception of a fragmented packet, either dropping it based on the Packet Drop Rate (PDR)
-    /// or forwarding and storing it in the drone's buffer.
+    /// Returns the IDs of the neighbors declared dead, see `DroneCore::dead_neighbors`.
+    #[must_use]
+    pub fn dead_neighbors(&self) -> Vec<NodeId> {
+        self.core.dead_neighbors()
+    }
+
+    /// Handles an incoming packet with the `DroneCore` and performs the resulting actions.
     ///
-    /// This method processes a packet fragment by either dropping it, based on the drone's PDR, or forwarding it
-    /// to the next hop. If the fragment is not dropped, it is added to the drone's buffer to be potentially retransmitted
-    /// later. If the fragment is dropped, a NACK is sent to notify the sender of the packet drop.
+    /// See `DroneCore::handle_packet` for the protocol logic.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` containing the routing header and session ID, which the fragment belongs to.
-    /// - `fragment`: The `Fragment` that is part of the `packet` and contains the fragmented data.
-    ///
-    /// # Behavior
-    /// - **If the fragment is dropped** (based on the `check_drop_fragment` method):
-    ///   - A message is printed to indicate that the fragment was dropped by the drone.
-    ///   - A NACK is sent to the previous node, indicating that the fragment was dropped.
-    /// - **If the fragment is not dropped**:
-    ///   - The fragment is added to the drone's buffer using its `session_id` and the fragment's `fragment_index`.
-    ///   - A message is printed to indicate that the fragment was successfully added to the buffer.
-    ///   - The `packet` is forwarded to the next hop by calling `send_message`.
+    /// - `packet`: The incoming `Packet` that needs to be processed.
     ///
     /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// let fragment = Fragment { /* fragment data */ };
-    /// drone.handle_fragment(packet, fragment);
+    /// ```ignore
+    /// // Assuming `packet` is a received packet to handle
+    /// drone.handle_packet(packet);
     /// ```
-    fn handle_fragment(&mut self, mut packet: Packet, fragment: Fragment) {
-        if self.check_drop_fragment() {
-            warn!(
-                "{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ] has been dropped by [ Drone {} ]",
-                "!!!".yellow(),
-                fragment.fragment_index,
-                packet.session_id,
-                self.id
-            );
-            self.send_nack(packet.clone(), Some(fragment), NackType::Dropped);
-            packet.routing_header.decrease_hop_index();
-            self.controller_send
-                .send(DroneEvent::PacketDropped(packet))
-                .unwrap();
-        } else {
-            // Add the fragment to the buffer
-            info!(
-                "{} [ Drone {} ]: forwarded the the fragment [ fragment_index: {} ] of the Packet [ session_id: {} ]",
-                "✓".green(),
-                self.id,
-                fragment.fragment_index,
-                packet.session_id
-            );
-
-            self.buffer.add_fragment(
-                packet.clone().session_id,
-                fragment.fragment_index,
-                packet.clone(),
-            );
-
-            warn!(
-                "└─>{} Fragment was added to the [ Drone {} ] buffer",
-                "!!!".yellow(),
-                self.id
-            );
-
-            self.send_message(packet);
+    pub fn handle_packet(&mut self, packet: Packet) {
+        self.core.set_clock(self.started.elapsed());
+        let actions = self.core.handle_packet(packet);
+        // A refused or misrouted flood isn't sent to any neighbor: only the trace of an accepted one is learned
+        let sent = actions.iter().find_map(|action| match action {
+            DroneAction::Send { packet, .. } => Some(packet),
+            _ => None,
+        });
+        if let Some(packet) = sent {
+            self.learn_topology(packet);
         }
+        self.perform(actions);
     }
 
-    /// Sends a NACK (Negative Acknowledgment) to the previous hop or to the simulation controller in case of an error.
-    ///
-    /// This function sends a NACK message back to the previous drone in the routing path when there is a problem with
-    /// the packet or its fragment, such as a routing error or dropped packet. If the NACK cannot be sent to the
-    /// previous drone due to an issue (e.g., no connection), the NACK is sent to the simulation controller to notify
-    /// of the failure.
+    /// Updates the learned `Topology`, if any, with the `path_trace` of a flood.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` that needs to be acknowledged (or nacked). This packet is either a regular packet or a fragment of a larger message.
-    /// - `fragment`: An optional `Fragment` object that contains specific fragment data, if the NACK is related to a dropped fragment.
-    /// - `nack_type`: The `NackType` representing the type of error or problem that occurred. This can be a dropped packet, an unexpected recipient, etc.
+    /// - `packet`: A packet the drone sends after the core accepted its input, e.g. a `FloodResponse` it
+    ///   forwards, or the `FloodRequest` or `FloodResponse` it sends for a request: its trace already ends
+    ///   with the drone.
     ///
     /// # Behavior
-    /// - The function reverses the routing header to determine the previous hop in the routing path and attempts to send the NACK to that drone.
-    /// - If the NACK is related to a fragment (i.e., the `fragment` argument is `Some`), the function updates the NACK's fragment index and type accordingly.
-    /// - If the NACK is successfully sent to the previous drone, a message is logged to indicate the successful transmission.
-    /// - If the drone cannot send the NACK to the previous hop (e.g., no connection), it sends the NACK to the simulation controller and logs the event.
-    ///
-    /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// let fragment = Some(Fragment { /* fragment data */ });
-    /// let nack_type = NackType::Dropped;
-    /// drone.send_nack(packet, fragment, nack_type);
-    /// ```
-    fn send_nack(&self, mut packet: Packet, fragment: Option<Fragment>, nack_type: NackType) {
-        packet
-            .routing_header
-            .hops
-            .drain(packet.routing_header.hop_index..);
-
-        if let NackType::UnexpectedRecipient(id) = nack_type {
-            packet.routing_header.hops.push(id);
-        }
-
-        packet.routing_header.hops.reverse();
-
-        packet.routing_header.hop_index = 1;
-
-        let prev_hop = packet.routing_header.current_hop().unwrap();
-
-        // Attempt to send the NACK to the previous hop
-
-        let nack = Nack {
-            fragment_index: match fragment {
-                Some(frag) => frag.fragment_index,
-                None => 0,
-            }, // Default fragment index for non-fragmented NACKs
-            nack_type,
+    /// - The other packets are ignored. A NACK is not counted here but once it is sent, so that a topology
+    ///   shared by the drones counts it once per link.
+    fn learn_topology(&self, packet: &Packet) {
+        let path_trace = match &packet.pack_type {
+            PacketType::FloodRequest(flood_request) => flood_request.path_trace.clone(),
+            PacketType::FloodResponse(flood_response) => flood_response.path_trace.clone(),
+            _ => return,
         };
 
-        packet.pack_type = PacketType::Nack(nack);
-
-        if let Some(sender) = self.packet_send.get(&prev_hop) {
-            // Send the NACK to the previous hop
-            match sender.send(packet.clone()) {
-                Ok(()) => {
-                    warn!(
-                        "{} Nack was sent from [ Drone {} ] to [ Drone {} ]",
-                        "!!!".yellow(),
-                        self.id,
-                        prev_hop
-                    );
-
-                    self.controller_send
-                        .send(DroneEvent::PacketSent(packet))
-                        .unwrap();
-                }
-                Err(e) => {
-                    // Handle failure to send the NACK, send to the simulation controller instead
-                    warn!(
-                        "{} [ Drone {} ]: Failed to send the Nack to [ Drone {} ]: {}",
-                        "✗".red(),
-                        self.id,
-                        prev_hop,
-                        e
-                    );
-
-                    warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
-
-                    //there is an error in sending the packet, the drone should send the packet to the simulation controller
-                    self.controller_send
-                        .send(DroneEvent::ControllerShortcut(packet))
-                        .unwrap();
-                    warn!(
-                        "└─>{} [ Drone {} ]: sent A Nack to the Simulation Controller",
-                        "!!!".yellow(),
-                        self.id
-                    );
-                }
-            }
-        } else {
-            // If no connection to the previous hop, send the NACK to the simulation controller
-            error!(
-                "{} [ Drone {} ]: Failed to send the Nack: No connection to {}",
-                "✗".red(),
-                self.id,
-                prev_hop
-            );
-
-            warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+        self.update_topology(|topology, _, _| topology.observe(&path_trace, Instant::now()));
+    }
 
-            // Create the NACK (same logic as above)
+    /// Applies `update` to the learned `Topology`, if any, with the ID and the neighbors of the drone.
+    fn update_topology(&self, update: impl FnOnce(&mut Topology, NodeId, Vec<NodeId>)) {
+        let Some(topology) = &self.topology else {
+            return;
+        };
 
-            // Send to the simulation controller
-            self.controller_send
-                .send(DroneEvent::ControllerShortcut(packet))
-                .unwrap();
-            warn!(
-                "└─>{} [ Drone {} ]: sent A Nack to the Simulation Controller",
+        match topology.lock() {
+            Ok(mut topology) => update(&mut topology, self.core.id(), self.transport.neighbors()),
+            Err(_) => warn!(
+                "{} [ Drone {} ]: can't update the topology, its lock is poisoned",
                 "!!!".yellow(),
-                self.id
-            );
+                self.core.id()
+            ),
         }
     }
 
-    /// Checks if the destination of a packet is a neighboring drone.
+    /// Handles a command from the simulation controller with the `DroneCore` and performs the resulting actions.
     ///
-    /// This function checks whether the destination drone, as indicated in the packet's routing header,
-    /// is a valid neighbor of the current drone. It does so by verifying if there is a connection to
-    /// the destination drone in the `packet_send` map, which holds the communication channels for sending
-    /// packets to neighboring drones.
+    /// See `DroneCore::handle_command` for the protocol logic.
     ///
     /// # Arguments
-    /// - `packet`: The `Packet` whose destination is being checked. The packet contains a routing header
-    ///   that specifies the destination drone and the current hop index.
+    /// - `command`: The `DroneCommand` to handle.
     ///
     /// # Returns
-    /// - `true` if the destination of the packet is a valid neighbor of the current drone (i.e., there is
-    ///   a communication channel to the destination).
-    /// - `false` if the destination is not a neighbor (i.e., there is no communication channel to the destination).
-    ///
-    /// # Example
-    /// ```rust
-    /// let packet = Packet { /* packet data */ };
-    /// let is_neighbor = drone.check_neighbor(packet);
-    /// if is_neighbor {
-    ///     println!("The destination is a neighbor.");
-    /// } else {
-    ///     println!("The destination is not a neighbor.");
-    /// }
-    /// ```
-    fn check_neighbor(&self, packet: &Packet) -> bool {
-        let destination = packet.routing_header.hops[packet.routing_header.hop_index];
-        self.packet_send.contains_key(&destination)
+    /// `false` if the drone crashed and must stop running, `true` otherwise.
+    pub fn handle_command(&mut self, command: DroneCommand) -> bool {
+        let actions = self.core.handle_command(command);
+        self.perform(actions)
     }
 
-    // Determines if a packet fragment should be dropped based on the Packet Drop Rate (PDR).
+    /// Performs the actions returned by the `DroneCore`, in order.
     ///
-    /// This function simulates the packet drop behavior based on the current drone's Packet Drop Rate (PDR).
-    /// It generates a random number between 1 and 100 and compares it to the scaled PDR (multiplied by 100).
-    /// If the random value is less than or equal to the scaled PDR, the fragment is considered to be dropped.
+    /// # Arguments
+    /// - `actions`: The `DroneAction`s to perform.
     ///
     /// # Returns
-    /// - `true` if the packet fragment should be dropped based on the current PDR.
-    /// - `false` if the packet fragment should not be dropped.
-    ///
-    /// # Example
-    /// ```rust
-    /// let should_drop = drone.check_drop_fragment();
-    /// if should_drop {
-    ///     println!("The packet fragment will be dropped.");
-    /// } else {
-    ///     println!("The packet fragment will not be dropped.");
-    /// }
-    /// ```
-    fn check_drop_fragment(&self) -> bool {
-        let mut rng = rand::thread_rng();
-        let val = rng.gen_range(1f32..=100f32);
-        val <= self.pdr * 100f32
+    /// `false` if one of the actions is a `DroneAction::Crash`, `true` otherwise.
+    fn perform(&mut self, actions: Vec<DroneAction>) -> bool {
+        let mut running = true;
+
+        for action in actions {
+            match action {
+                DroneAction::Send { to, packet } => self.delay_packet(to, packet),
+                DroneAction::Shortcut(packet) => self
+                    .controller_send
+                    .send(DroneEvent::ControllerShortcut(packet))
+                    .unwrap(),
+                DroneAction::Dropped(packet) => {
+                    // The packet is reported as received: the drop happened on the way to the next hop
+                    let hops = &packet.routing_header.hops;
+                    if let Some(next_hop) = hops.get(packet.routing_header.hop_index + 1) {
+                        self.update_topology(|topology, drone_id, _| {
+                            topology.record_drop(drone_id, *next_hop);
+                        });
+                    }
+                    self.controller_send
+                        .send(DroneEvent::PacketDropped(packet))
+                        .unwrap();
+                }
+                DroneAction::Event(event) => self.send_event(event),
+                DroneAction::Connect(node_id, sender) => {
+                    self.transport.add_sender(node_id, sender);
+                    self.update_topology(Topology::set_local_links);
+                }
+                DroneAction::Disconnect(node_id) => {
+                    self.transport.remove_sender(node_id);
+                    self.update_topology(Topology::set_local_links);
+                }
+                DroneAction::Crash => {
+                    // The packets still waiting for their link can't leave early: they are lost with the drone
+                    let waiting = self.delay_queue.drain().into_iter();
+                    for (to, packet) in waiting.chain(self.shaper.drain()) {
+                        self.drop_on_crash(to, packet);
+                    }
+                    self.send_backlog();
+                    // The congested neighbors lose what they could not take
+                    for (to, packet) in self.backlog.drain() {
+                        self.core.forget_undelivered(&packet);
+                        self.report_overflow(to, packet);
+                    }
+                    running = false;
+                }
+            }
+        }
+
+        running
     }
 
-    /// Handles an incoming `FloodRequest` packet and processes it accordingly.
-    ///
-    /// This function handles the logic for processing a `FloodRequest` packet that has been received by the drone.
-    /// It checks if the flood request has already been received, processes the path trace, and either forwards
-    /// the flood request to neighbors or responds with a `FloodResponse`. The flood request is a type of routing
-    /// protocol used to propagate information across the network of drones, and the drone can either forward or
-    /// respond based on its state.
+    /// Sends a packet to a neighbor once the delay of their link has elapsed.
     ///
     /// # Arguments
-    /// - `flood_request`: The `FloodRequest` packet containing information about the flood and its path trace.
-    /// - `packet`: The full packet that contains the flood request and additional metadata, such as the routing header.
-    ///
-    /// # Behavior:
-    /// - The function first determines the last node in the path trace to identify the drone that sent the request.
-    /// - It then checks if the flood request has already been received based on its `flood_id` and `initiator_id`.
-    /// - If the request has already been processed, the drone sends a `FloodResponse` back to the previous node
-    ///   indicating that the flood request has already been received.
-    /// - If the drone has no neighbors to forward the request to (except the previous node), it sends a `FloodResponse`
-    ///   to the previous node indicating that no further hops are available.
-    /// - If the drone has neighbors to forward the request to, it sends the `FloodRequest` to each neighbor except
-    ///   the one that sent the request (the `prev_node`).
-    ///
-    /// # Example:
-    /// ```rust
-    /// drone.handle_flood_request(flood_request, packet);
-    /// ```
-    fn handle_flood_request(&self, mut flood_request: FloodRequest, packet: &Packet) {
-        // Determine the previous node that sent the packet
-        let prev_node = if let Some(node) = flood_request.path_trace.last() {
-            node.0
+    /// - `destination`: The ID of the neighbor to send the packet to.
+    /// - `packet`: The `Packet` to send.
+    fn delay_packet(&mut self, destination: NodeId, packet: Packet) {
+        let delay = self
+            .link_delays
+            .get(&destination)
+            .unwrap_or(&self.default_delay)
+            .sample(&mut self.rng);
+
+        if delay.is_zero() {
+            self.shape_packet(destination, packet);
         } else {
-            error!("A drone can't be the first node in the path-trace.");
-            return;
-        };
-
-        // Add the current drone to the path-trace
-        flood_request.path_trace.push((self.id, NodeType::Drone));
-
-        // Check if the flood request has already been processed
-        if self
-            .flood_id_received
-            .contains(&(flood_request.flood_id, flood_request.initiator_id))
-        {
-            // If it has been processed, send a FloodResponse to the previous node
-
-            let mut new_hops: Vec<u8> = flood_request
-                .clone()
-                .path_trace
-                .into_iter()
-                .map(|(id, _ntype)| id)
-                .collect();
-
-            new_hops.reverse();
+            self.delay_queue
+                .push(Instant::now() + delay, destination, packet);
+        }
+    }
 
-            if new_hops.last() != Some(&flood_request.initiator_id) {
-                new_hops.push(flood_request.initiator_id);
-            }
+    /// Sends the packets of the delay queue that are due.
+    fn send_due_packets(&mut self) {
+        let now = Instant::now();
+        while let Some((destination, packet)) = self.delay_queue.pop_due(now) {
+            self.shape_packet(destination, packet);
+        }
+    }
 
-            let new_routing_header = SourceRoutingHeader {
-                hop_index: 1,
-                hops: new_hops,
-            };
-
-            // Send the FloodResponse to the previous node
-            self.send_flood_response(
-                prev_node,
-                &flood_request,
-                new_routing_header,
-                packet.session_id,
-                format!(
-                    "{} [ Drone {} ]: has already received a FloodRequest with flood_id: {}",
-                    "!!!".yellow(),
-                    self.id,
-                    flood_request.flood_id
-                )
-                .as_str(),
-            );
-        } else if self.packet_send.len() == 1 {
-            // If the drone has no neighbors except the previous node
-            let mut new_hops: Vec<u8> = flood_request
-                .clone()
-                .path_trace
-                .into_iter()
-                .map(|(id, _ntype)| id)
-                .collect();
-
-            new_hops.reverse();
-
-            let new_routing_header = SourceRoutingHeader {
-                hop_index: 1,
-                hops: new_hops,
-            };
-
-            // Send a FloodResponse indicating no further neighbors to forward the request
-            self.send_flood_response(
-                prev_node,
-                &flood_request,
-                new_routing_header,
-                packet.session_id,
-                format!(
-                    "{} [ Drone {} ]: doesn't have any other neighbors to send the FloodRequest with flood_id: {}",
+    /// Sends a packet to a neighbor once its link has room, see `RustasticDrone::with_link_shaping`.
+    ///
+    /// # Arguments
+    /// - `destination`: The ID of the neighbor to send the packet to.
+    /// - `packet`: The `Packet` to send.
+    ///
+    /// # Behavior
+    /// - If the link is not shaped, or its bucket holds enough tokens, the packet is sent right away.
+    /// - Otherwise the packet waits for the link, or is dropped, according to its `SaturationPolicy`. A dropped
+    ///   packet is reported with a `ShapingDrop` event, and shortcut to the simulation controller if it is an
+    ///   `Ack`, a `Nack` or a `FloodResponse`.
+    /// - A dropped fragment is NACKed to its source and reported with a `PacketDropped` event, see
+    ///   `DroneCore::nack_undelivered`.
+    fn shape_packet(&mut self, destination: NodeId, packet: Packet) {
+        match self.shaper.shape(destination, packet, Instant::now()) {
+            Shaped::Send(packet) => self.send_packet(destination, packet),
+            Shaped::Queued => {}
+            Shaped::Dropped(packet) => {
+                warn!(
+                    "{} [ Drone {} ]: The link to [ Drone {} ] is saturated, the {} was dropped",
                     "!!!".yellow(),
-                    self.id,
-                    flood_request.flood_id
-                )
-                    .as_str(),
-            );
-        } else {
-            // Forward the FloodRequest to all neighbors except the previous node
-            for neighbor in self
-                .packet_send
-                .iter()
-                .filter(|neighbor| *neighbor.0 != prev_node)
-            {
-                self.send_flood_request(
-                    neighbor,
-                    &flood_request,
-                    packet.routing_header.clone(),
-                    packet.session_id,
+                    self.core.id(),
+                    destination,
+                    packet.pack_type
                 );
 
-                info!(
-                    "{} [ Drone {} ]: sent a FloodRequest with flood_id: {} to the [ Drone {} ]",
-                    "✓".green(),
-                    self.id,
-                    flood_request.flood_id,
-                    neighbor.0
-                );
+                // A dropped fragment is NACKed, for its source to send it again
+                let actions = self.core.nack_undelivered(packet.clone());
+                self.perform(actions);
+                self.report_dropped_fragment(&packet);
+
+                self.shortcut_lost(&packet);
+                self.update_topology(|topology, drone_id, _| {
+                    topology.record_drop(drone_id, destination);
+                });
+                self.send_event(RustasticEvent::ShapingDrop {
+                    drone_id: self.core.id(),
+                    neighbor_id: destination,
+                    packet: Box::new(packet),
+                });
             }
         }
     }
 
-    /// Handles the incoming `FloodResponse` packet, processes it, and sends it back to the appropriate drone.
-    ///
-    /// This function processes a received `FloodResponse` packet by forwarding it to the next hop in the path.
-    /// If the next hop is unavailable or if an error occurs while sending the response, the packet is sent to the
-    /// simulation controller instead. It plays a critical role in routing flood responses back through the network
-    /// of drones.
+    /// Drops a packet that was still waiting for its link when the drone crashed.
     ///
     /// # Arguments
-    /// - `flood_response`: The `FloodResponse` packet that was received. This packet contains the information
-    ///   about the flood response and its associated metadata.
-    /// - `packet`: The full packet, which contains additional information like the routing header and session ID.
+    /// - `destination`: The ID of the neighbor the packet was for.
+    /// - `packet`: The `Packet` to drop.
     ///
-    /// # Behavior:
-    /// - The function attempts to forward the `FloodResponse` to the next drone in the path specified by the
-    ///   `routing_header` in the `packet`.
-    /// - If the next hop is available in `packet_send`, the packet is forwarded to the next drone.
-    /// - If the next hop is not available, or if there is an error in sending the packet, the packet is sent to
-    ///   the simulation controller to handle the issue.
-    ///
-    /// # Example:
-    /// ```rust
-    /// drone.handle_flood_response(flood_response, packet);
-    /// ```
-    fn handle_flood_response(&self, flood_response: &FloodResponse, packet: &Packet) {
-        let new_routing_header = packet.routing_header.clone();
-
-        // Prepare a new packet to send the flood response back
-        let new_packet = Packet {
-            pack_type: PacketType::FloodResponse(flood_response.clone()),
-            routing_header: new_routing_header.clone(),
-            session_id: packet.session_id,
-        };
+    /// # Behavior
+    /// - A fragment is NACKed to its source right away, bypassing the delays and the shaping of the link back,
+    ///   and reported with a `PacketDropped` event.
+    /// - An `Ack`, a `Nack` or a `FloodResponse` is shortcut to the simulation controller.
+    fn drop_on_crash(&mut self, destination: NodeId, packet: Packet) {
+        warn!(
+            "{} [ Drone {} ]: crashed before the {} left for [ Drone {} ], it was dropped",
+            "!!!".yellow(),
+            self.core.id(),
+            packet.pack_type,
+            destination
+        );
 
-        // Try to send the FloodResponse to the next hop in the routing path
-        if let Some(sender) = self
-            .packet_send
-            .get(&new_routing_header.hops[new_routing_header.hop_index])
-        {
-            match sender.send(new_packet.clone()) {
-                Ok(()) => {
-                    info!(
-                        "{} [ Drone {} ]: sent a FloodResponse with flood_id: {} to [ Drone {} ]",
-                        "✓".green(),
-                        self.id,
-                        flood_response.flood_id,
-                        &new_routing_header.hops[new_routing_header.hop_index]
-                    );
-                    self.controller_send
-                        .send(DroneEvent::PacketSent(packet.clone()))
-                        .unwrap();
+        let actions = self.core.nack_undelivered(packet.clone());
+        for action in actions {
+            match action {
+                DroneAction::Send { to, packet: nack } => {
+                    if let Some(nack) = self.try_send_packet(to, nack) {
+                        self.shortcut_lost(&nack);
+                    }
                 }
-                Err(e) => {
-                    error!(
-                        "{} [ Drone {} ]: failed to send the FloodResponse to the Drone {}: {}",
-                        "✗".red(),
-                        self.id,
-                        &new_routing_header.hops[new_routing_header.hop_index],
-                        e
-                    );
-
-                    warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
-
-                    self.controller_send
-                        .send(DroneEvent::ControllerShortcut(new_packet))
-                        .unwrap();
-
-                    warn!(
-                        "└─>{} [ Drone {} ]: sent the FloodResponse to the Simulation Controller",
-                        "!!!".yellow(),
-                        self.id
-                    );
+                action => {
+                    self.perform(vec![action]);
                 }
             }
-        } else {
-            // If the next hop is unavailable, send the packet to the simulation controller
-            error!(
-                "{} [ Drone {} ]: failed to send the FloodResponse: No connection to [ Drone {} ]",
-                "✗".red(),
-                self.id,
-                &new_routing_header.hops[new_routing_header.hop_index]
-            );
+        }
+        self.report_dropped_fragment(&packet);
 
-            warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+        self.shortcut_lost(&packet);
+        self.update_topology(|topology, drone_id, _| {
+            topology.record_drop(drone_id, destination);
+        });
+    }
 
-            // Send the packet to the simulation controller
+    /// Reports a fragment the drone dropped on its way to the next hop with a `PacketDropped` event, like a
+    /// fragment dropped on receipt: its `hop_index` points to this drone. The other packets are ignored.
+    ///
+    /// # Arguments
+    /// - `packet`: The dropped `Packet`, its `hop_index` pointing to the next hop.
+    fn report_dropped_fragment(&self, packet: &Packet) {
+        if matches!(packet.pack_type, PacketType::MsgFragment(_)) {
+            let mut dropped = packet.clone();
+            dropped.routing_header.decrease_hop_index();
             self.controller_send
-                .send(DroneEvent::ControllerShortcut(new_packet))
+                .send(DroneEvent::PacketDropped(dropped))
                 .unwrap();
+        }
+    }
 
-            warn!(
-                "└─>{} [ Drone {} ]: sent the FloodResponse to the Simulation Controller",
-                "!!!".yellow(),
-                self.id
-            );
+    /// Sends the shaped packets whose link has room.
+    fn send_shaped_packets(&mut self) {
+        for (destination, packet) in self.shaper.pop_ready(Instant::now()) {
+            self.send_packet(destination, packet);
         }
     }
 
-    /// Sends a `FloodRequest` packet to a specified destination drone.
-    ///
-    /// This function creates a new `FloodRequest` packet and sends it to the specified destination drone.
-    /// The `FloodRequest` contains information about the flood ID, initiator ID, and path trace. The packet
-    /// is routed according to the provided `routing_header` and `session_id`.
+    /// Sends a packet to a neighbor, or queues it if the neighbor is congested.
     ///
     /// # Arguments
-    /// - `dest_node`: A tuple containing the destination drone's ID (`NodeId`) and the corresponding sender
-    ///   (`Sender<Packet>`) to send the packet to.
-    /// - `flood_request`: The `FloodRequest` that is being sent, containing the flood ID, initiator ID, and the
-    ///   path trace up to this point in the flood.
-    /// - `routing_header`: The `SourceRoutingHeader` which provides routing information for this packet.
-    /// - `session_id`: A unique session ID that helps track the packet across the network.
+    /// - `destination`: The ID of the neighbor to send the packet to.
+    /// - `packet`: The `Packet` to send.
     ///
-    /// # Behavior:
-    /// - The function creates a new `FloodRequest` packet based on the provided `flood_request` and `routing_header`.
-    /// - It then attempts to send this packet to the specified destination drone using the provided sender (`Sender<Packet>`).
-    /// - If sending the packet is successful, a success message is logged. If there is an error, an error message is logged.
+    /// # Behavior
+    /// - The packets for a congested neighbor wait behind the ones already queued, to keep their order.
+    /// - A packet that the neighbor's channel refuses starts its outbound queue, see `RustasticDrone::queue_packet`.
+    fn send_packet(&mut self, destination: NodeId, packet: Packet) {
+        if self.backlog.is_congested(destination) {
+            self.queue_packet(destination, packet);
+        } else if let Some(packet) = self.try_send_packet(destination, packet) {
+            self.queue_packet(destination, packet);
+        }
+    }
+
+    /// Queues a packet for a congested neighbor.
     ///
-    /// # Example:
-    /// ```rust
-    /// drone.send_flood_request((&destination_id, &destination_sender), &flood_request, routing_header, session_id);
-    /// ```
-    fn send_flood_request(
-        &self,
-        dest_node: (&NodeId, &Sender<Packet>),
-        flood_request: &FloodRequest,
-        routing_header: SourceRoutingHeader,
-        session_id: u64,
-    ) {
-        let flood_id = flood_request.flood_id;
-        let new_flood_request = FloodRequest {
-            flood_id,
-            initiator_id: flood_request.initiator_id,
-            path_trace: flood_request.path_trace.clone(),
-        };
+    /// # Arguments
+    /// - `destination`: The ID of the congested neighbor.
+    /// - `packet`: The `Packet` to send.
+    ///
+    /// # Behavior
+    /// - The first packet queued for a neighbor is reported with a `NeighborCongested` event.
+    /// - If the queue is full, a packet is dropped according to the `OverflowPolicy`, see
+    ///   `RustasticDrone::report_overflow`. With `OverflowPolicy::NackUpstream`, a dropped fragment is NACKed.
+    /// - A dropped fragment is forgotten by the `DroneCore`, so that its retransmission is not discarded as a
+    ///   duplicate, see `DroneCore::forget_undelivered`.
+    fn queue_packet(&mut self, destination: NodeId, packet: Packet) {
+        match self.backlog.push(destination, packet) {
+            Backlogged::Queued { congested: true } => {
+                warn!(
+                    "{} [ Drone {} ]: [ Drone {} ] is congested, its packets are queued",
+                    "!!!".yellow(),
+                    self.core.id(),
+                    destination
+                );
+                self.send_event(RustasticEvent::NeighborCongested {
+                    drone_id: self.core.id(),
+                    neighbor_id: destination,
+                });
+            }
+            Backlogged::Queued { congested: false } => {}
+            Backlogged::Dropped(packet) => {
+                if self.backlog.policy() == OverflowPolicy::NackUpstream {
+                    let actions = self.core.nack_undelivered(packet.clone());
+                    self.perform(actions);
+                } else {
+                    self.core.forget_undelivered(&packet);
+                }
+                self.report_overflow(destination, packet);
+            }
+        }
+    }
 
-        let new_packet = Packet {
-            pack_type: PacketType::FloodRequest(new_flood_request),
-            routing_header,
-            session_id,
-        };
+    /// Retries the packets queued for the congested neighbors, in order, until a neighbor refuses one again.
+    ///
+    /// A neighbor whose queue is emptied is reported with a `NeighborRecovered` event.
+    fn send_backlog(&mut self) {
+        for destination in self.backlog.congested() {
+            while let Some(packet) = self.backlog.pop(destination) {
+                if let Some(packet) = self.try_send_packet(destination, packet) {
+                    self.backlog.push_front(destination, packet);
+                    break;
+                }
+            }
 
-        match dest_node.1.send(new_packet.clone()) {
-            Ok(()) => {
+            if self.backlog.clear_if_empty(destination) {
                 info!(
-                    "{} [ Drone {} ]: sent the FloodRequest with flood_id: {} sent to [ Drone {} ]",
+                    "{} [ Drone {} ]: [ Drone {} ] is not congested anymore",
                     "✓".green(),
-                    self.id,
-                    flood_id,
-                    dest_node.0
+                    self.core.id(),
+                    destination
                 );
-                self.controller_send
-                    .send(DroneEvent::PacketSent(new_packet))
-                    .unwrap();
+                self.send_event(RustasticEvent::NeighborRecovered {
+                    drone_id: self.core.id(),
+                    neighbor_id: destination,
+                });
             }
-            Err(e) => error!(
-                "{} [ Drone {} ]: failed to send FloodRequest to the [ Drone {} ]: {}",
-                "✗".red(),
-                self.id,
-                dest_node.0,
-                e
-            ),
-        };
+        }
     }
 
-    /// Sends a `FloodResponse` packet to a specified destination drone.
-    ///
-    /// This function creates a `FloodResponse` packet and sends it to a destination drone. The response includes
-    /// the flood ID and the path trace that was followed during the flood request. The packet is routed according
-    /// to the provided `routing_header` and is identified by the provided `session_id`.
+    /// Reports a packet dropped for a congested neighbor with an `OverflowDrop` event.
     ///
     /// # Arguments
-    /// - `dest_node`: The ID of the destination drone to send the response to.
-    /// - `flood_request`: The `FloodRequest` that prompted this response. It contains the `flood_id` and `path_trace`.
-    /// - `routing_header`: The `SourceRoutingHeader` containing routing information for the packet.
-    /// - `session_id`: A unique session ID used to track the packet across the network.
-    /// - `reason`: A description or reason for sending the response. This will be logged along with the action.
-    ///
-    /// # Behavior:
-    /// - The function creates a `FloodResponse` packet based on the `flood_request` and the `routing_header`.
-    /// - The response is then sent to the destination drone using the provided `dest_node`.
-    /// - If the packet is successfully sent, a success message is logged. If there is an error in sending the packet,
-    ///   the packet is sent to the simulation controller to indicate a failure.
-    ///
-    /// # Example:
-    /// ```rust
-    /// drone.send_flood_response(destination_id, &flood_request, routing_header, session_id, "FloodRequest already received");
-    /// ```
-    fn send_flood_response(
-        &self,
-        dest_node: NodeId,
-        flood_request: &FloodRequest,
-        routing_header: SourceRoutingHeader,
-        session_id: u64,
-        reason: &str,
-    ) {
-        let flood_response = FloodResponse {
-            flood_id: flood_request.flood_id,
-            path_trace: flood_request.path_trace.clone(),
-        };
+    /// - `destination`: The ID of the congested neighbor.
+    /// - `packet`: The dropped `Packet`, shortcut to the simulation controller if it is an `Ack`, a `Nack` or
+    ///   a `FloodResponse`.
+    fn report_overflow(&self, destination: NodeId, packet: Packet) {
+        warn!(
+            "{} [ Drone {} ]: The outbound queue of [ Drone {} ] is full, the {} was dropped",
+            "!!!".yellow(),
+            self.core.id(),
+            destination,
+            packet.pack_type
+        );
 
-        let new_packet = Packet {
-            pack_type: PacketType::FloodResponse(flood_response.clone()),
-            routing_header,
-            session_id,
-        };
+        self.shortcut_lost(&packet);
+        self.update_topology(|topology, drone_id, _| {
+            topology.record_drop(drone_id, destination);
+        });
+        self.send_event(RustasticEvent::OverflowDrop {
+            drone_id: self.core.id(),
+            neighbor_id: destination,
+            packet: Box::new(packet),
+        });
+    }
 
-        if let Some(sender) = self.packet_send.get(&dest_node) {
-            match sender.send(new_packet.clone()) {
-                Ok(()) => {
-                    info!(
-                        "{} [ Drone {} ]: sent the FloodResponse to [ Drone {} ]\n└─>Reason: {}",
-                        "✓".green(),
-                        self.id,
-                        dest_node,
-                        reason
-                    );
-                    self.controller_send
-                        .send(DroneEvent::PacketSent(new_packet))
-                        .unwrap();
+    /// Sends a packet the drone has to drop to the simulation controller, if it is an `Ack`, a `Nack` or a
+    /// `FloodResponse`: these packets must not be lost.
+    ///
+    /// # Arguments
+    /// - `packet`: The dropped `Packet`.
+    fn shortcut_lost(&self, packet: &Packet) {
+        if matches!(
+            packet.pack_type,
+            PacketType::Ack(_) | PacketType::Nack(_) | PacketType::FloodResponse(_)
+        ) {
+            self.controller_send
+                .send(DroneEvent::ControllerShortcut(packet.clone()))
+                .unwrap();
+        }
+    }
+
+    /// Hands a packet to the transport, or forwards it to the simulation controller if an error occurs.
+    ///
+    /// # Arguments
+    /// - `destination`: The ID of the neighbor to send the packet to.
+    /// - `packet`: The `Packet` to send.
+    ///
+    /// # Returns
+    /// The packet, if the neighbor is congested and can't take it now.
+    ///
+    /// # Behavior
+    /// - If the packet is sent, the controller is notified with a `PacketSent` event.
+    /// - If the transport can't deliver the packet, a fragment is NACKed to its source with `ErrorInRouting`, see
+    ///   `DroneCore::nack_unreachable`. The other packets are sent to the simulation controller with a
+    ///   `ControllerShortcut`, except for a `FloodRequest` that can only be logged.
+    /// - If the link to the neighbor is gone, the failure is reported to the `DroneCore`, which may declare the
+    ///   neighbor dead, see `RustasticDrone::with_liveness`. A socket error is not reported: a datagram socket
+    ///   can't tell a dead neighbor from one restarting at the same address, which must not be given up on.
+    fn try_send_packet(&mut self, destination: NodeId, packet: Packet) -> Option<Packet> {
+        match self.transport.send(destination, packet.clone()) {
+            Ok(()) => {
+                self.core.report_send_success(destination);
+                info!(
+                    "{} [ Drone {} ]: was sent a {} packet to [ Drone {} ]",
+                    "✓".green(),
+                    self.core.id(),
+                    packet.pack_type,
+                    destination
+                );
+                if matches!(packet.pack_type, PacketType::Nack(_)) {
+                    self.update_topology(|topology, drone_id, _| {
+                        topology.record_nack(drone_id, destination);
+                    });
                 }
-                Err(e) => {
-                    error!(
-                        "{} [ Drone {} ]: Failed to send the FloodResponse to [ Drone {} ]: {}",
-                        "✗".red(),
-                        self.id,
-                        dest_node,
-                        e
-                    );
+                self.controller_send
+                    .send(DroneEvent::PacketSent(packet))
+                    .unwrap();
+            }
+            Err(TransportError::Full(_)) => return Some(packet),
+            Err(e) => {
+                error!(
+                    "{} [ Drone {} ]: Failed to send the {} to [ Drone {} ]: {}",
+                    "✗".red(),
+                    self.core.id(),
+                    packet.pack_type,
+                    destination,
+                    e
+                );
 
-                    warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+                if matches!(
+                    e,
+                    TransportError::NotConnected(_) | TransportError::Disconnected(_)
+                ) {
+                    let actions = self.core.report_send_failure(destination);
+                    self.perform(actions);
+                }
 
-                    self.controller_send
-                        .send(DroneEvent::ControllerShortcut(new_packet))
-                        .unwrap();
+                // A fragment is NACKed like one whose next hop the core doesn't know
+                if matches!(packet.pack_type, PacketType::MsgFragment(_)) {
+                    let actions = self.core.nack_unreachable(packet);
+                    self.perform(actions);
+                    return None;
+                }
 
-                    warn!(
-                        "└─>{} [ Drone {} ]: FloodResponse sent to Simulation Controller",
-                        "!!!".yellow(),
-                        self.id
-                    );
+                if matches!(packet.pack_type, PacketType::FloodRequest(_)) {
+                    return None;
                 }
-            }
-        } else {
-            // Handle the case where there is no connection to the destination drone
-            error!(
-                "{} [ Drone {} ]: Failed to send the FloodResponse: No connection to [ Drone {} ]",
-                "✗".red(),
-                self.id,
-                dest_node
-            );
 
-            warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+                warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
 
-            self.controller_send
-                .send(DroneEvent::ControllerShortcut(new_packet))
-                .unwrap();
+                warn!(
+                    "└─>{} [ Drone {} ]: {} sent to Simulation Controller",
+                    "!!!".yellow(),
+                    self.core.id(),
+                    packet.pack_type,
+                );
 
-            warn!(
-                "└─>{} [ Drone {} ]: FloodResponse sent to Simulation Controller",
-                "!!!".yellow(),
-                self.id
-            );
+                self.controller_send
+                    .send(DroneEvent::ControllerShortcut(packet))
+                    .unwrap();
+            }
         }
+
+        None
     }
 
-    /// Handles incoming drone commands to manage network connections and settings.
-    ///
-    /// This function processes different types of commands for the drone, including adding or removing senders,
-    /// adjusting the packet drop rate (PDR), and handling a crash command. Based on the command type, the drone
-    /// either updates its network connections, modifies its settings, or triggers the respective behavior.
+    /// Sends a `RustasticEvent`, if the drone has an event channel.
     ///
     /// # Arguments
-    /// - `command`: The drone command to handle, which can be one of the following:
-    ///   - `AddSender(node_id, sender)`: Adds a sender (a connection to another drone) to the drone's network.
-    ///   - `SetPacketDropRate(pdr)`: Sets the packet drop rate (PDR) of the drone, controlling the likelihood of
-    ///     dropping packets during transmission.
-    ///   - `RemoveSender(node_id)`: Removes a sender (a connection to another drone) from the drone's network.
-    ///   - `Crash`: This command simulates a crash of the drone, but it is marked as `unreachable` because it is
-    ///     not meant to be processed here.
-    ///
-    /// # Behavior:
-    /// - **`AddSender`**: Adds the sender to the drone's list of connected drones if not already connected.
-    /// - **`SetPacketDropRate`**: Sets the drone’s packet drop rate (PDR), ensuring the value is between `0.0` and `1.0`.
-    /// - **`RemoveSender`**: Removes the sender from the drone's list of connected drones if it exists.
-    /// - **`Crash`**: This command is considered unreachable in the current context and will not be processed.
-    ///
-    /// # Panic:
-    /// - Panic if a command of type `DroneCommand::Crash` is passed
-    ///
-    /// # Example:
-    /// ```rust
-    /// drone.handle_command(DroneCommand::AddSender(node_id, sender));
-    /// drone.handle_command(DroneCommand::SetPacketDropRate(0.1));
-    /// drone.handle_command(DroneCommand::RemoveSender(node_id));
-    /// ```
-    fn handle_command(&mut self, command: DroneCommand) {
-        match command {
-            DroneCommand::AddSender(node_id, sender) => {
-                if let std::collections::hash_map::Entry::Vacant(e) =
-                    self.packet_send.entry(node_id)
-                {
-                    info!(
-                        "{} Adding sender: {} to [ Drone {} ]",
-                        "✓".green(),
-                        node_id,
-                        self.id,
-                    );
-                    e.insert(sender);
-                } else {
-                    warn!(
-                        "{} [ Drone {} ] is already connected to [ Drone {} ]",
-                        "!!!".yellow(),
-                        self.id,
-                        node_id
-                    );
-                }
-            }
-            DroneCommand::SetPacketDropRate(pdr) => {
-                if (0.0..=1.0).contains(&pdr) {
-                    info!(
-                        "{} Setting [ Drone {} ] pdr to: {}",
-                        "✓".green(),
-                        self.id,
-                        pdr
-                    );
-                    self.pdr = pdr;
-                } else {
-                    error!(
-                        "{} The pdr is a value that must be between `0.0` and `1.0`",
-                        "✗".red()
-                    );
-                }
-            }
-            DroneCommand::RemoveSender(node_id) => {
-                if self.packet_send.contains_key(&node_id) {
-                    info!(
-                        "{} Removing sender: {} from [ Drone {} ]",
-                        "✓".green(),
-                        node_id,
-                        self.id
-                    );
-                    self.packet_send.remove(&node_id);
-                } else {
-                    warn!(
-                        "{} [ Drone {} ] is already disconnected from [ Drone {} ]",
-                        "!!!".yellow(),
-                        self.id,
-                        node_id
-                    );
-                }
+    /// - `event`: The `RustasticEvent` to send.
+    fn send_event(&self, event: RustasticEvent) {
+        if let Some(event_send) = &self.event_send {
+            if event_send.send(event).is_err() {
+                warn!(
+                    "{} [ Drone {} ]: can't send the event, the event channel is closed",
+                    "!!!".yellow(),
+                    self.core.id()
+                );
             }
-            DroneCommand::Crash => unreachable!(),
         }
     }
 }
@@ -0,0 +1,1775 @@
+//! This file contains the Rustastic Drone's protocol core, developed by the Group Rustastic.
+//!
+//! File:   drone/drone_core.rs
+//!
+//! Brief:  File for the `DroneCore`, the drone's protocol state machine, free from any channel or I/O.
+//!
+//! Author: Rustastic
+
+use colored::Colorize;
+use crossbeam_channel::Sender;
+use log::{error, info, warn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeSet, HashSet};
+use std::time::Duration;
+
+use wg_2024::{
+    controller::DroneCommand,
+    network::{NodeId, SourceRoutingHeader},
+    packet::{FloodRequest, FloodResponse, Fragment, Nack, NackType, NodeType, Packet, PacketType},
+};
+
+use crate::duplicate_filter::DuplicateFilter;
+use crate::event::{ProtocolViolation, RustasticEvent};
+use crate::flood_limit::{FloodLimitAction, FloodLimiter, FloodRateLimit};
+use crate::liveness::Liveness;
+use crate::packet_buffer;
+use crate::policy::{LivenessPolicy, LoopPolicy, NackRecoveryPolicy};
+
+/// Statistics of the `FloodRequest`s handled by a `DroneCore`, counting what became of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FloodStats {
+    /// New floods forwarded to the neighbors.
+    pub forwarded: u64,
+    /// Floods already seen, answered with a `FloodResponse`.
+    pub duplicates: u64,
+    /// New floods answered because the drone had no other neighbor to forward them to.
+    pub dead_ends: u64,
+    /// New floods answered because they reached the maximum hop count.
+    pub hop_limited: u64,
+    /// New floods answered or dropped because their initiator was over its `FloodRateLimit`.
+    pub rate_limited: u64,
+}
+
+/// An input of the `DroneCore`: everything that can happen to a drone.
+#[derive(Debug, Clone)]
+pub enum DroneInput {
+    /// A packet received from a neighbor.
+    Packet(Packet),
+    /// A command received from the simulation controller.
+    Command(DroneCommand),
+}
+
+/// An action decided by the `DroneCore`, that the driver running the core has to perform.
+#[derive(Debug, Clone)]
+pub enum DroneAction {
+    /// Send `packet` to the neighbor `to`, then report it with a `DroneEvent::PacketSent`.
+    Send { to: NodeId, packet: Packet },
+    /// Deliver the packet through the simulation controller with a `DroneEvent::ControllerShortcut`.
+    Shortcut(Packet),
+    /// Report a fragment dropped by the drone with a `DroneEvent::PacketDropped`.
+    Dropped(Packet),
+    /// Report a `RustasticEvent`.
+    Event(RustasticEvent),
+    /// Start sending packets to a new neighbor through `Sender`.
+    Connect(NodeId, Sender<Packet>),
+    /// Stop sending packets to a neighbor.
+    Disconnect(NodeId),
+    /// Stop the drone.
+    Crash,
+}
+
+/// The protocol state machine of a Rustastic drone.
+///
+/// The `DroneCore` holds all the state the drone needs to take its decisions, but it never touches a
+/// channel: each input returns the list of `DroneAction`s to perform, in order. This makes the protocol
+/// logic testable on its own and reusable by any driver, `RustasticDrone` being the one over crossbeam
+/// channels.
+///
+/// # Fields
+/// - `id`: The unique identifier of the drone.
+/// - `pdr`: The Packet Drop Rate (PDR), a float representing the probability
+///   that a packet will be dropped during transmission.
+/// - `neighbors`: The IDs of the nodes the drone can send packets to, ordered so that a `FloodRequest` is
+///   always forwarded to them in the same order.
+/// - `flood_id_received`: A set that caches flood IDs already processed, used to prevent
+///   duplicate packet processing in the context of flood-based protocols.
+/// - `buffer`: A packet buffer to temporarily store packets that pass through the drone.
+/// - `loop_policy`: How the drone reacts to routing headers that visit the same node more than once.
+/// - `duplicate_filter`: Remembers the fragments recently forwarded, to discard their duplicates.
+/// - `nack_recovery`: How the drone recovers fragments dropped downstream from its buffer.
+/// - `rng`: The random number generator deciding which fragments are dropped.
+/// - `flood_limiter`: The token buckets of the flood initiators, if new floods are rate limited.
+/// - `clock`: The current time, as told by the driver with `DroneCore::set_clock`.
+/// - `max_flood_hops`: The number of hops after which a flood is answered instead of forwarded, if limited.
+/// - `flood_stats`: What became of the floods handled so far.
+/// - `liveness`: The failed sends to each neighbor, and the neighbors declared dead.
+/// - `actions`: The actions decided while handling the current input.
+#[derive(Debug, Clone)]
+pub struct DroneCore {
+    id: NodeId,
+    pdr: f32,
+    neighbors: BTreeSet<NodeId>,
+    flood_id_received: HashSet<(u64, NodeId)>, // Caching received flood_id
+    pub buffer: packet_buffer::PacketBuffer,   // Packet buffer
+    loop_policy: LoopPolicy,
+    duplicate_filter: DuplicateFilter,
+    nack_recovery: NackRecoveryPolicy,
+    rng: StdRng,
+    flood_limiter: Option<FloodLimiter>,
+    clock: Duration,
+    max_flood_hops: Option<usize>,
+    flood_stats: FloodStats,
+    liveness: Liveness,
+    actions: Vec<DroneAction>,
+}
+
+impl DroneCore {
+    /// Creates a new `DroneCore`.
+    ///
+    /// # Arguments
+    /// - `id`: The unique identifier of the drone.
+    /// - `pdr`: The Packet Drop Rate of the drone, which affects transmission reliability.
+    /// - `neighbors`: The IDs of the nodes the drone is connected to.
+    ///
+    /// The field `flood_id_received` is initialized to an empty`HashSet`
+    /// The field buffer is initialized with a `PacketBuffer` with default size of 16 packet
+    /// The field `loop_policy` is initialized to `LoopPolicy::Strict`
+    /// The field `duplicate_filter` is initialized with a window of 0 fragments, duplicates are not detected
+    /// The field `nack_recovery` is initialized to `NackRecoveryPolicy::default()`
+    /// The field `rng` is seeded from the operating system, see `DroneCore::with_rng_seed`
+    /// The field `flood_limiter` is initialized to `None`, floods are not rate limited
+    /// The field `clock` is initialized to zero
+    /// The field `max_flood_hops` is initialized to `None`, floods can cross any number of hops
+    /// The field `liveness` is initialized with `LivenessPolicy::default()`
+    ///
+    /// # Returns
+    /// A new instance of `DroneCore`.
+    pub fn new(id: NodeId, pdr: f32, neighbors: impl IntoIterator<Item = NodeId>) -> Self {
+        Self {
+            id,
+            pdr,
+            neighbors: neighbors.into_iter().collect(),
+            flood_id_received: HashSet::new(),
+            buffer: packet_buffer::PacketBuffer::new(16),
+            loop_policy: LoopPolicy::default(),
+            duplicate_filter: DuplicateFilter::new(0),
+            nack_recovery: NackRecoveryPolicy::default(),
+            rng: StdRng::from_entropy(),
+            flood_limiter: None,
+            clock: Duration::ZERO,
+            max_flood_hops: None,
+            flood_stats: FloodStats::default(),
+            liveness: Liveness::default(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Sets how the drone reacts to routing headers that contain a loop.
+    ///
+    /// # Arguments
+    /// - `loop_policy`: The `LoopPolicy` to apply to every packet that is not a `FloodRequest`.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_loop_policy(mut self, loop_policy: LoopPolicy) -> Self {
+        self.loop_policy = loop_policy;
+        self
+    }
+
+    /// Sets how many recently forwarded fragments the drone remembers to discard duplicates.
+    ///
+    /// A fragment, identified by its source, session and index, is forwarded only once while it is in the window,
+    /// unless a NACK for it travels back through the drone, meaning that the forwarded copy was lost. A discarded
+    /// duplicate is reported to the simulation controller as dropped. Duplicate detection is disabled by default.
+    ///
+    /// # Arguments
+    /// - `window`: The number of fragments to remember, `0` disables duplicate detection.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_duplicate_window(mut self, window: usize) -> Self {
+        self.duplicate_filter.set_window(window);
+        self
+    }
+
+    /// Sets how the drone recovers fragments dropped downstream from its buffer.
+    ///
+    /// # Arguments
+    /// - `nack_recovery`: The `NackRecoveryPolicy` applied to the `Dropped` NACKs that travel through the drone.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_nack_recovery(mut self, nack_recovery: NackRecoveryPolicy) -> Self {
+        self.nack_recovery = nack_recovery;
+        self
+    }
+
+    /// Seeds the random number generator that decides which fragments are dropped.
+    ///
+    /// Two drones with the same seed, PDR and inputs drop exactly the same fragments, which makes runs reproducible.
+    ///
+    /// # Arguments
+    /// - `seed`: The seed of the generator.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_rng_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Limits how many new floods per second the drone forwards for each initiator.
+    ///
+    /// The limit is measured on the clock of the core, see `DroneCore::set_clock`.
+    ///
+    /// # Arguments
+    /// - `limit`: The `FloodRateLimit` of every initiator.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_flood_rate_limit(mut self, limit: FloodRateLimit) -> Self {
+        self.flood_limiter = Some(FloodLimiter::new(limit));
+        self
+    }
+
+    /// Limits how far floods travel, bounding the cost of discovery in large topologies.
+    ///
+    /// A flood that has crossed `max_hops` hops when it reaches the drone, its `path_trace` holding `max_hops`
+    /// nodes, is answered with a `FloodResponse` instead of being forwarded.
+    ///
+    /// # Arguments
+    /// - `max_hops`: The maximum hop count of a flood.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_max_flood_hops(mut self, max_hops: usize) -> Self {
+        self.max_flood_hops = Some(max_hops);
+        self
+    }
+
+    /// Sets when the drone gives up on a neighbor it can't send to anymore.
+    ///
+    /// # Arguments
+    /// - `policy`: The `LivenessPolicy` applied to the failed sends reported by the driver, see
+    ///   `DroneCore::report_send_failure`.
+    ///
+    /// # Returns
+    /// The same `DroneCore`, for chaining after `DroneCore::new`.
+    #[must_use]
+    pub fn with_liveness(mut self, policy: LivenessPolicy) -> Self {
+        self.liveness.set_policy(policy);
+        self
+    }
+
+    /// Returns the IDs of the neighbors declared dead, which the drone doesn't route to anymore.
+    #[must_use]
+    pub fn dead_neighbors(&self) -> Vec<NodeId> {
+        self.liveness.dead()
+    }
+
+    /// Returns the statistics of the floods handled by the drone.
+    #[must_use]
+    pub fn flood_stats(&self) -> FloodStats {
+        self.flood_stats
+    }
+
+    /// Tells the core the current time, which its rate limits are measured on.
+    ///
+    /// The core never reads a clock itself: the driver sets the time before each input, e.g. the time elapsed
+    /// since the drone started, or the virtual time of a `Simulation`.
+    ///
+    /// # Arguments
+    /// - `now`: The time elapsed since an origin of the driver's choice, which must not go backwards.
+    pub fn set_clock(&mut self, now: Duration) {
+        self.clock = now;
+    }
+
+    /// Returns the ID of the drone.
+    #[must_use]
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Handles an input of the drone.
+    ///
+    /// # Arguments
+    /// - `input`: The `DroneInput` to handle.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform, in order.
+    pub fn handle(&mut self, input: DroneInput) -> Vec<DroneAction> {
+        match input {
+            DroneInput::Packet(packet) => self.handle_packet(packet),
+            DroneInput::Command(command) => self.handle_command(command),
+        }
+    }
+
+    /// Handles incoming packets for the drone.
+    ///
+    /// This method is responsible for processing incoming packets. It checks the type of packet received and
+    /// handles it accordingly. The packet could be a flood request, a normal message fragment, or an acknowledgment
+    /// (either `Nack` or `Ack`). The method performs several checks to ensure that the packet is valid and
+    /// can be forwarded to the correct next hop. If any errors are found, appropriate `Nack` packets are sent.
+    ///
+    /// # Packet Handling Logic
+    /// - **`FloodRequest`**: If the packet is a flood request, it handles the request by calling `handle_flood_request`,
+    ///   and then adds the flood ID to the `flood_id_received` set to prevent duplicate processing of the same flood.
+    /// - **Correct Packet ID**: If the packet has the correct ID and is routable, it continues with routing and hop management.
+    /// - **Destination Check**: If the destination of the packet is not a valid destination (e.g., a drone instead of a client/server),
+    ///   it sends a `Nack` with an error message (`DestinationIsDrone`).
+    /// - **Neighbor Check**: If the packet cannot be forwarded to a neighbor (i.e., the next hop is not in the drone's neighbor list),
+    ///   it sends a `Nack` indicating an error in routing.
+    /// - **Packet Type Handling**: Depending on the packet type, the method delegates the handling to the appropriate sub-methods:
+    ///   - `Nack` and `Ack` packets are processed by `handle_ack_nack`.
+    ///   - Fragmented messages are processed by `handle_fragment`.
+    ///   - Flood responses are handled by `handle_flood_response`.
+    ///
+    /// # Arguments
+    /// - `packet`: The incoming `Packet` that needs to be processed.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform, in order.
+    ///
+    /// # Behavior
+    /// - The method first checks if the packet is a flood request, and handles it accordingly.
+    /// - Any other packet must have a well-formed routing header (see `check_routing_header`), otherwise it is
+    ///   reported as a `ProtocolViolation` and never forwarded.
+    /// - Routing headers that visit the same node twice are handled according to the `LoopPolicy`
+    ///   (see `check_routing_loop`).
+    /// - If the packet is a normal message, it checks the validity of the destination and the neighbors, then forwards it or
+    ///   sends a `Nack` if needed.
+    ///
+    /// # Example
+    /// ```ignore
+    /// // Assuming `packet` is a received packet to handle
+    /// for action in core.handle_packet(packet) {
+    ///     // Perform the action
+    /// }
+    /// ```
+    pub fn handle_packet(&mut self, mut packet: Packet) -> Vec<DroneAction> {
+        info!(
+            "{} [ Drone {} ]: has received the packet {:?}",
+            "✓".green(),
+            self.id,
+            packet
+        );
+
+        if let PacketType::FloodRequest(flood_request) = packet.clone().pack_type {
+            if self.check_path_trace(&flood_request, &packet) {
+                let flood_id = flood_request.flood_id;
+                let flood_initiator = flood_request.initiator_id;
+                if self.handle_flood_request(flood_request, &packet) {
+                    self.flood_id_received.insert((flood_id, flood_initiator));
+                }
+            }
+        } else if self.check_routing_header(&packet)
+            && self.check_packet_correct_id(packet.clone())
+            && self.check_routing_loop(&packet)
+        {
+            // Increase hop_index
+            packet.routing_header.increase_hop_index();
+
+            // If the destination has been reached, and it is a Drone (invalid destination)
+            if packet.routing_header.hop_index == packet.routing_header.hops.len() {
+                error!(
+                    "{} The selected destination in the RoutingHeader of [ Drone {} ] is a Drone",
+                    "✗".red(),
+                    self.id
+                );
+                if let PacketType::MsgFragment(frag) = packet.clone().pack_type {
+                    self.send_nack(packet, Some(frag), NackType::DestinationIsDrone);
+                } else {
+                    self.send_nack(packet, None, NackType::DestinationIsDrone);
+                }
+
+                return std::mem::take(&mut self.actions);
+            }
+
+            // Check if the next hop is a valid neighbor
+            if !self.check_neighbor(&packet) {
+                //Step4
+                let neighbor = packet.routing_header.hops[packet.routing_header.hop_index];
+                error!(
+                    "{} [ Drone {} ]: can't send packet to Drone {} because it is not its neighbor",
+                    "✗".red(),
+                    self.id,
+                    neighbor
+                );
+                //problematico
+                match packet.clone().pack_type {
+                    PacketType::MsgFragment(fragment) => {
+                        self.send_nack(packet, Some(fragment), NackType::ErrorInRouting(neighbor));
+                    }
+                    PacketType::FloodRequest(_flood_request) => unreachable!(),
+                    _ => self.shortcut(packet),
+                }
+
+                return std::mem::take(&mut self.actions);
+            }
+
+            info!(
+                "{} Packet with [ session_id: {} ] is being handled from [ Drone {} ]",
+                "✓".green(),
+                packet.session_id,
+                self.id
+            );
+
+            // Handle packet types: Nack, Ack, MsgFragment, FloodResponse
+            match packet.clone().pack_type {
+                PacketType::Nack(_nack) => self.handle_ack_nack(packet),
+                PacketType::Ack(_ack) => self.handle_ack_nack(packet),
+                PacketType::MsgFragment(fragment) => self.handle_fragment(packet, fragment),
+                PacketType::FloodRequest(_) => unreachable!(),
+                PacketType::FloodResponse(flood_response) => {
+                    self.handle_flood_response(&flood_response, &packet);
+                }
+            }
+        }
+
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Handles incoming drone commands to manage network connections and settings.
+    ///
+    /// This function processes different types of commands for the drone, including adding or removing senders,
+    /// adjusting the packet drop rate (PDR), and handling a crash command. Based on the command type, the drone
+    /// either updates its neighbors, modifies its settings, or asks its driver to stop.
+    ///
+    /// # Arguments
+    /// - `command`: The drone command to handle, which can be one of the following:
+    ///   - `AddSender(node_id, sender)`: Adds a sender (a connection to another drone) to the drone's network.
+    ///   - `SetPacketDropRate(pdr)`: Sets the packet drop rate (PDR) of the drone, controlling the likelihood of
+    ///     dropping packets during transmission.
+    ///   - `RemoveSender(node_id)`: Removes a sender (a connection to another drone) from the drone's network.
+    ///   - `Crash`: This command simulates a crash of the drone.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform, in order.
+    ///
+    /// # Behavior:
+    /// - **`AddSender`**: Adds the node to the drone's neighbors if not already connected, with a `Connect` action.
+    ///   A neighbor declared dead is restored, with a `NeighborRestored` event, unless the `LivenessPolicy` doesn't
+    ///   restore dead neighbors: the command is then ignored.
+    /// - **`SetPacketDropRate`**: Sets the drone’s packet drop rate (PDR), ensuring the value is between `0.0` and `1.0`.
+    /// - **`RemoveSender`**: Removes the node from the drone's neighbors if it exists, with a `Disconnect` action.
+    ///   A neighbor declared dead is forgotten, so that it can be added again.
+    /// - **`Crash`**: Returns a `Crash` action, the driver must stop running the drone.
+    ///
+    /// # Example:
+    /// ```ignore
+    /// core.handle_command(DroneCommand::AddSender(node_id, sender));
+    /// core.handle_command(DroneCommand::SetPacketDropRate(0.1));
+    /// core.handle_command(DroneCommand::RemoveSender(node_id));
+    /// ```
+    pub fn handle_command(&mut self, command: DroneCommand) -> Vec<DroneAction> {
+        match command {
+            DroneCommand::AddSender(node_id, sender) => {
+                if self.liveness.is_dead(node_id) {
+                    if !self.liveness.policy().restore_on_add_sender {
+                        warn!(
+                            "{} [ Drone {} ]: [ Drone {} ] is dead, it is not added again",
+                            "!!!".yellow(),
+                            self.id,
+                            node_id
+                        );
+                        return Vec::new();
+                    }
+                    self.liveness.forget(node_id);
+                    self.actions
+                        .push(DroneAction::Event(RustasticEvent::NeighborRestored {
+                            drone_id: self.id,
+                            neighbor_id: node_id,
+                        }));
+                }
+
+                if self.neighbors.insert(node_id) {
+                    info!(
+                        "{} Adding sender: {} to [ Drone {} ]",
+                        "✓".green(),
+                        node_id,
+                        self.id,
+                    );
+                    self.actions.push(DroneAction::Connect(node_id, sender));
+                } else {
+                    warn!(
+                        "{} [ Drone {} ] is already connected to [ Drone {} ]",
+                        "!!!".yellow(),
+                        self.id,
+                        node_id
+                    );
+                }
+            }
+            DroneCommand::SetPacketDropRate(pdr) => {
+                if (0.0..=1.0).contains(&pdr) {
+                    info!(
+                        "{} Setting [ Drone {} ] pdr to: {}",
+                        "✓".green(),
+                        self.id,
+                        pdr
+                    );
+                    self.pdr = pdr;
+                } else {
+                    error!(
+                        "{} The pdr is a value that must be between `0.0` and `1.0`",
+                        "✗".red()
+                    );
+                }
+            }
+            DroneCommand::RemoveSender(node_id) => {
+                let was_dead = self.liveness.forget(node_id);
+                if self.neighbors.remove(&node_id) {
+                    info!(
+                        "{} Removing sender: {} from [ Drone {} ]",
+                        "✓".green(),
+                        node_id,
+                        self.id
+                    );
+                    self.actions.push(DroneAction::Disconnect(node_id));
+                } else if was_dead {
+                    info!(
+                        "{} [ Drone {} ]: forgets the dead [ Drone {} ]",
+                        "✓".green(),
+                        self.id,
+                        node_id
+                    );
+                } else {
+                    warn!(
+                        "{} [ Drone {} ] is already disconnected from [ Drone {} ]",
+                        "!!!".yellow(),
+                        self.id,
+                        node_id
+                    );
+                }
+            }
+            DroneCommand::Crash => {
+                warn!("{} [ Drone {} ]: Has crashed", "!!!".yellow(), self.id);
+                self.actions.push(DroneAction::Crash);
+            }
+        }
+
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Counts a packet that the driver could not send to a neighbor, its channel or link being gone.
+    ///
+    /// A congested neighbor, whose channel is only full, must not be reported: it is alive.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform: if the neighbor has failed `LivenessPolicy::max_failures` times in a row, it
+    /// is removed from the neighbors with a `Disconnect` action and reported with a `NeighborDead` event.
+    pub fn report_send_failure(&mut self, node_id: NodeId) -> Vec<DroneAction> {
+        if !self.neighbors.contains(&node_id) {
+            return Vec::new();
+        }
+
+        if let Some(failures) = self.liveness.fail(node_id) {
+            error!(
+                "{} [ Drone {} ]: [ Drone {} ] failed {} times in a row, it is considered dead",
+                "✗".red(),
+                self.id,
+                node_id,
+                failures
+            );
+            self.neighbors.remove(&node_id);
+            self.actions.push(DroneAction::Disconnect(node_id));
+            self.actions
+                .push(DroneAction::Event(RustasticEvent::NeighborDead {
+                    drone_id: self.id,
+                    neighbor_id: node_id,
+                    failures,
+                }));
+        }
+
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Counts a packet that the driver sent to a neighbor: its failed sends in a row start over.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    pub fn report_send_success(&mut self, node_id: NodeId) {
+        self.liveness.succeed(node_id);
+    }
+
+    /// Refuses a fragment that the driver could not hand over to the next hop, e.g. because it is congested.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` as it was sent, its `hop_index` pointing to the next hop.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform: a `Dropped` NACK to the source for a fragment, nothing for the other packets.
+    ///
+    /// # Behavior
+    /// - The fragment is forgotten first, see `DroneCore::forget_undelivered`.
+    pub fn nack_undelivered(&mut self, packet: Packet) -> Vec<DroneAction> {
+        self.forget_undelivered(&packet);
+        if let PacketType::MsgFragment(fragment) = packet.clone().pack_type {
+            self.send_nack(packet, Some(fragment), NackType::Dropped);
+        }
+
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Refuses a fragment that the driver could not send to the next hop, because the link is gone.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` as it was sent, its `hop_index` pointing to the next hop.
+    ///
+    /// # Returns
+    /// The `DroneAction`s to perform: an `ErrorInRouting` NACK to the source for a fragment, as for a next hop
+    /// that is not a neighbor, nothing for the other packets.
+    ///
+    /// # Behavior
+    /// - The fragment is forgotten first, see `DroneCore::forget_undelivered`.
+    pub fn nack_unreachable(&mut self, packet: Packet) -> Vec<DroneAction> {
+        self.forget_undelivered(&packet);
+        if let PacketType::MsgFragment(fragment) = packet.clone().pack_type {
+            if let Some(next_hop) = packet.routing_header.current_hop() {
+                self.send_nack(packet, Some(fragment), NackType::ErrorInRouting(next_hop));
+            }
+        }
+
+        std::mem::take(&mut self.actions)
+    }
+
+    /// Forgets a fragment that the driver dropped on its way to the next hop.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` as it was sent, its `hop_index` pointing to the next hop.
+    ///
+    /// # Behavior
+    /// - The fragment is removed from the `duplicate_filter`, so that its retransmission is forwarded again.
+    /// - The fragment is discarded from the buffer, the drone can't recover it anymore.
+    /// - The other packets are ignored.
+    pub fn forget_undelivered(&mut self, packet: &Packet) {
+        let PacketType::MsgFragment(fragment) = &packet.pack_type else {
+            return;
+        };
+
+        if let Some(&source) = packet.routing_header.hops.first() {
+            self.duplicate_filter
+                .remove(source, packet.session_id, fragment.fragment_index);
+        }
+        self.buffer
+            .discard_fragment(packet.session_id, fragment.fragment_index);
+    }
+
+    /// Sends a message packet to the next hop, or forwards it to the simulation controller if it can't be sent.
+    ///
+    /// This method is responsible for sending a `Packet` to the next drone in the routing path. It checks if the
+    /// destination is one of the drone's neighbors. If it is, a `Send` action is queued and the method returns `true`.
+    /// If the destination is unreachable, it logs the error and handles the packet as described below, returning
+    /// `false` to indicate failure.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` to be sent. It contains the routing information and the packet type.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the packet is sent to the destination drone.
+    /// - `false`: if the destination is unreachable.
+    ///
+    /// # Behavior
+    /// - If the routing header has no hop at `hop_index`, the packet is reported as a `ProtocolViolation` and dropped.
+    /// - If the destination is unreachable and the packet is a `MsgFragment`, an `ErrorInRouting` NACK is sent back.
+    ///   Other packet types are forwarded to the simulation controller with a `ControllerShortcut`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// let sent = core.send_message(packet);
+    /// ```
+    fn send_message(&mut self, packet: Packet) -> bool {
+        let Some(destination) = packet.routing_header.current_hop() else {
+            let violation = ProtocolViolation::HopIndexOutOfRange {
+                hop_index: packet.routing_header.hop_index,
+                hops_len: packet.routing_header.hops.len(),
+            };
+            self.report_protocol_violation(packet, violation);
+            return false;
+        };
+
+        if self.neighbors.contains(&destination) {
+            info!(
+                "{} [ Drone {} ]: is sending a {} packet to [ Drone {} ]",
+                "✓".green(),
+                self.id,
+                packet.pack_type,
+                destination
+            );
+            self.actions.push(DroneAction::Send {
+                to: destination,
+                packet,
+            });
+            return true;
+        }
+
+        // Handle case where there is no connection to the destination drone
+        if let PacketType::MsgFragment(fragment) = packet.pack_type.clone() {
+            error!(
+                "{} [ Drone {} ]: does not exist in the path",
+                "✗".red(),
+                destination
+            );
+            self.send_nack(
+                packet,
+                Some(fragment),
+                NackType::ErrorInRouting(destination),
+            );
+        } else {
+            error!(
+                "{} [ Drone {} ]: Failed to send the {}: No connection to [ Drone {} ]",
+                "✗".red(),
+                self.id,
+                packet.pack_type,
+                destination
+            );
+            self.shortcut(packet);
+        }
+
+        false
+    }
+
+    /// Forwards a packet to the simulation controller with a `ControllerShortcut`.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` that can't travel through the network.
+    fn shortcut(&mut self, packet: Packet) {
+        warn!("├─>{} Sending to Simulation Controller...", "!!!".yellow());
+
+        warn!(
+            "└─>{} [ Drone {} ]: {} sent to Simulation Controller",
+            "!!!".yellow(),
+            self.id,
+            packet.pack_type,
+        );
+
+        self.actions.push(DroneAction::Shortcut(packet));
+    }
+
+    /// Checks that the routing header of a packet can be safely used by the drone.
+    ///
+    /// A packet that is not a `FloodRequest` must carry at least one hop, and its `hop_index` must point inside
+    /// `hops`. Any other header can't be routed and would make the drone index outside of the route.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` whose routing header is being checked.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the routing header is well-formed.
+    /// - `false`: if the routing header is malformed.
+    ///
+    /// # Behavior
+    /// - A malformed header is reported as a `ProtocolViolation`.
+    /// - `Ack`, `Nack` and `FloodResponse` packets that still have a destination are also sent to the simulation
+    ///   controller with a `ControllerShortcut`, since the protocol allows these packets to be delivered that way.
+    fn check_routing_header(&mut self, packet: &Packet) -> bool {
+        let hops_len = packet.routing_header.hops.len();
+        let violation = if hops_len == 0 {
+            ProtocolViolation::EmptyRoute
+        } else if packet.routing_header.hop_index >= hops_len {
+            ProtocolViolation::HopIndexOutOfRange {
+                hop_index: packet.routing_header.hop_index,
+                hops_len,
+            }
+        } else {
+            return true;
+        };
+
+        error!(
+            "{} [ Drone {} ]: received a Packet [ session_id: {} ] with a malformed routing header: {:?}",
+            "✗".red(),
+            self.id,
+            packet.session_id,
+            violation
+        );
+
+        self.report_protocol_violation(packet.clone(), violation);
+
+        if hops_len > 0 && !matches!(packet.pack_type, PacketType::MsgFragment(_)) {
+            self.shortcut(packet.clone());
+        }
+
+        false
+    }
+
+    /// Checks that the `path_trace` of a `FloodRequest` is consistent, before the drone extends it.
+    ///
+    /// The trace must start with the initiator, hold every other node once, and end with the node that sent the
+    /// request, a neighbor of the drone. Any other trace would give the clients a corrupted view of the network.
+    /// The drone itself may already be in the trace: the flood looped back to it, which is answered as a flood
+    /// already received, see `DroneCore::handle_flood_request`.
+    ///
+    /// # Arguments
+    /// - `flood_request`: The `FloodRequest` being checked.
+    /// - `packet`: The `Packet` carrying the request.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the request can be handled.
+    /// - `false`: if the request was refused.
+    ///
+    /// # Behavior
+    /// - An inconsistent trace is reported as a `ProtocolViolation`, and the request is dropped: it is neither
+    ///   forwarded nor answered, and doesn't count as received.
+    fn check_path_trace(&mut self, flood_request: &FloodRequest, packet: &Packet) -> bool {
+        let path_trace = &flood_request.path_trace;
+
+        let mut visited = HashSet::new();
+        let violation = if let Some(&(first, _)) = path_trace.first() {
+            if first != flood_request.initiator_id {
+                ProtocolViolation::InitiatorNotFirst {
+                    initiator_id: flood_request.initiator_id,
+                    first,
+                }
+            } else if let Some(&(node_id, _)) = path_trace
+                .iter()
+                .filter(|(node_id, _)| *node_id != self.id)
+                .find(|(node_id, _)| !visited.insert(*node_id))
+            {
+                ProtocolViolation::DuplicateInPathTrace { node_id }
+            } else {
+                let (prev_node, _) = path_trace[path_trace.len() - 1];
+                if self.neighbors.contains(&prev_node) {
+                    return true;
+                }
+                ProtocolViolation::UnknownPreviousHop { node_id: prev_node }
+            }
+        } else {
+            ProtocolViolation::EmptyPathTrace
+        };
+
+        error!(
+            "{} [ Drone {} ]: refused the FloodRequest with flood_id: {}, its path-trace is inconsistent: {:?}",
+            "✗".red(),
+            self.id,
+            flood_request.flood_id,
+            violation
+        );
+
+        self.report_protocol_violation(packet.clone(), violation);
+
+        false
+    }
+
+    /// Checks that the routing header of a packet doesn't visit the same node more than once.
+    ///
+    /// A route with a repeated node ID contains a cycle: forwarding such a packet wastes bandwidth and, with an
+    /// adversarial header, lets a single packet bounce inside the network.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` whose routing header is being checked. Its `hop_index` must point at this drone.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the packet can be forwarded.
+    /// - `false`: if the packet was refused.
+    ///
+    /// # Behavior
+    /// - `LoopPolicy::Off`: the route is not inspected.
+    /// - `LoopPolicy::Warn`: a loop is logged and reported as a `ProtocolViolation`, but the packet is forwarded.
+    /// - `LoopPolicy::Strict`: a loop is reported as a `ProtocolViolation` and the packet is refused. Fragments
+    ///   are answered with an `ErrorInRouting` NACK carrying the repeated node, the other packets are sent to the
+    ///   simulation controller with a `ControllerShortcut`.
+    fn check_routing_loop(&mut self, packet: &Packet) -> bool {
+        if self.loop_policy == LoopPolicy::Off {
+            return true;
+        }
+
+        let mut visited = HashSet::new();
+        let Some(&node_id) = packet
+            .routing_header
+            .hops
+            .iter()
+            .find(|&&hop| !visited.insert(hop))
+        else {
+            return true;
+        };
+
+        self.report_protocol_violation(packet.clone(), ProtocolViolation::RoutingLoop { node_id });
+
+        if self.loop_policy == LoopPolicy::Warn {
+            warn!(
+                "{} [ Drone {} ]: the route of the Packet [ session_id: {} ] visits [ Drone {} ] more than once",
+                "!!!".yellow(),
+                self.id,
+                packet.session_id,
+                node_id
+            );
+            return true;
+        }
+
+        error!(
+            "{} [ Drone {} ]: refused the Packet [ session_id: {} ], its route visits [ Drone {} ] more than once",
+            "✗".red(),
+            self.id,
+            packet.session_id,
+            node_id
+        );
+
+        if let PacketType::MsgFragment(fragment) = packet.clone().pack_type {
+            let mut packet = packet.clone();
+            packet.routing_header.increase_hop_index();
+            self.send_nack(packet, Some(fragment), NackType::ErrorInRouting(node_id));
+        } else {
+            self.shortcut(packet.clone());
+        }
+
+        false
+    }
+
+    /// Reports a packet that does not respect the protocol with a `RustasticEvent`.
+    ///
+    /// # Arguments
+    /// - `packet`: The malformed `Packet`.
+    /// - `violation`: The `ProtocolViolation` that was detected.
+    fn report_protocol_violation(&mut self, packet: Packet, violation: ProtocolViolation) {
+        self.actions
+            .push(DroneAction::Event(RustasticEvent::ProtocolViolation {
+                drone_id: self.id,
+                packet: Box::new(packet),
+                violation,
+            }));
+    }
+
+    /// Checks if the drone's ID matches the expected recipient ID in the packet's routing header.
+    ///
+    /// This method compares the current drone's ID with the ID specified in the packet's routing header at the
+    /// current hop index. If the IDs match, the method returns `true`, indicating that the packet is addressed to
+    /// this drone. If the IDs do not match, the method returns `false`, sends a NACK to the previous drone indicating
+    /// an unexpected recipient, and logs an error.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` that contains the routing header, which includes a list of hops and the current hop index.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the current drone's ID matches the recipient ID in the packet's routing header.
+    /// - `false`: if the current drone's ID does not match the recipient ID, indicating an incorrect recipient.
+    ///
+    /// # Behavior
+    /// - The method compares the drone's ID (`self.id`) with the destination ID in the packet's routing header,
+    ///   at the position indicated by `hop_index`.
+    /// - If the IDs match, the method returns `true`, confirming the packet is intended for this drone.
+    /// - If the IDs do not match, it sends a NACK with the error type `UnexpectedRecipient`, logs the mismatch error,
+    ///   and returns `false`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// let is_correct = core.check_packet_correct_id(packet);
+    /// if is_correct {
+    ///     println!("Packet addressed correctly to the drone.");
+    /// } else {
+    ///     println!("Packet addressed to the wrong drone.");
+    /// }
+    /// ```
+    fn check_packet_correct_id(&mut self, packet: Packet) -> bool {
+        if packet.routing_header.current_hop() == Some(self.id) {
+            true
+        } else {
+            error!(
+                "{} [ Drone {} ]: does not correspond to the Drone indicated by the `hop_index`",
+                "✗".red(),
+                self.id
+            );
+
+            if let PacketType::MsgFragment(frag) = packet.clone().pack_type {
+                self.send_nack(packet, Some(frag), NackType::UnexpectedRecipient(self.id));
+            } else {
+                self.shortcut(packet);
+            }
+
+            false
+        }
+    }
+
+    /// Handles the reception of ACK and NACK packets, managing fragment retransmissions and routing updates.
+    ///
+    /// This method processes incoming NACK or ACK packets, depending on the packet type. If a NACK is received, it attempts
+    /// to find the corresponding fragment in the drone's buffer and resends it. If the fragment is not found, a NACK is sent
+    /// back to the previous hop. For ACK packets, the method ensures the correct routing and either forwards the packet or
+    /// updates the routing header accordingly.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` that contains the routing header and packet type (NACK, ACK, etc.) to be processed.
+    ///
+    /// # Behavior
+    /// - **If the packet type is a NACK**:
+    ///   - The fragment is removed from the `duplicate_filter`, so that its retransmission is not discarded.
+    ///   - For a `Dropped` NACK, it tries to recover the fragment locally with `retransmit_fragment`.
+    ///   - For any other NACK the route is broken downstream, so the buffered copy is discarded without resending it.
+    ///   - If the fragment was not recovered, or the `NackRecoveryPolicy` doesn't absorb recovered NACKs, the NACK
+    ///     is sent to the previous node.
+    /// - **If the packet type is an ACK**:
+    ///   - The acknowledged fragment is released from the drone's buffer.
+    ///   - It increments the hop index and forwards the packet to the next hop, if the index is valid.
+    ///   - If the hop index is at the end of the route, it logs an error.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// core.handle_ack_nack(packet);
+    /// ```
+    fn handle_ack_nack(&mut self, packet: Packet) {
+        if packet.routing_header.hop_index >= packet.routing_header.hops.len() {
+            error!(
+                "{} Invalid hop index increment detected in [ Drone: {} ] for header of Packet [ session_id: {} ]",
+                "✗".red(),
+                self.id,
+                packet.session_id
+            );
+            return;
+        }
+        if let PacketType::Nack(nack) = packet.clone().pack_type {
+            warn!(
+                "{} [ Drone {} ]: received a {}",
+                "!!!".yellow(),
+                self.id,
+                packet.pack_type,
+            );
+
+            warn!(
+                "\n├─>{} Checking [ Drone {} ] buffer...",
+                "!!!".yellow(),
+                self.id
+            );
+
+            // The forwarded copy was lost, its retransmission must not be discarded as a duplicate. The NACK
+            // travels back to the source of the fragment, the last hop of its route
+            if let Some(&source) = packet.routing_header.hops.last() {
+                self.duplicate_filter
+                    .remove(source, packet.session_id, nack.fragment_index);
+            }
+
+            let recovered = if matches!(nack.nack_type, NackType::Dropped) {
+                self.retransmit_fragment(packet.session_id, nack.fragment_index)
+            } else {
+                // The route is broken downstream, resending the buffered copy on it is pointless
+                self.buffer
+                    .get_fragment(packet.session_id, nack.fragment_index);
+                false
+            };
+
+            if !recovered || !self.nack_recovery.absorb_recovered {
+                // Send a nack to the previous node
+                self.send_message(packet);
+            }
+        } else {
+            info!(
+                "{} [ Drone {} ]: received a {}",
+                "i".blue(),
+                self.id,
+                packet.pack_type,
+            );
+
+            // The destination received the fragment, the buffered copy is no longer needed
+            if let PacketType::Ack(ack) = &packet.pack_type {
+                if self
+                    .buffer
+                    .ack_fragment(packet.session_id, ack.fragment_index)
+                {
+                    info!(
+                        "├─>{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ] was released from the [ Drone {} ] buffer",
+                        "✓".green(),
+                        ack.fragment_index,
+                        packet.session_id,
+                        self.id
+                    );
+                }
+            }
+
+            self.send_message(packet);
+        }
+    }
+
+    /// Resends a buffered fragment after a `Dropped` NACK from downstream.
+    ///
+    /// The fragment stays in the buffer, so that a later NACK can be recovered again, until it has been resent
+    /// `max_local_retries` times: after that the NACK has to travel back to the source.
+    ///
+    /// # Arguments
+    /// - `session_id`: The session ID of the dropped fragment.
+    /// - `fragment_index`: The index of the dropped fragment.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the fragment was found in the buffer and sent again.
+    /// - `false`: if the fragment is not buffered, ran out of retries, or its next hop is unreachable.
+    fn retransmit_fragment(&mut self, session_id: u64, fragment_index: u64) -> bool {
+        let Some(new_packet) = self.buffer.retry_fragment(
+            session_id,
+            fragment_index,
+            self.nack_recovery.max_local_retries,
+        ) else {
+            warn!(
+                "└─>{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ] can't be recovered by [ Drone {} ]",
+                "!!!".yellow(),
+                fragment_index,
+                session_id,
+                self.id
+            );
+            return false;
+        };
+
+        info!(
+            "├─>{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ]  was found in the buffer",
+            "✓".green(),
+            fragment_index,
+            session_id
+        );
+
+        let source = new_packet.routing_header.hops.first().copied();
+        if !self.send_message(new_packet) {
+            return false;
+        }
+
+        // A retransmission from the source would now be a duplicate of the resent copy
+        if let Some(source) = source {
+            self.duplicate_filter
+                .insert(source, session_id, fragment_index);
+        }
+
+        info!("└─>{} The Packet was sent", "✓".green());
+        true
+    }
+
+    /// Handles the reception of a fragmented packet, either dropping it based on the Packet Drop Rate (PDR)
+    /// or forwarding and storing it in the drone's buffer.
+    ///
+    /// This method processes a packet fragment by either dropping it, based on the drone's PDR, or forwarding it
+    /// to the next hop. If the fragment is not dropped, it is added to the drone's buffer to be potentially retransmitted
+    /// later. If the fragment is dropped, a NACK is sent to notify the sender of the packet drop.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` containing the routing header and session ID, which the fragment belongs to.
+    /// - `fragment`: The `Fragment` that is part of the `packet` and contains the fragmented data.
+    ///
+    /// # Behavior
+    /// - **If the fragment was already forwarded** (based on the `duplicate_filter`):
+    ///   - The duplicate is discarded, reported to the simulation controller as dropped and with a
+    ///     `DuplicateFragment` event.
+    /// - **If the fragment is dropped** (based on the `check_drop_fragment` method):
+    ///   - A message is printed to indicate that the fragment was dropped by the drone.
+    ///   - A NACK is sent to the previous node, indicating that the fragment was dropped.
+    /// - **If the fragment is not dropped**:
+    ///   - The fragment is added to the drone's buffer using its `session_id` and the fragment's `fragment_index`.
+    ///   - A message is printed to indicate that the fragment was successfully added to the buffer.
+    ///   - The fragment is recorded in the `duplicate_filter`.
+    ///   - The `packet` is forwarded to the next hop by calling `send_message`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// let fragment = Fragment { /* fragment data */ };
+    /// core.handle_fragment(packet, fragment);
+    /// ```
+    fn handle_fragment(&mut self, mut packet: Packet, fragment: Fragment) {
+        // The routing header was validated on entry, it is not empty
+        let source = packet.routing_header.hops[0];
+        if self
+            .duplicate_filter
+            .contains(source, packet.session_id, fragment.fragment_index)
+        {
+            warn!(
+                "{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ] from [ Node {} ] was already forwarded by [ Drone {} ], discarding the duplicate",
+                "!!!".yellow(),
+                fragment.fragment_index,
+                packet.session_id,
+                source,
+                self.id
+            );
+            self.actions
+                .push(DroneAction::Event(RustasticEvent::DuplicateFragment {
+                    drone_id: self.id,
+                    source_id: source,
+                    session_id: packet.session_id,
+                    fragment_index: fragment.fragment_index,
+                }));
+            packet.routing_header.decrease_hop_index();
+            self.actions.push(DroneAction::Dropped(packet));
+            return;
+        }
+
+        if self.check_drop_fragment() {
+            warn!(
+                "{} Fragment [ fragment_index: {} ] of the Packet [ session_id: {} ] has been dropped by [ Drone {} ]",
+                "!!!".yellow(),
+                fragment.fragment_index,
+                packet.session_id,
+                self.id
+            );
+            self.send_nack(packet.clone(), Some(fragment), NackType::Dropped);
+            packet.routing_header.decrease_hop_index();
+            self.actions.push(DroneAction::Dropped(packet));
+        } else {
+            // Add the fragment to the buffer
+            info!(
+                "{} [ Drone {} ]: forwarded the the fragment [ fragment_index: {} ] of the Packet [ session_id: {} ]",
+                "✓".green(),
+                self.id,
+                fragment.fragment_index,
+                packet.session_id
+            );
+
+            self.buffer.add_fragment(
+                packet.clone().session_id,
+                fragment.fragment_index,
+                packet.clone(),
+            );
+
+            warn!(
+                "└─>{} Fragment was added to the [ Drone {} ] buffer",
+                "!!!".yellow(),
+                self.id
+            );
+
+            self.duplicate_filter
+                .insert(source, packet.session_id, fragment.fragment_index);
+            self.send_message(packet);
+        }
+    }
+
+    /// Sends a NACK (Negative Acknowledgment) to the previous hop or to the simulation controller in case of an error.
+    ///
+    /// This function sends a NACK message back to the previous drone in the routing path when there is a problem with
+    /// the packet or its fragment, such as a routing error or dropped packet. If the previous drone is not a neighbor,
+    /// the NACK is sent to the simulation controller to notify of the failure.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` that needs to be acknowledged (or nacked). This packet is either a regular packet or a fragment of a larger message.
+    /// - `fragment`: An optional `Fragment` object that contains specific fragment data, if the NACK is related to a dropped fragment.
+    /// - `nack_type`: The `NackType` representing the type of error or problem that occurred. This can be a dropped packet, an unexpected recipient, etc.
+    ///
+    /// # Behavior
+    /// - The function reverses the routing header to determine the previous hop in the routing path and sends the NACK to that drone.
+    /// - If the NACK is related to a fragment (i.e., the `fragment` argument is `Some`), the function updates the NACK's fragment index and type accordingly.
+    /// - If the previous hop is not a neighbor, it sends the NACK to the simulation controller and logs the event.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// let fragment = Some(Fragment { /* fragment data */ });
+    /// let nack_type = NackType::Dropped;
+    /// core.send_nack(packet, fragment, nack_type);
+    /// ```
+    fn send_nack(&mut self, mut packet: Packet, fragment: Option<Fragment>, nack_type: NackType) {
+        packet
+            .routing_header
+            .hops
+            .drain(packet.routing_header.hop_index..);
+
+        if let NackType::UnexpectedRecipient(id) = nack_type {
+            packet.routing_header.hops.push(id);
+        }
+
+        packet.routing_header.hops.reverse();
+
+        packet.routing_header.hop_index = 1;
+
+        let Some(prev_hop) = packet.routing_header.current_hop() else {
+            error!(
+                "{} [ Drone {} ]: can't send the Nack, the routing header has no previous hop",
+                "✗".red(),
+                self.id
+            );
+            self.report_protocol_violation(packet, ProtocolViolation::MissingPreviousHop);
+            return;
+        };
+
+        let nack = Nack {
+            fragment_index: match fragment {
+                Some(frag) => frag.fragment_index,
+                None => 0,
+            }, // Default fragment index for non-fragmented NACKs
+            nack_type,
+        };
+
+        packet.pack_type = PacketType::Nack(nack);
+
+        if self.neighbors.contains(&prev_hop) {
+            warn!(
+                "{} Nack is being sent from [ Drone {} ] to [ Drone {} ]",
+                "!!!".yellow(),
+                self.id,
+                prev_hop
+            );
+            self.actions.push(DroneAction::Send {
+                to: prev_hop,
+                packet,
+            });
+        } else {
+            // If no connection to the previous hop, send the NACK to the simulation controller
+            error!(
+                "{} [ Drone {} ]: Failed to send the Nack: No connection to {}",
+                "✗".red(),
+                self.id,
+                prev_hop
+            );
+            self.shortcut(packet);
+        }
+    }
+
+    /// Checks if the destination of a packet is a neighboring drone.
+    ///
+    /// This function checks whether the destination drone, as indicated in the packet's routing header,
+    /// is one of the `neighbors` of the current drone.
+    ///
+    /// # Arguments
+    /// - `packet`: The `Packet` whose destination is being checked. The packet contains a routing header
+    ///   that specifies the destination drone and the current hop index.
+    ///
+    /// # Returns
+    /// - `true` if the destination of the packet is a neighbor of the current drone.
+    /// - `false` if the destination is not a neighbor.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let packet = Packet { /* packet data */ };
+    /// let is_neighbor = core.check_neighbor(packet);
+    /// if is_neighbor {
+    ///     println!("The destination is a neighbor.");
+    /// } else {
+    ///     println!("The destination is not a neighbor.");
+    /// }
+    /// ```
+    fn check_neighbor(&self, packet: &Packet) -> bool {
+        packet
+            .routing_header
+            .current_hop()
+            .is_some_and(|destination| self.neighbors.contains(&destination))
+    }
+
+    // Determines if a packet fragment should be dropped based on the Packet Drop Rate (PDR).
+    ///
+    /// This function simulates the packet drop behavior based on the current drone's Packet Drop Rate (PDR).
+    /// It generates a random number between 1 and 100 and compares it to the scaled PDR (multiplied by 100).
+    /// If the random value is less than or equal to the scaled PDR, the fragment is considered to be dropped.
+    ///
+    /// # Returns
+    /// - `true` if the packet fragment should be dropped based on the current PDR.
+    /// - `false` if the packet fragment should not be dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let should_drop = core.check_drop_fragment();
+    /// if should_drop {
+    ///     println!("The packet fragment will be dropped.");
+    /// } else {
+    ///     println!("The packet fragment will not be dropped.");
+    /// }
+    /// ```
+    fn check_drop_fragment(&mut self) -> bool {
+        let val = self.rng.gen_range(1f32..=100f32);
+        val <= self.pdr * 100f32
+    }
+
+    /// Handles an incoming `FloodRequest` packet and processes it accordingly.
+    ///
+    /// This function handles the logic for processing a `FloodRequest` packet that has been received by the drone.
+    /// It checks if the flood request has already been received, processes the path trace, and either forwards
+    /// the flood request to neighbors or responds with a `FloodResponse`. The flood request is a type of routing
+    /// protocol used to propagate information across the network of drones, and the drone can either forward or
+    /// respond based on its state.
+    ///
+    /// # Arguments
+    /// - `flood_request`: The `FloodRequest` packet containing information about the flood and its path trace.
+    /// - `packet`: The full packet that contains the flood request and additional metadata, such as the routing header.
+    ///
+    /// # Return Value
+    /// Returns a `bool`:
+    /// - `true`: if the request was forwarded or answered, the flood then counts as received.
+    /// - `false`: if the request was dropped, a later copy is handled as a new flood.
+    ///
+    /// # Behavior:
+    /// - The function first determines the last node in the path trace to identify the drone that sent the request.
+    /// - It then checks if the flood request has already been received based on its `flood_id` and `initiator_id`,
+    ///   or because the drone is already in its `path_trace`: the flood looped back to it.
+    /// - If the request has already been processed, the drone sends a `FloodResponse` back to the previous node
+    ///   indicating that the flood request has already been received.
+    /// - A new flood that has crossed the maximum hop count is answered, see `DroneCore::with_max_flood_hops`.
+    /// - A new flood over the `FloodRateLimit` of its initiator is answered or dropped.
+    /// - The request is forwarded to the neighbors except the previous node. If there is none, it sends a
+    ///   `FloodResponse` to the previous node indicating that no further hops are available.
+    /// - The `path_trace` has been validated by `DroneCore::check_path_trace`: the previous node is a neighbor. It
+    ///   may be removed before the responses come back, they are then shortcut to the simulation controller.
+    ///
+    /// # Example:
+    /// ```ignore
+    /// let received = core.handle_flood_request(flood_request, packet);
+    /// ```
+    fn handle_flood_request(&mut self, mut flood_request: FloodRequest, packet: &Packet) -> bool {
+        // Determine the previous node that sent the packet
+        let prev_node = if let Some(node) = flood_request.path_trace.last() {
+            node.0
+        } else {
+            error!("A drone can't be the first node in the path-trace.");
+            return false;
+        };
+
+        // A flood that looped back to the drone went through it already
+        let looped = flood_request
+            .path_trace
+            .iter()
+            .any(|&(node_id, _)| node_id == self.id);
+
+        // Add the current drone to the path-trace
+        let hops_crossed = flood_request.path_trace.len();
+        flood_request.path_trace.push((self.id, NodeType::Drone));
+
+        // Check if the flood request has already been processed
+        if looped
+            || self
+                .flood_id_received
+                .contains(&(flood_request.flood_id, flood_request.initiator_id))
+        {
+            // If it has been processed, send a FloodResponse to the previous node
+            self.flood_stats.duplicates += 1;
+            self.respond_to_flood(
+                prev_node,
+                &flood_request,
+                packet.session_id,
+                format!(
+                    "{} [ Drone {} ]: has already received a FloodRequest with flood_id: {}",
+                    "!!!".yellow(),
+                    self.id,
+                    flood_request.flood_id
+                )
+                .as_str(),
+            );
+        } else if self
+            .max_flood_hops
+            .is_some_and(|max_hops| hops_crossed >= max_hops)
+        {
+            // The flood has travelled far enough
+            self.flood_stats.hop_limited += 1;
+            self.respond_to_flood(
+                prev_node,
+                &flood_request,
+                packet.session_id,
+                format!(
+                    "{} [ Drone {} ]: the FloodRequest with flood_id: {} has crossed {} hops",
+                    "!!!".yellow(),
+                    self.id,
+                    flood_request.flood_id,
+                    hops_crossed
+                )
+                .as_str(),
+            );
+        } else if !self.allow_flood(&flood_request) {
+            // The initiator is over its rate: the flood stops here
+            self.flood_stats.rate_limited += 1;
+            if self
+                .flood_limiter
+                .as_ref()
+                .is_some_and(|limiter| limiter.action() == FloodLimitAction::Respond)
+            {
+                self.respond_to_flood(
+                    prev_node,
+                    &flood_request,
+                    packet.session_id,
+                    format!(
+                        "{} [ Drone {} ]: [ Node {} ] is over its flood rate",
+                        "!!!".yellow(),
+                        self.id,
+                        flood_request.initiator_id
+                    )
+                    .as_str(),
+                );
+            } else {
+                return false;
+            }
+        } else {
+            // Forward the FloodRequest to all neighbors except the previous node
+            let forward_set: Vec<NodeId> = self
+                .neighbors
+                .iter()
+                .copied()
+                .filter(|&neighbor| neighbor != prev_node)
+                .collect();
+
+            if forward_set.is_empty() {
+                // Send a FloodResponse indicating no further neighbors to forward the request
+                self.flood_stats.dead_ends += 1;
+                self.respond_to_flood(
+                    prev_node,
+                    &flood_request,
+                    packet.session_id,
+                    format!(
+                        "{} [ Drone {} ]: doesn't have any other neighbors to send the FloodRequest with flood_id: {}",
+                        "!!!".yellow(),
+                        self.id,
+                        flood_request.flood_id
+                    )
+                    .as_str(),
+                );
+            } else {
+                self.flood_stats.forwarded += 1;
+                for neighbor in forward_set {
+                    self.send_flood_request(
+                        neighbor,
+                        &flood_request,
+                        packet.routing_header.clone(),
+                        packet.session_id,
+                    );
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Takes a token for a new flood from the bucket of its initiator, if floods are rate limited.
+    ///
+    /// # Arguments
+    /// - `flood_request`: The new `FloodRequest`.
+    ///
+    /// # Returns
+    /// `true` if the flood can be forwarded. Otherwise the flood is reported with a `FloodRateLimited` event.
+    fn allow_flood(&mut self, flood_request: &FloodRequest) -> bool {
+        let Some(limiter) = &mut self.flood_limiter else {
+            return true;
+        };
+        if limiter.allow(flood_request.initiator_id, self.clock) {
+            return true;
+        }
+
+        warn!(
+            "{} [ Drone {} ]: [ Node {} ] is over its flood rate, the FloodRequest with flood_id: {} is not forwarded",
+            "!!!".yellow(),
+            self.id,
+            flood_request.initiator_id,
+            flood_request.flood_id
+        );
+        self.actions
+            .push(DroneAction::Event(RustasticEvent::FloodRateLimited {
+                drone_id: self.id,
+                initiator_id: flood_request.initiator_id,
+                flood_id: flood_request.flood_id,
+            }));
+        false
+    }
+
+    /// Answers a `FloodRequest` with a `FloodResponse`, instead of forwarding it.
+    ///
+    /// The response goes back along the `path_trace` of the request, which already ends with the drone, up to
+    /// the initiator. If the flood looped back to the drone, the loop is cut out of the route: the response
+    /// leaves from the drone's first position in the trace.
+    ///
+    /// # Arguments
+    /// - `prev_node`: The node the request came from.
+    /// - `flood_request`: The `FloodRequest` to answer.
+    /// - `session_id`: The session of the request.
+    /// - `reason`: Why the request is answered, for the logs.
+    fn respond_to_flood(
+        &mut self,
+        prev_node: NodeId,
+        flood_request: &FloodRequest,
+        session_id: u64,
+        reason: &str,
+    ) {
+        let mut new_hops: Vec<u8> = flood_request
+            .path_trace
+            .iter()
+            .map(|(id, _ntype)| *id)
+            .collect();
+
+        new_hops.reverse();
+
+        if new_hops.last() != Some(&flood_request.initiator_id) {
+            new_hops.push(flood_request.initiator_id);
+        }
+
+        // A flood that looped back to the drone is answered the way it first came, without the loop
+        let mut destination = prev_node;
+        if let Some(loop_len) = new_hops.iter().skip(1).position(|&id| id == self.id) {
+            new_hops.drain(1..=loop_len + 1);
+            destination = new_hops.get(1).copied().unwrap_or(prev_node);
+        }
+
+        let new_routing_header = SourceRoutingHeader {
+            hop_index: 1,
+            hops: new_hops,
+        };
+
+        self.send_flood_response(
+            destination,
+            flood_request,
+            new_routing_header,
+            session_id,
+            reason,
+        );
+    }
+
+    /// Handles the incoming `FloodResponse` packet, processes it, and sends it back to the appropriate drone.
+    ///
+    /// This function processes a received `FloodResponse` packet by forwarding it to the next hop in the path.
+    /// If the next hop is not a neighbor, the packet is sent to the simulation controller instead. It plays a
+    /// critical role in routing flood responses back through the network of drones.
+    ///
+    /// # Arguments
+    /// - `flood_response`: The `FloodResponse` packet that was received. This packet contains the information
+    ///   about the flood response and its associated metadata.
+    /// - `packet`: The full packet, which contains additional information like the routing header and session ID.
+    ///
+    /// # Behavior:
+    /// - The function forwards the `FloodResponse` to the next drone in the path specified by the
+    ///   `routing_header` in the `packet`.
+    /// - If the next hop is not a neighbor, the packet is sent to the simulation controller to handle the issue.
+    ///
+    /// # Example:
+    /// ```ignore
+    /// core.handle_flood_response(flood_response, packet);
+    /// ```
+    fn handle_flood_response(&mut self, flood_response: &FloodResponse, packet: &Packet) {
+        let new_routing_header = packet.routing_header.clone();
+        let next_hop = new_routing_header.hops[new_routing_header.hop_index];
+
+        // Prepare a new packet to send the flood response back
+        let new_packet = Packet {
+            pack_type: PacketType::FloodResponse(flood_response.clone()),
+            routing_header: new_routing_header,
+            session_id: packet.session_id,
+        };
+
+        // Send the FloodResponse to the next hop in the routing path
+        if self.neighbors.contains(&next_hop) {
+            info!(
+                "{} [ Drone {} ]: is sending a FloodResponse with flood_id: {} to [ Drone {} ]",
+                "✓".green(),
+                self.id,
+                flood_response.flood_id,
+                next_hop
+            );
+            self.actions.push(DroneAction::Send {
+                to: next_hop,
+                packet: new_packet,
+            });
+        } else {
+            // If the next hop is unavailable, send the packet to the simulation controller
+            error!(
+                "{} [ Drone {} ]: failed to send the FloodResponse: No connection to [ Drone {} ]",
+                "✗".red(),
+                self.id,
+                next_hop
+            );
+            self.shortcut(new_packet);
+        }
+    }
+
+    /// Sends a `FloodRequest` packet to a specified destination drone.
+    ///
+    /// This function creates a new `FloodRequest` packet and sends it to the specified destination drone.
+    /// The `FloodRequest` contains information about the flood ID, initiator ID, and path trace. The packet
+    /// is routed according to the provided `routing_header` and `session_id`.
+    ///
+    /// # Arguments
+    /// - `dest_node`: The ID of the neighbor to send the packet to.
+    /// - `flood_request`: The `FloodRequest` that is being sent, containing the flood ID, initiator ID, and the
+    ///   path trace up to this point in the flood.
+    /// - `routing_header`: The `SourceRoutingHeader` which provides routing information for this packet.
+    /// - `session_id`: A unique session ID that helps track the packet across the network.
+    ///
+    /// # Example:
+    /// ```ignore
+    /// core.send_flood_request(destination_id, &flood_request, routing_header, session_id);
+    /// ```
+    fn send_flood_request(
+        &mut self,
+        dest_node: NodeId,
+        flood_request: &FloodRequest,
+        routing_header: SourceRoutingHeader,
+        session_id: u64,
+    ) {
+        let new_flood_request = FloodRequest {
+            flood_id: flood_request.flood_id,
+            initiator_id: flood_request.initiator_id,
+            path_trace: flood_request.path_trace.clone(),
+        };
+
+        let new_packet = Packet {
+            pack_type: PacketType::FloodRequest(new_flood_request),
+            routing_header,
+            session_id,
+        };
+
+        info!(
+            "{} [ Drone {} ]: is sending a FloodRequest with flood_id: {} to the [ Drone {} ]",
+            "✓".green(),
+            self.id,
+            flood_request.flood_id,
+            dest_node
+        );
+
+        self.actions.push(DroneAction::Send {
+            to: dest_node,
+            packet: new_packet,
+        });
+    }
+
+    /// Sends a `FloodResponse` packet to a specified destination drone.
+    ///
+    /// This function creates a `FloodResponse` packet and sends it to a destination drone. The response includes
+    /// the flood ID and the path trace that was followed during the flood request. The packet is routed according
+    /// to the provided `routing_header` and is identified by the provided `session_id`.
+    ///
+    /// # Arguments
+    /// - `dest_node`: The ID of the destination drone to send the response to.
+    /// - `flood_request`: The `FloodRequest` that prompted this response. It contains the `flood_id` and `path_trace`.
+    /// - `routing_header`: The `SourceRoutingHeader` containing routing information for the packet.
+    /// - `session_id`: A unique session ID used to track the packet across the network.
+    /// - `reason`: A description or reason for sending the response. This will be logged along with the action.
+    ///
+    /// # Behavior:
+    /// - The function creates a `FloodResponse` packet based on the `flood_request` and the `routing_header`.
+    /// - If `dest_node` is a neighbor the response is sent to it, otherwise it is sent to the simulation controller.
+    ///
+    /// # Example:
+    /// ```ignore
+    /// core.send_flood_response(destination_id, &flood_request, routing_header, session_id, "FloodRequest already received");
+    /// ```
+    fn send_flood_response(
+        &mut self,
+        dest_node: NodeId,
+        flood_request: &FloodRequest,
+        routing_header: SourceRoutingHeader,
+        session_id: u64,
+        reason: &str,
+    ) {
+        let flood_response = FloodResponse {
+            flood_id: flood_request.flood_id,
+            path_trace: flood_request.path_trace.clone(),
+        };
+
+        let new_packet = Packet {
+            pack_type: PacketType::FloodResponse(flood_response),
+            routing_header,
+            session_id,
+        };
+
+        if self.neighbors.contains(&dest_node) {
+            info!(
+                "{} [ Drone {} ]: is sending the FloodResponse to [ Drone {} ]\n└─>Reason: {}",
+                "✓".green(),
+                self.id,
+                dest_node,
+                reason
+            );
+            self.actions.push(DroneAction::Send {
+                to: dest_node,
+                packet: new_packet,
+            });
+        } else {
+            // Handle the case where there is no connection to the destination drone
+            error!(
+                "{} [ Drone {} ]: Failed to send the FloodResponse: No connection to [ Drone {} ]",
+                "✗".red(),
+                self.id,
+                dest_node
+            );
+            self.shortcut(new_packet);
+        }
+    }
+}
@@ -0,0 +1,132 @@
+//! This file contains the Rustastic Drone's duplicate filter, developed by the Group Rustastic.
+//!
+//! File:   drone/duplicate_filter.rs
+//!
+//! Brief:  File for the filter that stops the Rustastic Drone from forwarding the same fragment twice.
+//!
+//! Author: Rustastic
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use wg_2024::network::NodeId;
+
+/// A session, as picked by its source: two sources may use the same `session_id`.
+type SessionKey = (NodeId, u64);
+
+/// Remembers the most recently forwarded fragments, grouped by source and session.
+///
+/// The filter holds at most `window` fragments: when it is full, the oldest fragment is forgotten
+/// to make space for the new one. A fragment that is still in the window is a duplicate.
+#[derive(Clone, Debug)]
+pub struct DuplicateFilter {
+    /// Stores the forwarded `fragment_index`es of each session of each source.
+    sessions: HashMap<SessionKey, HashSet<u64>>,
+    /// Maintains the forwarding order of `((source, session_id), fragment_index)` keys, to forget the oldest one.
+    order: VecDeque<(SessionKey, u64)>,
+    /// Maximum number of fragments remembered, `0` disables the filter.
+    window: usize,
+}
+
+impl DuplicateFilter {
+    /// Creates a new `DuplicateFilter` that remembers up to `window` fragments.
+    ///
+    /// # Parameters
+    ///
+    /// - `window`: The number of fragments to remember, `0` disables the filter.
+    ///
+    /// # Returns
+    ///
+    /// A new `DuplicateFilter` instance.
+    pub fn new(window: usize) -> Self {
+        Self {
+            sessions: HashMap::new(),
+            order: VecDeque::new(),
+            window,
+        }
+    }
+
+    /// Checks if a fragment was already forwarded within the window.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: The node that sent the fragment, the first hop of its routing header.
+    /// - `session_id`: The session ID of the fragment.
+    /// - `fragment_index`: The index of the fragment inside the session.
+    ///
+    /// # Returns
+    ///
+    /// - `true`: if the fragment is a duplicate.
+    /// - `false`: if the fragment is new, or the filter is disabled.
+    pub fn contains(&self, source: NodeId, session_id: u64, fragment_index: u64) -> bool {
+        self.sessions
+            .get(&(source, session_id))
+            .is_some_and(|fragments| fragments.contains(&fragment_index))
+    }
+
+    /// Records a forwarded fragment.
+    ///
+    /// If the filter is full, the oldest fragment is forgotten to make space for the new one.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: The node that sent the fragment, the first hop of its routing header.
+    /// - `session_id`: The session ID of the fragment.
+    /// - `fragment_index`: The index of the fragment inside the session.
+    pub fn insert(&mut self, source: NodeId, session_id: u64, fragment_index: u64) {
+        if self.window == 0 || self.contains(source, session_id, fragment_index) {
+            return;
+        }
+
+        if self.order.len() >= self.window {
+            if let Some((oldest_session, oldest_index)) = self.order.pop_front() {
+                self.forget(oldest_session, oldest_index);
+            }
+        }
+
+        self.sessions
+            .entry((source, session_id))
+            .or_default()
+            .insert(fragment_index);
+        self.order.push_back(((source, session_id), fragment_index));
+    }
+
+    /// Forgets a fragment, so that its next copy is forwarded again.
+    ///
+    /// This is used when the forwarded copy was lost: a NACK showed it, or the drone itself dropped it.
+    ///
+    /// # Parameters
+    ///
+    /// - `source`: The node that sent the fragment, the first hop of its routing header.
+    /// - `session_id`: The session ID of the fragment.
+    /// - `fragment_index`: The index of the fragment inside the session.
+    pub fn remove(&mut self, source: NodeId, session_id: u64, fragment_index: u64) {
+        let key = ((source, session_id), fragment_index);
+        if let Some(pos) = self.order.iter().position(|&k| k == key) {
+            self.order.remove(pos);
+            self.forget(key.0, fragment_index);
+        }
+    }
+
+    /// Updates the number of fragments remembered, forgetting the oldest ones if needed.
+    ///
+    /// # Parameters
+    ///
+    /// - `window`: The new window, `0` disables the filter.
+    pub fn set_window(&mut self, window: usize) {
+        self.window = window;
+        while self.order.len() > window {
+            if let Some((oldest_session, oldest_index)) = self.order.pop_front() {
+                self.forget(oldest_session, oldest_index);
+            }
+        }
+    }
+
+    /// Removes a fragment from its session, dropping the session once it is empty.
+    fn forget(&mut self, session: SessionKey, fragment_index: u64) {
+        if let Some(fragments) = self.sessions.get_mut(&session) {
+            fragments.remove(&fragment_index);
+            if fragments.is_empty() {
+                self.sessions.remove(&session);
+            }
+        }
+    }
+}
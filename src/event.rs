@@ -0,0 +1,100 @@
+//! This file contains the Rustastic Drone's extended events, developed by the Group Rustastic.
+//!
+//! File:   drone/event.rs
+//!
+//! Brief:  File for the events that the Rustastic Drone reports on top of the standard `DroneEvent`s.
+//!
+//! Author: Rustastic
+
+use wg_2024::{network::NodeId, packet::Packet};
+
+/// An event reported by a `RustasticDrone` in addition to the standard `DroneEvent`s.
+///
+/// `DroneEvent` is defined by the shared protocol crate and cannot be extended, so the drone
+/// reports its own diagnostics on a separate, optional channel (see `RustasticDrone::with_event_send`).
+/// A `DroneCore` returns them as `DroneAction::Event`s.
+#[derive(Debug, Clone)]
+pub enum RustasticEvent {
+    /// A packet received by the drone does not respect the protocol and was not forwarded.
+    ProtocolViolation {
+        drone_id: NodeId,
+        packet: Box<Packet>,
+        violation: ProtocolViolation,
+    },
+    /// A fragment that the drone had already forwarded was received again and discarded.
+    ///
+    /// The duplicate is reported to the simulation controller as a `PacketDropped` as well.
+    DuplicateFragment {
+        drone_id: NodeId,
+        source_id: NodeId,
+        session_id: u64,
+        fragment_index: u64,
+    },
+    /// A packet was dropped because the link to `neighbor_id` was saturated (see `LinkShaping`).
+    ///
+    /// `Ack`s, `Nack`s and `FloodResponse`s must not be lost: they are delivered through the simulation
+    /// controller with a `ControllerShortcut` as well.
+    ShapingDrop {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+        packet: Box<Packet>,
+    },
+    /// The channel to `neighbor_id` refused a packet: the packets for it wait in an outbound queue.
+    NeighborCongested {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+    },
+    /// The outbound queue of `neighbor_id` has been emptied, packets are sent right away again.
+    NeighborRecovered {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+    },
+    /// A packet was dropped because the outbound queue of the congested `neighbor_id` was full
+    /// (see `OverflowPolicy`).
+    ///
+    /// `Ack`s, `Nack`s and `FloodResponse`s are delivered through the simulation controller as well.
+    OverflowDrop {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+        packet: Box<Packet>,
+    },
+    /// A new flood of `initiator_id` was not forwarded, the initiator being over its `FloodRateLimit`.
+    FloodRateLimited {
+        drone_id: NodeId,
+        initiator_id: NodeId,
+        flood_id: u64,
+    },
+    /// `neighbor_id` could not be reached `failures` times in a row: the drone stopped routing to it
+    /// (see `LivenessPolicy`).
+    NeighborDead {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+        failures: u32,
+    },
+    /// The dead `neighbor_id` was added again by the simulation controller, the drone routes to it again.
+    NeighborRestored {
+        drone_id: NodeId,
+        neighbor_id: NodeId,
+    },
+}
+
+/// The reason why a packet was rejected as malformed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolViolation {
+    /// The routing header does not contain any hop.
+    EmptyRoute,
+    /// The `hop_index` of the routing header points outside of `hops`.
+    HopIndexOutOfRange { hop_index: usize, hops_len: usize },
+    /// The drone has to answer to the previous hop, but the routing header does not contain one.
+    MissingPreviousHop,
+    /// The routing header visits `node_id` more than once.
+    RoutingLoop { node_id: NodeId },
+    /// The `path_trace` of a `FloodRequest` is empty: it doesn't even hold its initiator.
+    EmptyPathTrace,
+    /// The `path_trace` of a `FloodRequest` doesn't start with its initiator.
+    InitiatorNotFirst { initiator_id: NodeId, first: NodeId },
+    /// The `path_trace` of a `FloodRequest` holds `node_id` more than once, or already holds the drone.
+    DuplicateInPathTrace { node_id: NodeId },
+    /// The last node of the `path_trace` of a `FloodRequest`, which sent it, is not a neighbor of the drone.
+    UnknownPreviousHop { node_id: NodeId },
+}
@@ -0,0 +1,159 @@
+//! This file contains the Rustastic Drone's topology exporters, developed by the Group Rustastic.
+//!
+//! File:   drone/export.rs
+//!
+//! Brief:  File for the Graphviz DOT and JSON renderings of the `Topology` observed by a drone.
+//!
+//! Author: Rustastic
+
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Instant;
+use wg_2024::{network::NodeId, packet::NodeType};
+
+use crate::topology::{LinkCounters, Topology};
+
+/// A link as exported: its last-seen time in a trace, whether it is a link of the drone itself, and its counters.
+struct ExportedEdge {
+    last_seen: Option<Instant>,
+    local: bool,
+    counters: LinkCounters,
+}
+
+impl Topology {
+    /// Returns the nodes to export with their types, if known: the learned nodes, the drone and its neighbors.
+    fn exported_nodes(&self) -> BTreeMap<NodeId, Option<NodeType>> {
+        let mut nodes: BTreeMap<NodeId, Option<NodeType>> = self
+            .nodes()
+            .into_iter()
+            .map(|node| (node.id, Some(node.node_type)))
+            .collect();
+        if let Some((drone_id, neighbors)) = self.local_links() {
+            nodes.insert(drone_id, Some(NodeType::Drone));
+            for neighbor in neighbors {
+                nodes.entry(neighbor).or_insert(None);
+            }
+        }
+        nodes
+    }
+
+    /// Returns the links to export: the learned links and the links of the drone.
+    fn exported_edges(&self) -> BTreeMap<(NodeId, NodeId), ExportedEdge> {
+        let mut edges: BTreeMap<(NodeId, NodeId), ExportedEdge> = self
+            .edges()
+            .into_iter()
+            .map(|edge| {
+                let exported = ExportedEdge {
+                    last_seen: Some(edge.last_seen),
+                    local: false,
+                    counters: self.counters(edge.a, edge.b),
+                };
+                ((edge.a, edge.b), exported)
+            })
+            .collect();
+        if let Some((drone_id, neighbors)) = self.local_links() {
+            for neighbor in neighbors {
+                let key = (drone_id.min(neighbor), drone_id.max(neighbor));
+                edges
+                    .entry(key)
+                    .or_insert_with(|| ExportedEdge {
+                        last_seen: None,
+                        local: true,
+                        counters: self.counters(drone_id, neighbor),
+                    })
+                    .local = true;
+            }
+        }
+        edges
+    }
+
+    /// Renders the topology as a Graphviz DOT graph.
+    ///
+    /// Nodes are colored by `NodeType` (gray if unknown) and the drone that owns the topology is circled twice.
+    /// Links are labelled with their drop and NACK counts; the links only learned from traces are dashed.
+    ///
+    /// # Returns
+    /// The source of an undirected DOT graph, e.g. for `dot -Tsvg`.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let drone_id = self.local_links().map(|(drone_id, _)| drone_id);
+
+        let mut dot = String::from("graph topology {\n    node [style=filled];\n");
+        for (node_id, node_type) in self.exported_nodes() {
+            let (kind, color) = match node_type {
+                Some(NodeType::Client) => ("Client", "lightblue"),
+                Some(NodeType::Drone) => ("Drone", "orange"),
+                Some(NodeType::Server) => ("Server", "palegreen"),
+                None => ("Node", "lightgray"),
+            };
+            let shape = if drone_id == Some(node_id) {
+                ", shape=doublecircle"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                dot,
+                "    {node_id} [label=\"{kind} {node_id}\", fillcolor={color}{shape}];"
+            );
+        }
+        for ((a, b), edge) in self.exported_edges() {
+            let style = if edge.local { "" } else { ", style=dashed" };
+            let _ = writeln!(
+                dot,
+                "    {a} -- {b} [label=\"drops: {}, nacks: {}\"{style}];",
+                edge.counters.drops, edge.counters.nacks
+            );
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the topology as JSON.
+    ///
+    /// Times are exported as the milliseconds elapsed since the node or link was last seen in a trace, `null`
+    /// for the ones never seen in a trace.
+    ///
+    /// # Returns
+    /// An object with the `drone` that owns the topology, its `nodes` (`id`, `type`, `seen_ms_ago`) and its
+    /// `edges` (`a`, `b`, `local`, `seen_ms_ago`, `drops`, `nacks`).
+    #[must_use]
+    pub fn to_json(&self) -> Value {
+        let now = Instant::now();
+        let age = |last_seen: Instant| {
+            u64::try_from(now.saturating_duration_since(last_seen).as_millis()).unwrap_or(u64::MAX)
+        };
+
+        let nodes: Vec<Value> = self
+            .exported_nodes()
+            .into_iter()
+            .map(|(node_id, node_type)| {
+                json!({
+                    "id": node_id,
+                    "type": node_type.map(|node_type| format!("{node_type:?}")),
+                    "seen_ms_ago": self.node(node_id).map(|node| age(node.last_seen)),
+                })
+            })
+            .collect();
+        let edges: Vec<Value> = self
+            .exported_edges()
+            .into_iter()
+            .map(|((a, b), edge)| {
+                json!({
+                    "a": a,
+                    "b": b,
+                    "local": edge.local,
+                    "seen_ms_ago": edge.last_seen.map(age),
+                    "drops": edge.counters.drops,
+                    "nacks": edge.counters.nacks,
+                })
+            })
+            .collect();
+
+        json!({
+            "drone": self.local_links().map(|(drone_id, _)| drone_id),
+            "nodes": nodes,
+            "edges": edges,
+        })
+    }
+}
@@ -0,0 +1,230 @@
+//! This file contains the Rustastic Drone's fair queuing, developed by the Group Rustastic.
+//!
+//! File:   drone/fair_queue.rs
+//!
+//! Brief:  File for the queue that shares the drone's forwarding capacity between the flows of fragments
+//!         with deficit round robin, and for its statistics.
+//!
+//! Author: Rustastic
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use wg_2024::{
+    network::NodeId,
+    packet::{Packet, PacketType},
+};
+
+use crate::policy::FairQueuing;
+
+/// The bytes a flow can forward in each round: the data of a full fragment.
+const QUANTUM: usize = 128;
+
+/// How many flows the statistics are kept for: beyond that, the flow idle for the longest is forgotten.
+const MAX_TRACKED_FLOWS: usize = 256;
+
+/// The flow a fragment belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlowKey {
+    /// The fragments of a session.
+    Session(u64),
+    /// The fragments sent by a source node.
+    Source(NodeId),
+}
+
+/// Statistics of a flow of fragments, see `RustasticDrone::flow_stats`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlowStats {
+    /// The flow.
+    pub key: FlowKey,
+    /// Fragments of the flow waiting to be forwarded.
+    pub queued: usize,
+    /// Fragments of the flow handed out for forwarding.
+    pub served: u64,
+    /// Bytes of data of the fragments handed out for forwarding.
+    pub served_bytes: u64,
+    /// The share of the bytes served to the flows reported that went to this flow, between `0.0` and `1.0`.
+    pub share: f64,
+}
+
+/// The fragments of a flow waiting to be forwarded.
+///
+/// # Fields
+/// - `queue`: The fragments, in arrival order.
+/// - `deficit`: The bytes the flow can still forward in the current round.
+/// - `visited`: Whether the flow has received its quantum for the current round.
+#[derive(Debug, Clone, Default)]
+struct Flow {
+    queue: VecDeque<Packet>,
+    deficit: usize,
+    visited: bool,
+}
+
+/// What a flow was served so far.
+///
+/// # Fields
+/// - `fragments`, `bytes`: The fragments and bytes of data handed out for forwarding.
+/// - `last`: When the flow was last served, counted in fragments served by the queue.
+#[derive(Debug, Clone, Copy, Default)]
+struct Served {
+    fragments: u64,
+    bytes: u64,
+    last: u64,
+}
+
+/// The fragments waiting to be forwarded by a drone, served in arrival order or by deficit round robin.
+///
+/// # Fields
+/// - `fair_queuing`: How fragments are grouped in flows.
+/// - `fifo`: The fragments, when fair queuing is off.
+/// - `flows`: The flows with fragments waiting.
+/// - `active`: The order in which the flows with fragments waiting are served.
+/// - `served`: What the most recently served flows were served, at most `MAX_TRACKED_FLOWS` of them.
+/// - `total_served`: The fragments served by the queue so far.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FairQueue {
+    fair_queuing: FairQueuing,
+    fifo: VecDeque<Packet>,
+    flows: HashMap<FlowKey, Flow>,
+    active: VecDeque<FlowKey>,
+    served: HashMap<FlowKey, Served>,
+    total_served: u64,
+}
+
+impl FairQueue {
+    /// Changes how fragments are grouped in flows, keeping the fragments waiting.
+    pub(crate) fn set_fair_queuing(&mut self, fair_queuing: FairQueuing) {
+        let mut waiting = Vec::new();
+        while let Some(packet) = self.pop() {
+            waiting.push(packet);
+        }
+        *self = Self {
+            fair_queuing,
+            ..Self::default()
+        };
+        for packet in waiting {
+            self.push(packet);
+        }
+    }
+
+    fn key(&self, packet: &Packet) -> Option<FlowKey> {
+        match self.fair_queuing {
+            FairQueuing::Off => None,
+            FairQueuing::PerSession => Some(FlowKey::Session(packet.session_id)),
+            FairQueuing::PerSource => Some(FlowKey::Source(
+                packet
+                    .routing_header
+                    .hops
+                    .first()
+                    .copied()
+                    .unwrap_or_default(),
+            )),
+        }
+    }
+
+    pub(crate) fn push(&mut self, packet: Packet) {
+        let Some(key) = self.key(&packet) else {
+            self.fifo.push_back(packet);
+            return;
+        };
+
+        let flow = self.flows.entry(key).or_insert_with(|| {
+            self.active.push_back(key);
+            Flow::default()
+        });
+        flow.queue.push_back(packet);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.fifo.is_empty() && self.active.is_empty()
+    }
+
+    /// Removes the next fragment to forward.
+    pub(crate) fn pop(&mut self) -> Option<Packet> {
+        if let Some(packet) = self.fifo.pop_front() {
+            return Some(packet);
+        }
+
+        loop {
+            let key = *self.active.front()?;
+            let flow = self.flows.get_mut(&key)?;
+            if !flow.visited {
+                flow.deficit += QUANTUM;
+                flow.visited = true;
+            }
+
+            let cost = flow.queue.front().map_or(0, data_len);
+            if flow.deficit < cost {
+                // The flow waits for the next round
+                flow.visited = false;
+                self.active.rotate_left(1);
+                continue;
+            }
+
+            flow.deficit -= cost;
+            let packet = flow.queue.pop_front();
+            if flow.queue.is_empty() {
+                // An idle flow doesn't keep its deficit
+                self.flows.remove(&key);
+                self.active.pop_front();
+            }
+
+            self.record_served(key, cost);
+            return packet;
+        }
+    }
+
+    /// Counts a fragment served to a flow, forgetting the flow idle for the longest if too many are tracked.
+    fn record_served(&mut self, key: FlowKey, bytes: usize) {
+        self.total_served += 1;
+        let served = self.served.entry(key).or_default();
+        served.fragments += 1;
+        served.bytes += bytes as u64;
+        served.last = self.total_served;
+
+        if self.served.len() > MAX_TRACKED_FLOWS {
+            let idlest = self
+                .served
+                .iter()
+                .filter(|(key, _)| !self.flows.contains_key(key))
+                .min_by_key(|(_, served)| served.last)
+                .map(|(key, _)| *key);
+            if let Some(idlest) = idlest {
+                self.served.remove(&idlest);
+            }
+        }
+    }
+
+    /// Returns the statistics of the flows waiting and of the most recently served ones, in increasing order
+    /// of key.
+    pub(crate) fn stats(&self) -> Vec<FlowStats> {
+        let total: u64 = self.served.values().map(|served| served.bytes).sum();
+
+        let mut stats: BTreeMap<FlowKey, FlowStats> = BTreeMap::new();
+        for key in self.served.keys().chain(self.flows.keys()) {
+            let served = self.served.get(key).copied().unwrap_or_default();
+            let (served, served_bytes) = (served.fragments, served.bytes);
+            stats.insert(
+                *key,
+                FlowStats {
+                    key: *key,
+                    queued: self.flows.get(key).map_or(0, |flow| flow.queue.len()),
+                    served,
+                    served_bytes,
+                    share: if total == 0 {
+                        0.0
+                    } else {
+                        served_bytes as f64 / total as f64
+                    },
+                },
+            );
+        }
+        stats.into_values().collect()
+    }
+}
+
+/// Returns the bytes of data of a fragment.
+fn data_len(packet: &Packet) -> usize {
+    match &packet.pack_type {
+        PacketType::MsgFragment(fragment) => usize::from(fragment.length),
+        _ => 0,
+    }
+}
@@ -0,0 +1,119 @@
+//! This file contains the Rustastic Drone's flood storm protection, developed by the Group Rustastic.
+//!
+//! File:   drone/flood_limit.rs
+//!
+//! Brief:  File for the `FloodRateLimit`, which limits with a token bucket per initiator how many new
+//!         floods a drone forwards.
+//!
+//! Author: Rustastic
+
+use std::collections::HashMap;
+use std::time::Duration;
+use wg_2024::network::NodeId;
+
+/// What a drone does with a new `FloodRequest` over the rate of its initiator.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FloodLimitAction {
+    /// The flood is answered right away with a `FloodResponse`, as if the drone had no other neighbor.
+    #[default]
+    Respond,
+    /// The flood is dropped.
+    Drop,
+}
+
+/// How many new floods per second a drone forwards for each initiator, with a token bucket.
+///
+/// The bucket of an initiator holds up to `burst` floods and is refilled at `rate` floods per second. Only new
+/// floods take a token: a `FloodRequest` already seen is answered as usual.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FloodRateLimit {
+    /// The floods per second forwarded for each initiator.
+    pub rate: f64,
+    /// The floods an initiator can start at once after being quiet.
+    pub burst: f64,
+    /// What happens to the floods over the limit.
+    pub action: FloodLimitAction,
+}
+
+impl FloodRateLimit {
+    /// Creates a `FloodRateLimit` of `rate` floods per second, with a burst of one second, answering the floods
+    /// over the limit.
+    #[must_use]
+    pub fn per_second(rate: f64) -> Self {
+        Self {
+            rate,
+            burst: rate.max(1.0),
+            action: FloodLimitAction::default(),
+        }
+    }
+
+    /// Sets the size of the bucket of each initiator.
+    ///
+    /// # Returns
+    /// The same `FloodRateLimit`, for chaining after its constructor.
+    #[must_use]
+    pub fn with_burst(mut self, burst: f64) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Sets what happens to the floods over the limit.
+    ///
+    /// # Returns
+    /// The same `FloodRateLimit`, for chaining after its constructor.
+    #[must_use]
+    pub fn with_action(mut self, action: FloodLimitAction) -> Self {
+        self.action = action;
+        self
+    }
+}
+
+/// The token buckets of the initiators of the floods crossing a drone.
+///
+/// Time is measured by the clock of the `DroneCore`, so that the limiter runs on the virtual time of a
+/// `Simulation` as well as on the real one.
+///
+/// # Fields
+/// - `limit`: The `FloodRateLimit` of every initiator.
+/// - `buckets`: The tokens of each initiator and when they were last refilled, keyed by its ID.
+#[derive(Debug, Clone)]
+pub(crate) struct FloodLimiter {
+    limit: FloodRateLimit,
+    buckets: HashMap<NodeId, (f64, Duration)>,
+}
+
+impl FloodLimiter {
+    pub(crate) fn new(limit: FloodRateLimit) -> Self {
+        Self {
+            limit,
+            buckets: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn action(&self) -> FloodLimitAction {
+        self.limit.action
+    }
+
+    /// Takes a token from the bucket of `initiator_id`.
+    ///
+    /// # Returns
+    /// `true` if the flood can be forwarded, `false` if the initiator is over its rate.
+    pub(crate) fn allow(&mut self, initiator_id: NodeId, now: Duration) -> bool {
+        let limit = self.limit;
+        let (tokens, refilled_at) = self
+            .buckets
+            .entry(initiator_id)
+            .or_insert((limit.burst, now));
+
+        let elapsed = now.saturating_sub(*refilled_at);
+        *tokens = (*tokens + elapsed.as_secs_f64() * limit.rate).min(limit.burst);
+        *refilled_at = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
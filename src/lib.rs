@@ -1,4 +1,37 @@
+mod backpressure;
+pub mod codec;
+mod delay;
 mod drone;
+mod drone_core;
+mod duplicate_filter;
+mod event;
+mod export;
+mod fair_queue;
+mod flood_limit;
+mod liveness;
+mod network;
 mod packet_buffer;
+mod policy;
+mod priority;
+mod scheduler;
+mod shaping;
+mod simulation;
+mod socket_transport;
+mod topology;
+mod transport;
 
+pub use delay::LinkDelay;
 pub use drone::*;
+pub use drone_core::{DroneAction, DroneCore, DroneInput, FloodStats};
+pub use event::*;
+pub use fair_queue::{FlowKey, FlowStats};
+pub use flood_limit::{FloodLimitAction, FloodRateLimit};
+pub use network::{parse_config, read_config, Network, NetworkError};
+pub use packet_buffer::{BufferStats, PacketBuffer};
+pub use policy::*;
+pub use priority::TrafficClass;
+pub use shaping::{LinkShaping, RateLimit, SaturationPolicy};
+pub use simulation::{Simulation, SimulationEvent, DEFAULT_LINK_LATENCY};
+pub use socket_transport::{SocketAddress, SocketTransport};
+pub use topology::{LearnedEdge, LearnedNode, LinkCounters, Topology};
+pub use transport::{ChannelTransport, PacketTransport, TransportError};
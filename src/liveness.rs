@@ -0,0 +1,98 @@
+//! This file contains the Rustastic Drone's neighbor liveness detection, developed by the Group Rustastic.
+//!
+//! File:   drone/liveness.rs
+//!
+//! Brief:  File for the `Liveness` tracker, which counts the failed sends to each neighbor and remembers
+//!         the neighbors declared dead.
+//!
+//! Author: Rustastic
+
+use std::collections::{BTreeSet, HashMap};
+use wg_2024::network::NodeId;
+
+use crate::policy::LivenessPolicy;
+
+/// The failed sends to the neighbors of a drone, and the neighbors it gave up on.
+///
+/// # Fields
+/// - `policy`: The `LivenessPolicy` deciding when a neighbor is dead.
+/// - `failures`: The failed sends in a row to each neighbor, keyed by its ID.
+/// - `dead`: The neighbors declared dead, until they are restored or removed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Liveness {
+    policy: LivenessPolicy,
+    failures: HashMap<NodeId, u32>,
+    dead: BTreeSet<NodeId>,
+}
+
+impl Liveness {
+    /// Sets when a neighbor is declared dead, the neighbors already dead stay so.
+    ///
+    /// # Arguments
+    /// - `policy`: The new `LivenessPolicy`.
+    pub(crate) fn set_policy(&mut self, policy: LivenessPolicy) {
+        self.policy = policy;
+    }
+
+    /// Returns the `LivenessPolicy` deciding when a neighbor is dead.
+    ///
+    /// # Returns
+    /// A copy of the current policy.
+    pub(crate) fn policy(&self) -> LivenessPolicy {
+        self.policy
+    }
+
+    /// Counts a failed send to `node_id`.
+    ///
+    /// # Returns
+    /// The failed sends in a row, if they make the neighbor dead: it is then remembered as dead.
+    pub(crate) fn fail(&mut self, node_id: NodeId) -> Option<u32> {
+        if self.policy.max_failures == 0 {
+            return None;
+        }
+
+        let failures = self.failures.entry(node_id).or_default();
+        *failures += 1;
+        if *failures < self.policy.max_failures {
+            return None;
+        }
+
+        let failures = *failures;
+        self.failures.remove(&node_id);
+        self.dead.insert(node_id);
+        Some(failures)
+    }
+
+    /// Resets the failed sends to `node_id`, a packet reached it.
+    pub(crate) fn succeed(&mut self, node_id: NodeId) {
+        self.failures.remove(&node_id);
+    }
+
+    /// Forgets everything about `node_id`.
+    ///
+    /// # Returns
+    /// `true` if the neighbor was dead.
+    pub(crate) fn forget(&mut self, node_id: NodeId) -> bool {
+        self.failures.remove(&node_id);
+        self.dead.remove(&node_id)
+    }
+
+    /// Tells whether a neighbor was declared dead.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    ///
+    /// # Returns
+    /// `true` if the neighbor is dead and was neither restored nor removed since.
+    pub(crate) fn is_dead(&self, node_id: NodeId) -> bool {
+        self.dead.contains(&node_id)
+    }
+
+    /// Returns the neighbors declared dead.
+    ///
+    /// # Returns
+    /// Their IDs, in increasing order.
+    pub(crate) fn dead(&self) -> Vec<NodeId> {
+        self.dead.iter().copied().collect()
+    }
+}
@@ -0,0 +1,477 @@
+//! This file contains the Rustastic Drone's network launcher, developed by the Group Rustastic.
+//!
+//! File:   drone/network.rs
+//!
+//! Brief:  File for the `Network`, which builds a whole topology of `RustasticDrone`s from a network
+//!         initialization file and runs every drone on its own thread.
+//!
+//! Author: Rustastic
+
+use crossbeam_channel::{unbounded, Receiver, Select, Sender};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use wg_2024::{
+    config::Config,
+    controller::{DroneCommand, DroneEvent},
+    drone::Drone,
+    network::NodeId,
+    packet::Packet,
+};
+
+use crate::drone::RustasticDrone;
+
+/// An error raised while loading or launching a network, or while commanding one of its drones.
+#[derive(Debug)]
+pub enum NetworkError {
+    /// The network initialization file can't be read, or a drone's thread can't be spawned.
+    Io(io::Error),
+    /// The network initialization file is not valid TOML, or doesn't describe a network.
+    Parse(String),
+    /// The same ID is given to more than one node.
+    DuplicateId(NodeId),
+    /// A node is connected to a node that doesn't exist.
+    UnknownNeighbor {
+        node_id: NodeId,
+        neighbor_id: NodeId,
+    },
+    /// A node is connected to itself, or a client or server is connected to something other than a drone.
+    InvalidLink {
+        node_id: NodeId,
+        neighbor_id: NodeId,
+    },
+    /// The PDR of a drone is outside of `0.0..=1.0`.
+    InvalidPdr { drone_id: NodeId, pdr: f32 },
+    /// There is no drone with this ID in the network.
+    UnknownDrone(NodeId),
+    /// The drone has crashed and no longer receives commands.
+    Crashed(NodeId),
+}
+
+impl fmt::Display for NetworkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkError::Io(e) => write!(f, "I/O error: {e}"),
+            NetworkError::Parse(e) => write!(f, "Invalid network file: {e}"),
+            NetworkError::DuplicateId(node_id) => write!(f, "The ID {node_id} is used twice"),
+            NetworkError::UnknownNeighbor {
+                node_id,
+                neighbor_id,
+            } => write!(
+                f,
+                "[ Node {node_id} ] is connected to [ Node {neighbor_id} ], which doesn't exist"
+            ),
+            NetworkError::InvalidLink {
+                node_id,
+                neighbor_id,
+            } => write!(
+                f,
+                "[ Node {node_id} ] can't be connected to [ Node {neighbor_id} ]"
+            ),
+            NetworkError::InvalidPdr { drone_id, pdr } => {
+                write!(f, "[ Drone {drone_id} ] has an invalid PDR of {pdr}")
+            }
+            NetworkError::UnknownDrone(drone_id) => {
+                write!(f, "There is no [ Drone {drone_id} ] in the network")
+            }
+            NetworkError::Crashed(drone_id) => write!(f, "[ Drone {drone_id} ] has crashed"),
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+/// Parses a network initialization file, in the TOML format of `wg_2024::config::Config`.
+///
+/// # Arguments
+/// - `content`: The content of the file.
+///
+/// # Errors
+/// Returns `NetworkError::Parse` if the content doesn't describe a network.
+pub fn parse_config(content: &str) -> Result<Config, NetworkError> {
+    toml::from_str(content).map_err(|e| NetworkError::Parse(e.to_string()))
+}
+
+/// Reads and parses a network initialization file, see `parse_config`.
+///
+/// # Arguments
+/// - `path`: The path of the file.
+///
+/// # Errors
+/// Returns `NetworkError::Io` if the file can't be read, `NetworkError::Parse` if it doesn't describe a network.
+pub fn read_config(path: impl AsRef<Path>) -> Result<Config, NetworkError> {
+    parse_config(&fs::read_to_string(path).map_err(NetworkError::Io)?)
+}
+
+/// A drone of the network, seen from the simulation controller.
+///
+/// # Fields
+/// - `command_send`: The channel to send commands to the drone.
+/// - `event_recv`: The channel to receive the drone's events.
+/// - `thread`: The thread running the drone.
+#[derive(Debug)]
+struct DroneHandle {
+    command_send: Sender<DroneCommand>,
+    event_recv: Receiver<DroneEvent>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// A network of `RustasticDrone`s, each running on its own thread.
+///
+/// The network plays the simulation controller: it sends `DroneCommand`s to the drones and collects their
+/// `DroneEvent`s. Clients and servers are not run: the network only creates their channels, so that packets
+/// can be injected into the network with `packet_send` and observed when they reach them with `packet_recv`.
+///
+/// Links are undirected: a link declared by only one of its ends connects both of them.
+///
+/// Dropping the network crashes every drone that is still running and waits for its thread.
+///
+/// # Fields
+/// - `drones`: The drones of the network, keyed by their IDs.
+/// - `packet_send`: The channels to send packets to every node of the network, keyed by their IDs.
+/// - `host_recv`: The channels on which the clients and servers receive packets, keyed by their IDs.
+#[derive(Debug)]
+pub struct Network {
+    drones: HashMap<NodeId, DroneHandle>,
+    packet_send: HashMap<NodeId, Sender<Packet>>,
+    host_recv: HashMap<NodeId, Receiver<Packet>>,
+}
+
+impl Network {
+    /// Launches the network described by `config`, with drones built by `Drone::new`.
+    ///
+    /// # Arguments
+    /// - `config`: The description of the network.
+    ///
+    /// # Errors
+    /// Returns a `NetworkError` if the description is inconsistent, see `Network::launch_with`.
+    pub fn launch(config: &Config) -> Result<Self, NetworkError> {
+        Self::launch_with(config, |drone| drone)
+    }
+
+    /// Reads a network initialization file and launches the network it describes.
+    ///
+    /// # Arguments
+    /// - `path`: The path of the file.
+    ///
+    /// # Errors
+    /// Returns a `NetworkError` if the file can't be read or describes an inconsistent network.
+    pub fn launch_file(path: impl AsRef<Path>) -> Result<Self, NetworkError> {
+        Self::launch(&read_config(path)?)
+    }
+
+    /// Launches the network described by `config`, letting `configure` tune every drone before it runs.
+    ///
+    /// # Arguments
+    /// - `config`: The description of the network.
+    /// - `configure`: Called on every drone built by `Drone::new`, e.g. to set its policies with the
+    ///   `with_*` builders of `RustasticDrone`.
+    ///
+    /// # Errors
+    /// Returns a `NetworkError` if an ID is used twice, a node is connected to a node that doesn't exist,
+    /// a client or a server is connected to something other than a drone, or a PDR is out of range.
+    /// Nothing is launched in that case.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use rustastic_drone::{read_config, LoopPolicy, Network, NetworkError};
+    /// # fn main() -> Result<(), NetworkError> {
+    /// let config = read_config("network.toml")?;
+    /// let network = Network::launch_with(&config, |drone| drone.with_loop_policy(LoopPolicy::Warn))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn launch_with(
+        config: &Config,
+        configure: impl Fn(RustasticDrone) -> RustasticDrone,
+    ) -> Result<Self, NetworkError> {
+        let links = links(config)?;
+
+        let mut network = Self {
+            drones: HashMap::new(),
+            packet_send: HashMap::new(),
+            host_recv: HashMap::new(),
+        };
+        for &node_id in links.keys() {
+            let (send, recv) = unbounded();
+            network.packet_send.insert(node_id, send);
+            network.host_recv.insert(node_id, recv);
+        }
+
+        // If a thread can't be spawned, dropping `network` stops the drones already running
+        for drone in &config.drone {
+            let neighbors_send = links[&drone.id]
+                .iter()
+                .map(|neighbor_id| (*neighbor_id, network.packet_send[neighbor_id].clone()))
+                .collect();
+            let (command_send, command_recv) = unbounded();
+            let (event_send, event_recv) = unbounded();
+            let mut rustastic_drone = configure(RustasticDrone::new(
+                drone.id,
+                event_send,
+                command_recv,
+                network.host_recv.remove(&drone.id).unwrap(),
+                neighbors_send,
+                drone.pdr,
+            ));
+
+            let thread = thread::Builder::new()
+                .name(format!("drone-{}", drone.id))
+                .spawn(move || rustastic_drone.run())
+                .map_err(NetworkError::Io)?;
+
+            network.drones.insert(
+                drone.id,
+                DroneHandle {
+                    command_send,
+                    event_recv,
+                    thread: Some(thread),
+                },
+            );
+        }
+
+        Ok(network)
+    }
+
+    /// Returns the IDs of the drones of the network, in increasing order.
+    #[must_use]
+    pub fn drone_ids(&self) -> Vec<NodeId> {
+        let mut drone_ids: Vec<NodeId> = self.drones.keys().copied().collect();
+        drone_ids.sort_unstable();
+        drone_ids
+    }
+
+    /// Sends a command to a drone.
+    ///
+    /// # Arguments
+    /// - `drone_id`: The ID of the drone.
+    /// - `command`: The `DroneCommand` to send.
+    ///
+    /// # Errors
+    /// Returns `NetworkError::UnknownDrone` if there is no such drone, `NetworkError::Crashed` if it has crashed.
+    pub fn send_command(
+        &self,
+        drone_id: NodeId,
+        command: DroneCommand,
+    ) -> Result<(), NetworkError> {
+        self.drones
+            .get(&drone_id)
+            .ok_or(NetworkError::UnknownDrone(drone_id))?
+            .command_send
+            .send(command)
+            .map_err(|_| NetworkError::Crashed(drone_id))
+    }
+
+    /// Connects two nodes of the network with a new link, by sending an `AddSender` to each end that is a drone.
+    ///
+    /// # Arguments
+    /// - `node_id`, `neighbor_id`: The IDs of the two nodes.
+    ///
+    /// # Errors
+    /// Returns `NetworkError::UnknownNeighbor` if one of the nodes doesn't exist, `NetworkError::InvalidLink`
+    /// if neither of them is a drone, and `NetworkError::Crashed` if one of them has crashed.
+    pub fn connect(&self, node_id: NodeId, neighbor_id: NodeId) -> Result<(), NetworkError> {
+        for (from, to) in [(node_id, neighbor_id), (neighbor_id, node_id)] {
+            if !self.packet_send.contains_key(&to) {
+                return Err(NetworkError::UnknownNeighbor {
+                    node_id: from,
+                    neighbor_id: to,
+                });
+            }
+        }
+        if node_id == neighbor_id
+            || !(self.drones.contains_key(&node_id) || self.drones.contains_key(&neighbor_id))
+        {
+            return Err(NetworkError::InvalidLink {
+                node_id,
+                neighbor_id,
+            });
+        }
+
+        for (from, to) in [(node_id, neighbor_id), (neighbor_id, node_id)] {
+            if self.drones.contains_key(&from) {
+                self.send_command(
+                    from,
+                    DroneCommand::AddSender(to, self.packet_send[&to].clone()),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes the link between two nodes of the network, by sending a `RemoveSender` to each end that is a drone.
+    ///
+    /// # Arguments
+    /// - `node_id`, `neighbor_id`: The IDs of the two nodes.
+    ///
+    /// # Errors
+    /// Returns `NetworkError::Crashed` if one of the drones has crashed.
+    pub fn disconnect(&self, node_id: NodeId, neighbor_id: NodeId) -> Result<(), NetworkError> {
+        for (from, to) in [(node_id, neighbor_id), (neighbor_id, node_id)] {
+            if self.drones.contains_key(&from) {
+                self.send_command(from, DroneCommand::RemoveSender(to))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the channel to send packets to a node of the network, drone, client or server.
+    #[must_use]
+    pub fn packet_send(&self, node_id: NodeId) -> Option<&Sender<Packet>> {
+        self.packet_send.get(&node_id)
+    }
+
+    /// Returns the channel on which a client or a server receives the packets the drones send to it.
+    #[must_use]
+    pub fn packet_recv(&self, host_id: NodeId) -> Option<&Receiver<Packet>> {
+        self.host_recv.get(&host_id)
+    }
+
+    /// Returns the channel on which a drone reports its `DroneEvent`s.
+    #[must_use]
+    pub fn event_recv(&self, drone_id: NodeId) -> Option<&Receiver<DroneEvent>> {
+        self.drones.get(&drone_id).map(|drone| &drone.event_recv)
+    }
+
+    /// Waits for the next `DroneEvent` of any drone.
+    ///
+    /// # Arguments
+    /// - `timeout`: How long to wait for an event.
+    ///
+    /// # Returns
+    /// The ID of the drone and its event, or `None` if no drone reported anything in time.
+    #[must_use]
+    pub fn recv_event(&self, timeout: Duration) -> Option<(NodeId, DroneEvent)> {
+        let drones: Vec<(NodeId, &Receiver<DroneEvent>)> = self
+            .drones
+            .iter()
+            .map(|(drone_id, drone)| (*drone_id, &drone.event_recv))
+            .collect();
+        let mut select = Select::new();
+        for (_, event_recv) in &drones {
+            select.recv(event_recv);
+        }
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let operation = select.select_deadline(deadline).ok()?;
+            let index = operation.index();
+            let (drone_id, event_recv) = drones[index];
+            match operation.recv(event_recv) {
+                Ok(event) => return Some((drone_id, event)),
+                // A crashed drone has nothing more to report
+                Err(_) => select.remove(index),
+            }
+        }
+    }
+
+    /// Collects the `DroneEvent`s reported so far by every drone, without waiting.
+    ///
+    /// # Returns
+    /// The ID of each drone with its events, drone by drone in increasing order of ID.
+    #[must_use]
+    pub fn collect_events(&self) -> Vec<(NodeId, DroneEvent)> {
+        self.drone_ids()
+            .into_iter()
+            .flat_map(|drone_id| {
+                self.drones[&drone_id]
+                    .event_recv
+                    .try_iter()
+                    .map(move |event| (drone_id, event))
+            })
+            .collect()
+    }
+
+    /// Crashes every drone that is still running and waits for all of them to stop.
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        for drone in self.drones.values() {
+            // A drone that has already crashed can't receive the command
+            let _ = drone.command_send.send(DroneCommand::Crash);
+        }
+        for drone in self.drones.values_mut() {
+            if let Some(thread) = drone.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl Drop for Network {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Validates the description of a network and returns its links.
+///
+/// # Returns
+/// The neighbors of every node, keyed by its ID. Each link is listed at both of its ends.
+///
+/// # Errors
+/// See `Network::launch_with`.
+pub(crate) fn links(config: &Config) -> Result<HashMap<NodeId, BTreeSet<NodeId>>, NetworkError> {
+    let mut links: HashMap<NodeId, BTreeSet<NodeId>> = HashMap::new();
+    let mut is_drone = HashMap::new();
+
+    let drones = config
+        .drone
+        .iter()
+        .map(|drone| (drone.id, &drone.connected_node_ids, true));
+    let hosts = config
+        .client
+        .iter()
+        .map(|client| (client.id, &client.connected_drone_ids, false))
+        .chain(
+            config
+                .server
+                .iter()
+                .map(|server| (server.id, &server.connected_drone_ids, false)),
+        );
+    let nodes: Vec<_> = drones.chain(hosts).collect();
+
+    for &(node_id, _, drone) in &nodes {
+        if is_drone.insert(node_id, drone).is_some() {
+            return Err(NetworkError::DuplicateId(node_id));
+        }
+        links.insert(node_id, BTreeSet::new());
+    }
+
+    for drone in &config.drone {
+        if !(0.0..=1.0).contains(&drone.pdr) {
+            return Err(NetworkError::InvalidPdr {
+                drone_id: drone.id,
+                pdr: drone.pdr,
+            });
+        }
+    }
+
+    for &(node_id, neighbor_ids, drone) in &nodes {
+        for &neighbor_id in neighbor_ids {
+            let Some(&neighbor_drone) = is_drone.get(&neighbor_id) else {
+                return Err(NetworkError::UnknownNeighbor {
+                    node_id,
+                    neighbor_id,
+                });
+            };
+            if node_id == neighbor_id || !(drone || neighbor_drone) {
+                return Err(NetworkError::InvalidLink {
+                    node_id,
+                    neighbor_id,
+                });
+            }
+            links.get_mut(&node_id).unwrap().insert(neighbor_id);
+            links.get_mut(&neighbor_id).unwrap().insert(node_id);
+        }
+    }
+
+    Ok(links)
+}
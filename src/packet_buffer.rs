@@ -21,8 +21,27 @@ pub struct PacketBuffer {
     buffer: HashMap<(u64, u64), Packet>,
     /// Maintains the insertion order of keys for efficient eviction of old packets.
     order: VecDeque<(u64, u64)>,
+    /// Counts how many times each buffered packet was retransmitted.
+    retries: HashMap<(u64, u64), u8>,
     /// Maximum capacity of the buffer.
     max_size: usize,
+    /// Counters of how the packets left the buffer.
+    stats: BufferStats,
+}
+
+/// Statistics of a `PacketBuffer`, counting how its packets were released.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferStats {
+    /// Packets released because their Ack travelled back through the drone.
+    pub acked: u64,
+    /// Packets retrieved from the buffer, after a NACK.
+    pub retrieved: u64,
+    /// Packets evicted to make space for newer ones.
+    pub evicted: u64,
+    /// Copies of buffered packets handed out for a retransmission.
+    pub retransmitted: u64,
+    /// Packets given up on, after they ran out of retransmissions or the drone dropped them.
+    pub abandoned: u64,
 }
 
 impl PacketBuffer {
@@ -39,13 +58,17 @@ impl PacketBuffer {
         Self {
             buffer: HashMap::new(),
             order: VecDeque::new(),
+            retries: HashMap::new(),
             max_size,
+            stats: BufferStats::default(),
         }
     }
 
     /// Adds a packet to the buffer.
     ///
     /// If the buffer is full, the oldest packet is removed to make space for the new one.
+    /// If a packet with the same key is already buffered, it is replaced, its retransmissions are reset and its
+    /// key becomes the newest one.
     ///
     /// # Parameters
     ///
@@ -55,10 +78,24 @@ impl PacketBuffer {
     pub fn add_fragment(&mut self, session_id: u64, fragment_index: u64, packet: Packet) {
         let key = (session_id, fragment_index);
 
+        // If the key is already buffered, replace the packet and refresh its position.
+        if let Some(buffered) = self.buffer.get_mut(&key) {
+            *buffered = packet;
+            self.retries.remove(&key);
+            if let Some(pos) = self.order.iter().position(|&k| k == key) {
+                self.order.remove(pos);
+            }
+            self.order.push_back(key);
+            return;
+        }
+
         // If the buffer is full, remove the oldest packet.
         if self.buffer.len() >= self.max_size {
             if let Some(oldest) = self.order.pop_front() {
-                self.buffer.remove(&oldest);
+                if self.buffer.remove(&oldest).is_some() {
+                    self.stats.evicted += 1;
+                }
+                self.retries.remove(&oldest);
             }
         }
 
@@ -79,9 +116,60 @@ impl PacketBuffer {
     /// - `Some(Packet)`: The packet if found in the buffer.
     /// - `None`: If the packet is not found.
     pub fn get_fragment(&mut self, session_id: u64, fragment_index: u64) -> Option<Packet> {
+        let packet = self.remove(session_id, fragment_index)?;
+        self.stats.retrieved += 1;
+        Some(packet)
+    }
+
+    /// Releases a packet whose Ack travelled back through the drone.
+    ///
+    /// The destination received the fragment, so it will never be NACKed: its space in the buffer is
+    /// better used by fragments that are still travelling.
+    ///
+    /// # Parameters
+    ///
+    /// - `session_id`: The session ID associated with the packet.
+    /// - `fragment_index`: The index of the acknowledged fragment.
+    ///
+    /// # Returns
+    ///
+    /// - `true`: if the packet was buffered and has been released.
+    /// - `false`: if the packet is not found.
+    pub fn ack_fragment(&mut self, session_id: u64, fragment_index: u64) -> bool {
+        if self.remove(session_id, fragment_index).is_some() {
+            self.stats.acked += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Discards a packet that the drone dropped itself: it can't be retransmitted from the buffer anymore.
+    ///
+    /// # Parameters
+    ///
+    /// - `session_id`: The session ID associated with the packet.
+    /// - `fragment_index`: The index of the dropped fragment.
+    ///
+    /// # Returns
+    ///
+    /// - `true`: if the packet was buffered and has been released.
+    /// - `false`: if the packet is not found.
+    pub fn discard_fragment(&mut self, session_id: u64, fragment_index: u64) -> bool {
+        if self.remove(session_id, fragment_index).is_some() {
+            self.stats.abandoned += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Removes a packet from the buffer, without updating the statistics.
+    fn remove(&mut self, session_id: u64, fragment_index: u64) -> Option<Packet> {
         let key = (session_id, fragment_index);
         // Remove the packet from the HashMap.
         if let Some(packet) = self.buffer.remove(&key) {
+            self.retries.remove(&key);
             // Remove the key from the VecDeque.
             if let Some(pos) = self.order.iter().position(|&k| k == key) {
                 self.order.remove(pos);
@@ -92,6 +180,57 @@ impl PacketBuffer {
         None
     }
 
+    /// Retrieves a copy of a packet to retransmit it, keeping it in the buffer.
+    ///
+    /// Each packet can be retransmitted at most `max_retries` times: once the limit is reached, the packet
+    /// is removed from the buffer.
+    ///
+    /// # Parameters
+    ///
+    /// - `session_id`: The session ID associated with the packet.
+    /// - `fragment_index`: The index of the fragment inside the packet.
+    /// - `max_retries`: The maximum number of retransmissions of the packet.
+    ///
+    /// # Returns
+    ///
+    /// - `Some(Packet)`: A copy of the packet, if it is buffered and has retries left.
+    /// - `None`: If the packet is not found, or it has no retries left.
+    pub fn retry_fragment(
+        &mut self,
+        session_id: u64,
+        fragment_index: u64,
+        max_retries: u8,
+    ) -> Option<Packet> {
+        let key = (session_id, fragment_index);
+        let packet = self.buffer.get(&key)?.clone();
+
+        let retries = self.retries.entry(key).or_insert(0);
+        if *retries >= max_retries {
+            self.remove(session_id, fragment_index);
+            self.stats.abandoned += 1;
+            return None;
+        }
+        *retries += 1;
+        self.stats.retransmitted += 1;
+
+        Some(packet)
+    }
+
+    /// Returns the number of packets currently stored in the buffer.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns `true` if the buffer doesn't contain any packet.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Returns the statistics of the buffer.
+    pub fn stats(&self) -> BufferStats {
+        self.stats
+    }
+
     /// Updates the maximum size of the buffer.
     ///
     /// # Parameters
@@ -117,6 +256,15 @@ impl fmt::Display for PacketBuffer {
             self.max_size,
             self.buffer.len()
         )?;
+        writeln!(
+            f,
+            "  Released: {} acked, {} retrieved, {} evicted, {} abandoned; Retransmitted: {}",
+            self.stats.acked,
+            self.stats.retrieved,
+            self.stats.evicted,
+            self.stats.abandoned,
+            self.stats.retransmitted
+        )?;
 
         for (key, packet) in &self.buffer {
             let (session_id, fragment_index) = key;
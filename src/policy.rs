@@ -0,0 +1,136 @@
+//! This file contains the Rustastic Drone's configurable policies, developed by the Group Rustastic.
+//!
+//! File:   drone/policy.rs
+//!
+//! Brief:  File for the policies that tune how the Rustastic Drone reacts to unusual traffic.
+//!
+//! Author: Rustastic
+
+/// How a `RustasticDrone` reacts to a routing header that visits the same node more than once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoopPolicy {
+    /// The packet is refused: fragments are NACKed, the other packets are shortcut to the controller.
+    #[default]
+    Strict,
+    /// The loop is reported, but the packet is forwarded anyway.
+    Warn,
+    /// Routing headers are not inspected for loops.
+    Off,
+}
+
+/// How a `RustasticDrone` recovers a fragment dropped downstream, when its `Dropped` NACK travels back through it.
+///
+/// Only `Dropped` NACKs are recovered locally: the other NACKs report a broken route, and resending the
+/// fragment on the same route would fail again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NackRecoveryPolicy {
+    /// How many times the drone resends the same fragment from its buffer, `0` disables local recovery.
+    pub max_local_retries: u8,
+    /// Whether a NACK recovered locally is absorbed, instead of being forwarded to the source anyway.
+    pub absorb_recovered: bool,
+}
+
+impl Default for NackRecoveryPolicy {
+    fn default() -> Self {
+        Self {
+            max_local_retries: 3,
+            absorb_recovered: true,
+        }
+    }
+}
+
+/// How a `RustasticDrone` shares its time between the classes of packets waiting to be handled.
+///
+/// While both classes have packets waiting, the drone handles `control` control packets for every `data`
+/// fragments: control traffic goes first, but fragments are never starved. A class with a weight of `0` is
+/// only handled when the other one has nothing waiting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriorityWeights {
+    /// The share of `Ack`s, `Nack`s, `FloodRequest`s and `FloodResponse`s.
+    pub control: u32,
+    /// The share of `MsgFragment`s.
+    pub data: u32,
+}
+
+impl Default for PriorityWeights {
+    fn default() -> Self {
+        Self {
+            control: 4,
+            data: 1,
+        }
+    }
+}
+
+/// How a `RustasticDrone` shares its forwarding capacity between the flows of fragments waiting to be forwarded.
+///
+/// With fair queuing, fragments are grouped in flows and served by deficit round robin: in each round, every
+/// flow can forward about as many bytes as a full fragment, so a heavy flow can't monopolize the drone.
+///
+/// Deficit round robin only orders the packets received and waiting to be handled. Once routed, the fragments
+/// waiting for a link (its delay, its shaping or a congested neighbor) leave in arrival order: a heavy flow can
+/// still fill the queue of a congested link.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FairQueuing {
+    /// Fragments are forwarded in arrival order.
+    #[default]
+    Off,
+    /// Each `session_id` is a flow.
+    PerSession,
+    /// Each source node, the first hop of the routing header, is a flow.
+    PerSource,
+}
+
+/// What a `RustasticDrone` does with a packet for a congested neighbor whose outbound queue is full.
+///
+/// A neighbor is congested when its channel refuses a packet: the drone doesn't wait for it, the packets for
+/// the neighbor are queued and retried by the main loop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// The new packet is dropped.
+    #[default]
+    DropTail,
+    /// The oldest packet of the queue is dropped to make room for the new one.
+    DropHead,
+    /// The new packet is dropped and, if it is a fragment, NACKed as `Dropped` to its source.
+    NackUpstream,
+}
+
+/// How a `RustasticDrone` shares its time between the commands of the simulation controller and the packets
+/// it receives, while both are waiting.
+///
+/// A turn handles one command, or a batch of packets (see `RustasticDrone::with_scheduling`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SchedulingPolicy {
+    /// Commands always go first: packets are only handled when no command is waiting.
+    #[default]
+    Strict,
+    /// Commands and packets take turns.
+    RoundRobin,
+    /// `commands` turns of commands for every `packets` turns of packets. A source with a weight of `0` is
+    /// only served when the other one has nothing waiting.
+    Weighted { commands: u32, packets: u32 },
+}
+
+/// When a `RustasticDrone` gives up on a neighbor that can't be reached anymore.
+///
+/// A send fails when the neighbor's channel is closed (e.g. its receiver was dropped) or its link is gone; a
+/// congested neighbor, whose channel is only full, is not failing, and neither is a neighbor a socket couldn't
+/// send to, which may only be restarting. After `max_failures` failed sends in a row,
+/// the neighbor is declared dead: it is removed from the drone's neighbors, so the packets for it are NACKed
+/// or shortcut to the simulation controller right away instead of being sent to a dead link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LivenessPolicy {
+    /// How many failed sends in a row make a neighbor dead, `0` disables liveness detection.
+    pub max_failures: u32,
+    /// Whether a dead neighbor is restored when the simulation controller adds it again with an `AddSender`.
+    pub restore_on_add_sender: bool,
+}
+
+impl Default for LivenessPolicy {
+    fn default() -> Self {
+        Self {
+            max_failures: 3,
+            restore_on_add_sender: true,
+        }
+    }
+}
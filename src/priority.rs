@@ -0,0 +1,135 @@
+//! This file contains the Rustastic Drone's input scheduling, developed by the Group Rustastic.
+//!
+//! File:   drone/priority.rs
+//!
+//! Brief:  File for the `TrafficClass` of packets, and for the queue that hands the packets waiting to be
+//!         handled to the drone in priority order.
+//!
+//! Author: Rustastic
+
+use std::collections::VecDeque;
+use wg_2024::packet::{Packet, PacketType};
+
+use crate::fair_queue::{FairQueue, FlowStats};
+use crate::policy::{FairQueuing, PriorityWeights};
+
+/// The class of a packet, which decides how soon the drone handles it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrafficClass {
+    /// `Ack`s, `Nack`s, `FloodRequest`s and `FloodResponse`s: small packets that others are waiting for.
+    Control,
+    /// `MsgFragment`s.
+    Data,
+}
+
+impl TrafficClass {
+    /// Returns the class of a packet.
+    #[must_use]
+    pub fn of(packet: &Packet) -> Self {
+        match packet.pack_type {
+            PacketType::MsgFragment(_) => TrafficClass::Data,
+            PacketType::Ack(_)
+            | PacketType::Nack(_)
+            | PacketType::FloodRequest(_)
+            | PacketType::FloodResponse(_) => TrafficClass::Control,
+        }
+    }
+}
+
+/// The packets waiting to be handled by a drone, one FIFO queue per `TrafficClass`.
+///
+/// Packets are handed out by weighted round robin: each class has as many credits as its weight, every
+/// packet takes one, and the credits are given back once no class with packets waiting has any left.
+///
+/// # Fields
+/// - `weights`: The share of each class.
+/// - `control`, `data`: The packets waiting in each class, the fragments being shared between their flows.
+/// - `control_credits`, `data_credits`: The packets each class can still hand out in the current round.
+/// - `len`: The number of packets waiting in both classes.
+#[derive(Debug, Clone)]
+pub(crate) struct PriorityQueue {
+    weights: PriorityWeights,
+    control: VecDeque<Packet>,
+    data: FairQueue,
+    control_credits: u32,
+    data_credits: u32,
+    len: usize,
+}
+
+impl PriorityQueue {
+    pub(crate) fn new(weights: PriorityWeights) -> Self {
+        Self {
+            weights,
+            control: VecDeque::new(),
+            data: FairQueue::default(),
+            control_credits: weights.control,
+            data_credits: weights.data,
+            len: 0,
+        }
+    }
+
+    pub(crate) fn set_weights(&mut self, weights: PriorityWeights) {
+        *self = Self {
+            control: std::mem::take(&mut self.control),
+            data: std::mem::take(&mut self.data),
+            len: self.len,
+            ..Self::new(weights)
+        };
+    }
+
+    pub(crate) fn push(&mut self, packet: Packet) {
+        self.len += 1;
+        match TrafficClass::of(&packet) {
+            TrafficClass::Control => self.control.push_back(packet),
+            TrafficClass::Data => self.data.push(packet),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.control.is_empty() && self.data.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn set_fair_queuing(&mut self, fair_queuing: FairQueuing) {
+        self.data.set_fair_queuing(fair_queuing);
+    }
+
+    pub(crate) fn flow_stats(&self) -> Vec<FlowStats> {
+        self.data.stats()
+    }
+
+    /// Removes the next packet to handle.
+    pub(crate) fn pop(&mut self) -> Option<Packet> {
+        let packet = self.take_next();
+        if packet.is_some() {
+            self.len -= 1;
+        }
+        packet
+    }
+
+    fn take_next(&mut self) -> Option<Packet> {
+        if self.control.is_empty() || self.data.is_empty() {
+            // A class alone doesn't compete: it is served without spending credits
+            return self.control.pop_front().or_else(|| self.data.pop());
+        }
+
+        if self.control_credits == 0 && self.data_credits == 0 {
+            self.control_credits = self.weights.control;
+            self.data_credits = self.weights.data;
+        }
+
+        if self.control_credits > 0 {
+            self.control_credits -= 1;
+            self.control.pop_front()
+        } else if self.data_credits > 0 {
+            self.data_credits -= 1;
+            self.data.pop()
+        } else {
+            // Both weights are 0: control traffic goes first
+            self.control.pop_front()
+        }
+    }
+}
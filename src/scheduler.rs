@@ -0,0 +1,101 @@
+//! This file contains the Rustastic Drone's scheduler, developed by the Group Rustastic.
+//!
+//! File:   drone/scheduler.rs
+//!
+//! Brief:  File for the scheduler that decides whether the drone's main loop handles a command of the
+//!         simulation controller or a batch of packets next.
+//!
+//! Author: Rustastic
+
+use crate::policy::SchedulingPolicy;
+
+/// What the main loop of the drone handles next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Turn {
+    /// One command of the simulation controller.
+    Command,
+    /// Up to `Scheduler::batch` packets.
+    Packets,
+}
+
+/// Shares the drone's time between commands and packets with weighted round robin.
+///
+/// # Fields
+/// - `policy`: The `SchedulingPolicy` of the drone.
+/// - `batch`: How many packets a turn of packets handles at most.
+/// - `command_credits`, `packet_credits`: The turns left to each source in the current round.
+#[derive(Debug, Clone)]
+pub(crate) struct Scheduler {
+    policy: SchedulingPolicy,
+    batch: usize,
+    command_credits: u32,
+    packet_credits: u32,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(SchedulingPolicy::default(), 1)
+    }
+}
+
+impl Scheduler {
+    pub(crate) fn new(policy: SchedulingPolicy, batch: usize) -> Self {
+        Self {
+            policy,
+            batch: batch.max(1),
+            command_credits: 0,
+            packet_credits: 0,
+        }
+    }
+
+    pub(crate) fn batch(&self) -> usize {
+        self.batch
+    }
+
+    /// Returns the turns of commands and packets in a round, `None` if commands always go first.
+    fn weights(&self) -> Option<(u32, u32)> {
+        match self.policy {
+            SchedulingPolicy::Strict => None,
+            SchedulingPolicy::RoundRobin => Some((1, 1)),
+            SchedulingPolicy::Weighted { commands, packets } => Some((commands, packets)),
+        }
+    }
+
+    /// Picks the next turn.
+    ///
+    /// # Arguments
+    /// - `commands_waiting`: Whether a command of the simulation controller is waiting.
+    /// - `packets_waiting`: Whether a packet is waiting.
+    ///
+    /// # Returns
+    /// The next `Turn`, `None` if nothing is waiting.
+    pub(crate) fn next(&mut self, commands_waiting: bool, packets_waiting: bool) -> Option<Turn> {
+        match (commands_waiting, packets_waiting) {
+            (false, false) => return None,
+            // A source alone doesn't compete: it is served without spending credits
+            (true, false) => return Some(Turn::Command),
+            (false, true) => return Some(Turn::Packets),
+            (true, true) => {}
+        }
+
+        let Some((commands, packets)) = self.weights() else {
+            return Some(Turn::Command);
+        };
+
+        if self.command_credits == 0 && self.packet_credits == 0 {
+            self.command_credits = commands;
+            self.packet_credits = packets;
+        }
+
+        if self.command_credits > 0 {
+            self.command_credits -= 1;
+            Some(Turn::Command)
+        } else if self.packet_credits > 0 {
+            self.packet_credits -= 1;
+            Some(Turn::Packets)
+        } else {
+            // Both weights are 0: commands go first
+            Some(Turn::Command)
+        }
+    }
+}
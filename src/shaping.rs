@@ -0,0 +1,250 @@
+//! This file contains the Rustastic Drone's bandwidth shaping, developed by the Group Rustastic.
+//!
+//! File:   drone/shaping.rs
+//!
+//! Brief:  File for the `LinkShaping` of a drone's links, which limits with token buckets how many packets
+//!         or bytes per second the drone sends to each neighbor.
+//!
+//! Author: Rustastic
+
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use wg_2024::{network::NodeId, packet::Packet};
+
+use crate::codec;
+
+/// The capacity of a link.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimit {
+    /// At most this many packets per second, whatever their size.
+    PacketsPerSecond(f64),
+    /// At most this many bytes per second, a packet weighing the length of its `codec` frame.
+    BytesPerSecond(f64),
+}
+
+/// What a drone does with a packet for a saturated link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaturationPolicy {
+    /// The packet waits for the link, unless `max_len` packets are already waiting: then it is dropped.
+    Queue { max_len: usize },
+    /// The packet is dropped.
+    Drop,
+}
+
+/// How the packets sent on a link are shaped, with a token bucket.
+///
+/// The bucket holds up to `burst` tokens and is refilled at the rate of `limit`. Sending a packet takes one
+/// token, or one token per byte: a link that has been idle can send `burst` at once, then only at its rate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkShaping {
+    /// The capacity of the link.
+    pub limit: RateLimit,
+    /// The size of the bucket, in packets or bytes like `limit`.
+    pub burst: f64,
+    /// What happens to the packets that find the link saturated.
+    pub policy: SaturationPolicy,
+}
+
+impl LinkShaping {
+    /// Creates a `LinkShaping` of `rate` packets per second, with a burst of one second and a queue of 64 packets.
+    #[must_use]
+    pub fn packets_per_second(rate: f64) -> Self {
+        Self {
+            limit: RateLimit::PacketsPerSecond(rate),
+            burst: rate.max(1.0),
+            policy: SaturationPolicy::Queue { max_len: 64 },
+        }
+    }
+
+    /// Creates a `LinkShaping` of `rate` bytes per second, with a burst of one second and a queue of 64 packets.
+    #[must_use]
+    pub fn bytes_per_second(rate: f64) -> Self {
+        Self {
+            limit: RateLimit::BytesPerSecond(rate),
+            burst: rate,
+            policy: SaturationPolicy::Queue { max_len: 64 },
+        }
+    }
+
+    /// Sets the size of the bucket.
+    ///
+    /// # Returns
+    /// The same `LinkShaping`, for chaining after its constructor.
+    #[must_use]
+    pub fn with_burst(mut self, burst: f64) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Sets what happens to the packets that find the link saturated.
+    ///
+    /// # Returns
+    /// The same `LinkShaping`, for chaining after its constructor.
+    #[must_use]
+    pub fn with_policy(mut self, policy: SaturationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    fn rate(&self) -> f64 {
+        match self.limit {
+            RateLimit::PacketsPerSecond(rate) | RateLimit::BytesPerSecond(rate) => rate,
+        }
+    }
+
+    /// Returns the number of tokens a packet takes, never more than the bucket can hold.
+    fn cost(&self, packet: &Packet) -> f64 {
+        let cost = match self.limit {
+            RateLimit::PacketsPerSecond(_) => 1.0,
+            RateLimit::BytesPerSecond(_) => {
+                codec::encode(packet).map_or(codec::HEADER_LEN, |frame| frame.len()) as f64
+            }
+        };
+        cost.min(self.burst)
+    }
+}
+
+/// A shaped link: its token bucket and the packets waiting for it.
+#[derive(Debug, Clone)]
+struct ShapedLink {
+    shaping: LinkShaping,
+    tokens: f64,
+    refilled_at: Instant,
+    queue: VecDeque<Packet>,
+}
+
+impl ShapedLink {
+    fn new(shaping: LinkShaping, now: Instant) -> Self {
+        Self {
+            shaping,
+            tokens: shaping.burst,
+            refilled_at: now,
+            queue: VecDeque::new(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * self.shaping.rate()).min(self.shaping.burst);
+        self.refilled_at = now;
+    }
+
+    /// Takes `cost` tokens if the bucket holds enough of them.
+    fn try_take(&mut self, cost: f64) -> bool {
+        if self.tokens >= cost {
+            self.tokens -= cost;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns when the first packet of the queue can be sent, if there is one.
+    fn next_ready(&self) -> Option<Instant> {
+        let packet = self.queue.front()?;
+        let missing = (self.shaping.cost(packet) - self.tokens).max(0.0);
+        let rate = self.shaping.rate();
+        if rate <= 0.0 {
+            return None;
+        }
+        Some(self.refilled_at + Duration::from_secs_f64(missing / rate))
+    }
+}
+
+/// The outcome of handing a packet to the `Shaper`.
+#[derive(Debug)]
+pub(crate) enum Shaped {
+    /// The link has room, the packet can be sent now.
+    Send(Packet),
+    /// The packet waits in the queue of the link.
+    Queued,
+    /// The link is saturated and the packet was refused.
+    Dropped(Packet),
+}
+
+/// The token buckets of a drone's links.
+///
+/// # Fields
+/// - `link_shaping`: The `LinkShaping` of some links, keyed by the ID of the neighbor.
+/// - `default_shaping`: The `LinkShaping` of the other links, if they are shaped.
+/// - `links`: The state of the shaped links the drone has sent packets on.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Shaper {
+    link_shaping: HashMap<NodeId, LinkShaping>,
+    default_shaping: Option<LinkShaping>,
+    links: HashMap<NodeId, ShapedLink>,
+}
+
+impl Shaper {
+    pub(crate) fn set_link_shaping(&mut self, node_id: NodeId, shaping: LinkShaping) {
+        self.link_shaping.insert(node_id, shaping);
+        self.links.remove(&node_id);
+    }
+
+    pub(crate) fn set_default_shaping(&mut self, shaping: LinkShaping) {
+        self.default_shaping = Some(shaping);
+        self.links.clear();
+    }
+
+    /// Hands a packet for `to` to its link.
+    pub(crate) fn shape(&mut self, to: NodeId, packet: Packet, now: Instant) -> Shaped {
+        let Some(shaping) = self.link_shaping.get(&to).copied().or(self.default_shaping) else {
+            return Shaped::Send(packet);
+        };
+
+        let link = self
+            .links
+            .entry(to)
+            .or_insert_with(|| ShapedLink::new(shaping, now));
+        link.refill(now);
+
+        // Packets already waiting go first
+        if link.queue.is_empty() && link.try_take(shaping.cost(&packet)) {
+            return Shaped::Send(packet);
+        }
+        match shaping.policy {
+            SaturationPolicy::Queue { max_len } if link.queue.len() < max_len => {
+                link.queue.push_back(packet);
+                Shaped::Queued
+            }
+            _ => Shaped::Dropped(packet),
+        }
+    }
+
+    /// Removes the queued packets that their links can send now.
+    ///
+    /// # Returns
+    /// The ID of the neighbor and the packet to send, link by link in increasing order of ID.
+    pub(crate) fn pop_ready(&mut self, now: Instant) -> Vec<(NodeId, Packet)> {
+        let mut ready = Vec::new();
+        for (node_id, link) in &mut self.links {
+            link.refill(now);
+            while let Some(packet) = link.queue.front() {
+                let cost = link.shaping.cost(packet);
+                if !link.try_take(cost) {
+                    break;
+                }
+                ready.push((*node_id, link.queue.pop_front().unwrap()));
+            }
+        }
+        ready.sort_by_key(|(node_id, _)| *node_id);
+        ready
+    }
+
+    /// Returns when the next queued packet can be sent, if any.
+    pub(crate) fn next_ready(&self) -> Option<Instant> {
+        self.links.values().filter_map(ShapedLink::next_ready).min()
+    }
+
+    /// Removes every queued packet, whether its link can send it or not.
+    pub(crate) fn drain(&mut self) -> Vec<(NodeId, Packet)> {
+        let mut packets: Vec<(NodeId, Packet)> = self
+            .links
+            .iter_mut()
+            .flat_map(|(node_id, link)| link.queue.drain(..).map(|packet| (*node_id, packet)))
+            .collect();
+        packets.sort_by_key(|(node_id, _)| *node_id);
+        packets
+    }
+}
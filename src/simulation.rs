@@ -0,0 +1,443 @@
+//! This file contains the Rustastic Drone's discrete-event simulator, developed by the Group Rustastic.
+//!
+//! File:   drone/simulation.rs
+//!
+//! Brief:  File for the `Simulation`, which runs a whole network of `DroneCore`s on a single thread,
+//!         under a virtual clock and a deterministic random number generator.
+//!
+//! Author: Rustastic
+
+use colored::Colorize;
+use log::{error, warn};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap};
+use std::time::Duration;
+
+use wg_2024::{
+    config::Config,
+    controller::{DroneCommand, DroneEvent},
+    network::NodeId,
+    packet::{Packet, PacketType},
+};
+
+use crate::delay::LinkDelay;
+use crate::drone_core::{DroneAction, DroneCore, DroneInput};
+use crate::event::RustasticEvent;
+use crate::network::{links, NetworkError};
+
+/// Latency of the links whose delay is not set, see `Simulation::with_default_delay`.
+pub const DEFAULT_LINK_LATENCY: Duration = Duration::from_millis(1);
+
+/// Something that happened during a `Simulation`, at a given virtual time.
+#[derive(Debug, Clone)]
+pub enum SimulationEvent {
+    /// A drone reported a `DroneEvent` to the simulation controller.
+    Controller {
+        time: Duration,
+        drone_id: NodeId,
+        event: DroneEvent,
+    },
+    /// A drone reported a `RustasticEvent`.
+    Rustastic {
+        time: Duration,
+        event: RustasticEvent,
+    },
+    /// A packet reached a client or a server.
+    Delivered {
+        time: Duration,
+        host_id: NodeId,
+        packet: Packet,
+    },
+}
+
+impl SimulationEvent {
+    /// Returns the virtual time at which the event happened.
+    #[must_use]
+    pub fn time(&self) -> Duration {
+        match self {
+            SimulationEvent::Controller { time, .. }
+            | SimulationEvent::Rustastic { time, .. }
+            | SimulationEvent::Delivered { time, .. } => *time,
+        }
+    }
+}
+
+/// An input waiting in the queue of the `Simulation` for its virtual time to come.
+///
+/// Inputs are ordered by time. At the same time, commands come before packets, as in the `select_biased!`
+/// loop of `RustasticDrone::serve`, and then inputs come in the order they were scheduled.
+#[derive(Debug)]
+struct Scheduled {
+    time: Duration,
+    seq: u64,
+    node_id: NodeId,
+    input: DroneInput,
+}
+
+impl Scheduled {
+    fn key(&self) -> (Duration, bool, u64) {
+        (
+            self.time,
+            matches!(self.input, DroneInput::Packet(_)),
+            self.seq,
+        )
+    }
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    // Reversed, so that the `BinaryHeap` pops the earliest input first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key().cmp(&self.key())
+    }
+}
+
+/// A discrete-event simulation of a network of drones.
+///
+/// Every drone is a `DroneCore` and every input it receives is an event in a single queue, ordered by a
+/// virtual clock: no thread is spawned and no time is spent waiting, so networks of hundreds of drones are
+/// simulated in a fraction of a second. The random number generator of every drone is seeded from the seed
+/// of the simulation, so that the same seed always gives exactly the same run.
+///
+/// A packet sent on a link reaches the other end after a delay drawn from the `LinkDelay` of the link, with
+/// the random number generator of the simulation. Clients and servers are not run: the packets that reach
+/// them are reported as `SimulationEvent::Delivered`.
+///
+/// Commands are sent as in the real simulation, with one difference: the channel of a `DroneCommand::AddSender`
+/// is ignored, the packets always travel through the simulation.
+///
+/// # Fields
+/// - `now`: The virtual time of the simulation.
+/// - `drones`: The drones that haven't crashed, keyed by their IDs.
+/// - `hosts`: The IDs of the clients and servers.
+/// - `link_delays`: The `LinkDelay` of the links whose delay was set, keyed by the IDs of their ends in
+///   increasing order.
+/// - `default_delay`: The `LinkDelay` of the other links.
+/// - `rng`: The random number generator drawing the delays.
+/// - `queue`: The inputs waiting for their time to come.
+/// - `next_seq`: The sequence number of the next scheduled input.
+/// - `events`: The events that happened so far.
+#[derive(Debug)]
+pub struct Simulation {
+    now: Duration,
+    drones: BTreeMap<NodeId, DroneCore>,
+    hosts: BTreeSet<NodeId>,
+    link_delays: HashMap<(NodeId, NodeId), LinkDelay>,
+    default_delay: LinkDelay,
+    rng: StdRng,
+    queue: BinaryHeap<Scheduled>,
+    next_seq: u64,
+    events: Vec<SimulationEvent>,
+}
+
+impl Simulation {
+    /// Creates the simulation of the network described by `config`.
+    ///
+    /// # Arguments
+    /// - `config`: The description of the network.
+    /// - `seed`: The seed from which the random number generator of every drone is seeded.
+    ///
+    /// # Errors
+    /// Returns a `NetworkError` if the description is inconsistent, see `Network::launch_with`.
+    pub fn new(config: &Config, seed: u64) -> Result<Self, NetworkError> {
+        Self::new_with(config, seed, |core| core)
+    }
+
+    /// Creates the simulation of the network described by `config`, letting `configure` tune every drone.
+    ///
+    /// # Arguments
+    /// - `config`: The description of the network.
+    /// - `seed`: The seed from which the random number generator of every drone is seeded.
+    /// - `configure`: Called on every `DroneCore`, in increasing order of ID, e.g. to set its policies.
+    ///
+    /// # Errors
+    /// Returns a `NetworkError` if the description is inconsistent, see `Network::launch_with`.
+    pub fn new_with(
+        config: &Config,
+        seed: u64,
+        configure: impl Fn(DroneCore) -> DroneCore,
+    ) -> Result<Self, NetworkError> {
+        let links = links(config)?;
+
+        let mut pdrs: Vec<(NodeId, f32)> = config
+            .drone
+            .iter()
+            .map(|drone| (drone.id, drone.pdr))
+            .collect();
+        pdrs.sort_unstable_by_key(|(drone_id, _)| *drone_id);
+
+        let mut seeds = StdRng::seed_from_u64(seed);
+        let drones = pdrs
+            .into_iter()
+            .map(|(drone_id, pdr)| {
+                let core = DroneCore::new(drone_id, pdr, links[&drone_id].iter().copied())
+                    .with_rng_seed(seeds.gen());
+                (drone_id, configure(core))
+            })
+            .collect();
+
+        let hosts = config
+            .client
+            .iter()
+            .map(|client| client.id)
+            .chain(config.server.iter().map(|server| server.id))
+            .collect();
+
+        Ok(Self {
+            now: Duration::ZERO,
+            drones,
+            hosts,
+            link_delays: HashMap::new(),
+            default_delay: LinkDelay::Fixed(DEFAULT_LINK_LATENCY),
+            rng: StdRng::seed_from_u64(seeds.gen()),
+            queue: BinaryHeap::new(),
+            next_seq: 0,
+            events: Vec::new(),
+        })
+    }
+
+    /// Sets the delay of the links whose delay is not set with `Simulation::set_link_delay`.
+    ///
+    /// # Arguments
+    /// - `delay`: The `LinkDelay` of the time a packet takes to cross a link, a fixed `DEFAULT_LINK_LATENCY`
+    ///   if not set.
+    ///
+    /// # Returns
+    /// The same `Simulation`, for chaining after `Simulation::new`.
+    #[must_use]
+    pub fn with_default_delay(mut self, delay: LinkDelay) -> Self {
+        self.default_delay = delay;
+        self
+    }
+
+    /// Sets a fixed delay for the links whose delay is not set, see `Simulation::with_default_delay`.
+    #[must_use]
+    pub fn with_default_latency(self, latency: Duration) -> Self {
+        self.with_default_delay(LinkDelay::Fixed(latency))
+    }
+
+    /// Sets the delay of the link between two nodes, in both directions.
+    ///
+    /// The packets already on their way keep the delay they were sent with.
+    ///
+    /// # Arguments
+    /// - `node_id`, `neighbor_id`: The IDs of the two ends of the link.
+    /// - `delay`: The `LinkDelay` of the time a packet takes to cross the link.
+    pub fn set_link_delay(&mut self, node_id: NodeId, neighbor_id: NodeId, delay: LinkDelay) {
+        self.link_delays
+            .insert(link_key(node_id, neighbor_id), delay);
+    }
+
+    /// Sets a fixed delay for the link between two nodes, see `Simulation::set_link_delay`.
+    pub fn set_latency(&mut self, node_id: NodeId, neighbor_id: NodeId, latency: Duration) {
+        self.set_link_delay(node_id, neighbor_id, LinkDelay::Fixed(latency));
+    }
+
+    /// Returns the delay of the link between two nodes.
+    #[must_use]
+    pub fn link_delay(&self, node_id: NodeId, neighbor_id: NodeId) -> LinkDelay {
+        self.link_delays
+            .get(&link_key(node_id, neighbor_id))
+            .copied()
+            .unwrap_or(self.default_delay)
+    }
+
+    /// Returns the virtual time of the simulation.
+    #[must_use]
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Returns a drone that hasn't crashed, e.g. to inspect its buffer.
+    #[must_use]
+    pub fn drone(&self, drone_id: NodeId) -> Option<&DroneCore> {
+        self.drones.get(&drone_id)
+    }
+
+    /// Schedules an input for a node.
+    ///
+    /// Inputs for a node that doesn't exist, or for a drone that has crashed by then, are discarded.
+    ///
+    /// # Arguments
+    /// - `delay`: How long after the current virtual time the node receives the input.
+    /// - `node_id`: The ID of the node.
+    /// - `input`: The packet or the command the node receives.
+    pub fn schedule(&mut self, delay: Duration, node_id: NodeId, input: DroneInput) {
+        self.queue.push(Scheduled {
+            time: self.now + delay,
+            seq: self.next_seq,
+            node_id,
+            input,
+        });
+        self.next_seq += 1;
+    }
+
+    /// Sends a packet to a node, which receives it at the current virtual time.
+    pub fn send_packet(&mut self, node_id: NodeId, packet: Packet) {
+        self.schedule(Duration::ZERO, node_id, DroneInput::Packet(packet));
+    }
+
+    /// Sends a command to a drone, which receives it at the current virtual time.
+    pub fn send_command(&mut self, drone_id: NodeId, command: DroneCommand) {
+        self.schedule(Duration::ZERO, drone_id, DroneInput::Command(command));
+    }
+
+    /// Handles the next input in the queue, moving the virtual clock to its time.
+    ///
+    /// # Returns
+    /// `false` if the queue was empty, `true` otherwise.
+    pub fn step(&mut self) -> bool {
+        let Some(scheduled) = self.queue.pop() else {
+            return false;
+        };
+        self.now = scheduled.time;
+        let node_id = scheduled.node_id;
+
+        if let Some(core) = self.drones.get_mut(&node_id) {
+            core.set_clock(self.now);
+            let actions = core.handle(scheduled.input);
+            self.perform(node_id, actions);
+        } else if let (true, DroneInput::Packet(packet)) =
+            (self.hosts.contains(&node_id), scheduled.input)
+        {
+            self.events.push(SimulationEvent::Delivered {
+                time: self.now,
+                host_id: node_id,
+                packet,
+            });
+        } else {
+            warn!(
+                "{} [ Node {} ] can't receive the input, it doesn't exist or has crashed",
+                "!!!".yellow(),
+                node_id
+            );
+        }
+
+        true
+    }
+
+    /// Handles the inputs in the queue until the virtual time reaches `time`.
+    ///
+    /// # Arguments
+    /// - `time`: The virtual time to reach, the inputs scheduled at that time are handled too.
+    pub fn run_until(&mut self, time: Duration) {
+        while self.queue.peek().is_some_and(|next| next.time <= time) {
+            self.step();
+        }
+        self.now = self.now.max(time);
+    }
+
+    /// Handles the inputs in the queue until there is none left.
+    ///
+    /// # Returns
+    /// The virtual time of the last input.
+    pub fn run(&mut self) -> Duration {
+        while self.step() {}
+        self.now
+    }
+
+    /// Returns the events that happened so far, in order.
+    #[must_use]
+    pub fn events(&self) -> &[SimulationEvent] {
+        &self.events
+    }
+
+    /// Returns the events that happened so far, in order, and forgets them.
+    pub fn take_events(&mut self) -> Vec<SimulationEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Performs the actions returned by the `DroneCore` of a drone, in order.
+    ///
+    /// # Arguments
+    /// - `drone_id`: The ID of the drone.
+    /// - `actions`: The `DroneAction`s to perform.
+    fn perform(&mut self, drone_id: NodeId, actions: Vec<DroneAction>) {
+        for action in actions {
+            match action {
+                DroneAction::Send { to, packet } => self.send(drone_id, to, packet),
+                DroneAction::Shortcut(packet) => {
+                    self.report(drone_id, DroneEvent::ControllerShortcut(packet));
+                }
+                DroneAction::Dropped(packet) => {
+                    self.report(drone_id, DroneEvent::PacketDropped(packet));
+                }
+                DroneAction::Event(event) => self.events.push(SimulationEvent::Rustastic {
+                    time: self.now,
+                    event,
+                }),
+                // The core keeps track of its neighbors, packets always travel through the simulation
+                DroneAction::Connect(..) | DroneAction::Disconnect(_) => {}
+                DroneAction::Crash => {
+                    self.drones.remove(&drone_id);
+                }
+            }
+        }
+    }
+
+    /// Sends a packet from a drone to its neighbor, which receives it after the delay of their link.
+    ///
+    /// # Behavior
+    /// - If the neighbor exists, the packet is reported with a `PacketSent` event.
+    /// - If the neighbor has crashed, the failure is reported to the drone's `DroneCore`, which may declare the
+    ///   neighbor dead. A fragment is NACKed to its source with `ErrorInRouting`, the other packets are shortcut
+    ///   to the simulation controller, except for a `FloodRequest` that can only be logged, as `RustasticDrone`
+    ///   does when its transport fails.
+    fn send(&mut self, drone_id: NodeId, to: NodeId, packet: Packet) {
+        if self.drones.contains_key(&to) || self.hosts.contains(&to) {
+            if let Some(core) = self.drones.get_mut(&drone_id) {
+                core.report_send_success(to);
+            }
+            let delay = self.link_delay(drone_id, to).sample(&mut self.rng);
+            self.schedule(delay, to, DroneInput::Packet(packet.clone()));
+            self.report(drone_id, DroneEvent::PacketSent(packet));
+            return;
+        }
+
+        error!(
+            "{} [ Drone {} ]: Failed to send the {} to [ Drone {} ]: it has crashed",
+            "✗".red(),
+            drone_id,
+            packet.pack_type,
+            to
+        );
+        let Some(core) = self.drones.get_mut(&drone_id) else {
+            return;
+        };
+        let mut actions = core.report_send_failure(to);
+        match packet.pack_type {
+            PacketType::MsgFragment(_) => actions.extend(core.nack_unreachable(packet)),
+            PacketType::FloodRequest(_) => {}
+            _ => actions.push(DroneAction::Shortcut(packet)),
+        }
+        self.perform(drone_id, actions);
+    }
+
+    fn report(&mut self, drone_id: NodeId, event: DroneEvent) {
+        self.events.push(SimulationEvent::Controller {
+            time: self.now,
+            drone_id,
+            event,
+        });
+    }
+}
+
+/// Returns the key of the link between two nodes in `Simulation::link_delays`.
+fn link_key(node_id: NodeId, neighbor_id: NodeId) -> (NodeId, NodeId) {
+    (node_id.min(neighbor_id), node_id.max(neighbor_id))
+}
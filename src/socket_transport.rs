@@ -0,0 +1,346 @@
+//! This file contains the Rustastic Drone's socket transport, developed by the Group Rustastic.
+//!
+//! File:   drone/socket_transport.rs
+//!
+//! Brief:  File for the `SocketTransport`, which lets drones in different processes exchange packets
+//!         over localhost UDP or Unix domain datagram sockets.
+//!
+//! Author: Rustastic
+
+use colored::Colorize;
+use crossbeam_channel::{unbounded, Receiver, Sender, TrySendError};
+use log::{error, warn};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use wg_2024::{network::NodeId, packet::Packet};
+
+use crate::codec::{self, HEADER_LEN, MAX_PAYLOAD_LEN};
+use crate::transport::{PacketTransport, TransportError};
+
+/// How often the receiving thread checks whether its transport was dropped.
+const RECV_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The address of a drone's socket.
+///
+/// Written as `udp:<ip>:<port>` (or just `<ip>:<port>`) for UDP, and as `unix:<path>` for Unix domain sockets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SocketAddress {
+    /// A UDP socket.
+    Udp(SocketAddr),
+    /// A Unix domain datagram socket.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SocketAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(SocketAddress::Unix(PathBuf::from(path)));
+        }
+
+        s.strip_prefix("udp:")
+            .unwrap_or(s)
+            .parse()
+            .map(SocketAddress::Udp)
+            .map_err(|e| format!("invalid socket address `{s}`: {e}"))
+    }
+}
+
+impl fmt::Display for SocketAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketAddress::Udp(addr) => write!(f, "udp:{addr}"),
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// A bound datagram socket.
+#[derive(Debug)]
+enum Socket {
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+impl Socket {
+    fn bind(address: &SocketAddress) -> io::Result<Self> {
+        match address {
+            SocketAddress::Udp(addr) => UdpSocket::bind(addr).map(Socket::Udp),
+            #[cfg(unix)]
+            SocketAddress::Unix(path) => UnixDatagram::bind(path).map(Socket::Unix),
+        }
+    }
+
+    /// Creates an unnamed socket of the same kind to send from, which doesn't block when a peer is full.
+    ///
+    /// A clone would share the non-blocking flag with the receiving socket, whose reads must wait.
+    fn nonblocking_sender(&self) -> io::Result<Self> {
+        let sender = match self {
+            Socket::Udp(socket) => {
+                UdpSocket::bind(SocketAddr::new(socket.local_addr()?.ip(), 0)).map(Socket::Udp)?
+            }
+            #[cfg(unix)]
+            Socket::Unix(_) => UnixDatagram::unbound().map(Socket::Unix)?,
+        };
+        match &sender {
+            Socket::Udp(socket) => socket.set_nonblocking(true)?,
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket.set_nonblocking(true)?,
+        }
+        Ok(sender)
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Socket::Udp(socket) => socket.try_clone().map(Socket::Udp),
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket.try_clone().map(Socket::Unix),
+        }
+    }
+
+    fn local_address(&self) -> io::Result<SocketAddress> {
+        match self {
+            Socket::Udp(socket) => socket.local_addr().map(SocketAddress::Udp),
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket
+                .local_addr()?
+                .as_pathname()
+                .map(|path| SocketAddress::Unix(path.to_path_buf()))
+                .ok_or_else(|| io::Error::other("unnamed Unix socket")),
+        }
+    }
+
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Socket::Udp(socket) => socket.set_read_timeout(Some(timeout)),
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket.set_read_timeout(Some(timeout)),
+        }
+    }
+
+    fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Socket::Udp(socket) => socket.recv_from(buf).map(|(len, _)| len),
+            #[cfg(unix)]
+            Socket::Unix(socket) => socket.recv(buf),
+        }
+    }
+
+    fn send_to(&self, frame: &[u8], address: &SocketAddress) -> io::Result<usize> {
+        match (self, address) {
+            (Socket::Udp(socket), SocketAddress::Udp(addr)) => socket.send_to(frame, addr),
+            #[cfg(unix)]
+            (Socket::Unix(socket), SocketAddress::Unix(path)) => socket.send_to(frame, path),
+            #[cfg(unix)]
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the address and the socket are of different kinds",
+            )),
+        }
+    }
+}
+
+/// A `PacketTransport` over datagram sockets, for drones running in separate OS processes.
+///
+/// Every packet travels in a single datagram, encoded as a frame by `codec::encode`. A background thread
+/// decodes the datagrams received by the socket and delivers the packets on the transport's `packet_recv`;
+/// frames that fail to decode are logged and discarded. Packets are sent from a separate non-blocking socket:
+/// a neighbor whose receive buffer is full is reported as `TransportError::Full`, instead of stalling the drone.
+///
+/// The links added by a `DroneCommand::AddSender` stay in-process, over their crossbeam channel.
+///
+/// # Fields
+/// - `socket`: The socket the drone is bound to.
+/// - `send_socket`: The non-blocking socket the packets are sent from.
+/// - `neighbors`: The socket addresses of the neighbors, keyed by their IDs.
+/// - `local_links`: The in-process links added by `add_sender`.
+/// - `packet_recv`: The channel on which the received packets are delivered.
+/// - `running`: Cleared when the transport is dropped, to stop the receiving thread.
+#[derive(Debug)]
+pub struct SocketTransport {
+    socket: Socket,
+    send_socket: Socket,
+    neighbors: HashMap<NodeId, SocketAddress>,
+    local_links: HashMap<NodeId, Sender<Packet>>,
+    packet_recv: Receiver<Packet>,
+    running: Arc<AtomicBool>,
+}
+
+impl SocketTransport {
+    /// Binds a new `SocketTransport` and starts receiving packets.
+    ///
+    /// # Arguments
+    /// - `address`: The address to bind the drone's socket to.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the socket can't be bound.
+    pub fn bind(address: &SocketAddress) -> io::Result<Self> {
+        let socket = Socket::bind(address)?;
+        let reader = socket.try_clone()?;
+        reader.set_read_timeout(RECV_POLL_INTERVAL)?;
+        let send_socket = socket.nonblocking_sender()?;
+
+        let (packet_send, packet_recv) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let reader_running = Arc::clone(&running);
+        thread::spawn(move || receive_loop(&reader, &packet_send, &reader_running));
+
+        Ok(Self {
+            socket,
+            send_socket,
+            neighbors: HashMap::new(),
+            local_links: HashMap::new(),
+            packet_recv,
+            running,
+        })
+    }
+
+    /// Adds a neighbor reachable at a socket address.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    /// - `address`: The address of the neighbor's socket.
+    ///
+    /// # Returns
+    /// The same `SocketTransport`, for chaining after `SocketTransport::bind`.
+    #[must_use]
+    pub fn with_neighbor(mut self, node_id: NodeId, address: SocketAddress) -> Self {
+        self.neighbors.insert(node_id, address);
+        self
+    }
+
+    /// Returns the address the transport is bound to.
+    ///
+    /// # Errors
+    /// Returns an `io::Error` if the address of the socket can't be read.
+    pub fn local_address(&self) -> io::Result<SocketAddress> {
+        self.socket.local_address()
+    }
+}
+
+impl Drop for SocketTransport {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+
+        // The socket file outlives the socket: a drone restarted on the same path would fail with `AddrInUse`
+        #[cfg(unix)]
+        if let Ok(SocketAddress::Unix(path)) = self.socket.local_address() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                warn!(
+                    "{} Can't remove the socket file {}: {}",
+                    "!!!".yellow(),
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+impl PacketTransport for SocketTransport {
+    fn send(&self, to: NodeId, packet: Packet) -> Result<(), TransportError> {
+        if let Some(sender) = self.local_links.get(&to) {
+            return sender.try_send(packet).map_err(|e| match e {
+                TrySendError::Full(_) => TransportError::Full(to),
+                TrySendError::Disconnected(_) => TransportError::Disconnected(to),
+            });
+        }
+
+        let address = self
+            .neighbors
+            .get(&to)
+            .ok_or(TransportError::NotConnected(to))?;
+        let frame = codec::encode(&packet).map_err(TransportError::Codec)?;
+        self.send_socket
+            .send_to(&frame, address)
+            .map(|_| ())
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::WouldBlock => TransportError::Full(to),
+                _ => TransportError::Io(to, e),
+            })
+    }
+
+    fn packet_recv(&self) -> &Receiver<Packet> {
+        &self.packet_recv
+    }
+
+    fn neighbors(&self) -> Vec<NodeId> {
+        self.neighbors
+            .keys()
+            .chain(self.local_links.keys())
+            .copied()
+            .collect()
+    }
+
+    fn add_sender(&mut self, node_id: NodeId, sender: Sender<Packet>) {
+        self.local_links.insert(node_id, sender);
+    }
+
+    fn remove_sender(&mut self, node_id: NodeId) {
+        self.local_links.remove(&node_id);
+        self.neighbors.remove(&node_id);
+    }
+}
+
+/// Receives datagrams until the transport is dropped, delivering the decoded packets on `packet_send`.
+///
+/// Receive errors don't stop the loop: they are logged, and the socket is polled again after a pause.
+///
+/// # Arguments
+/// - `socket`: A clone of the transport's socket, with a read timeout.
+/// - `packet_send`: The channel to deliver the packets on.
+/// - `running`: Cleared when the transport is dropped.
+fn receive_loop(socket: &Socket, packet_send: &Sender<Packet>, running: &AtomicBool) {
+    let mut buf = vec![0; HEADER_LEN + MAX_PAYLOAD_LEN];
+
+    while running.load(Ordering::Relaxed) {
+        let len = match socket.recv(&mut buf) {
+            Ok(len) => len,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock
+                        | io::ErrorKind::TimedOut
+                        | io::ErrorKind::Interrupted
+                ) =>
+            {
+                continue;
+            }
+            Err(e) => {
+                error!("{} Failed to receive from the socket: {}", "✗".red(), e);
+                thread::sleep(RECV_POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        match codec::decode(&buf[..len]) {
+            Ok((packet, frame_len)) if frame_len == len => {
+                if packet_send.send(packet).is_err() {
+                    return;
+                }
+            }
+            Ok((_, frame_len)) => warn!(
+                "{} Discarded a datagram with {} bytes after its frame",
+                "!!!".yellow(),
+                len - frame_len
+            ),
+            Err(e) => warn!("{} Discarded an invalid frame: {}", "!!!".yellow(), e),
+        }
+    }
+}
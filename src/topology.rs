@@ -0,0 +1,208 @@
+//! This file contains the Rustastic Drone's topology learning, developed by the Group Rustastic.
+//!
+//! File:   drone/topology.rs
+//!
+//! Brief:  File for the `Topology` a drone learns from the `path_trace`s of the floods that cross it.
+//!
+//! Author: Rustastic
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
+use wg_2024::{network::NodeId, packet::NodeType};
+
+/// A node of a learned `Topology`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LearnedNode {
+    /// The ID of the node.
+    pub id: NodeId,
+    /// The type of the node, as reported by the last trace it appeared in.
+    pub node_type: NodeType,
+    /// When the node last appeared in a trace.
+    pub last_seen: Instant,
+}
+
+/// A link of a learned `Topology`, between two nodes that were consecutive in a trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LearnedEdge {
+    /// The end of the link with the lowest ID.
+    pub a: NodeId,
+    /// The end of the link with the highest ID.
+    pub b: NodeId,
+    /// When the link last appeared in a trace.
+    pub last_seen: Instant,
+}
+
+/// The drops and NACKs a drone observed on a link.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkCounters {
+    /// Packets dropped on their way through the link.
+    pub drops: u64,
+    /// NACKs that crossed the link, in either direction.
+    pub nacks: u64,
+}
+
+/// The network graph a drone learns passively from the `path_trace`s of the `FloodRequest`s and
+/// `FloodResponse`s that cross it, see `RustasticDrone::with_topology`.
+///
+/// Links are undirected. Nodes and links are never forgotten: their `last_seen` time tells how fresh they are.
+/// The drone also records its own links and what it observes on them, so that the topology can be exported
+/// (see `Topology::to_dot` and `Topology::to_json`).
+///
+/// # Fields
+/// - `nodes`: The type and last-seen time of the nodes, keyed by their IDs.
+/// - `edges`: The last-seen time of the links, keyed by their ends in increasing order.
+/// - `local`: The ID of the drone that owns the topology and its current neighbors, if it recorded them.
+/// - `counters`: The `LinkCounters` of the links, keyed by their ends in increasing order.
+#[derive(Debug, Clone, Default)]
+pub struct Topology {
+    nodes: BTreeMap<NodeId, (NodeType, Instant)>,
+    edges: BTreeMap<(NodeId, NodeId), Instant>,
+    local: Option<(NodeId, BTreeSet<NodeId>)>,
+    counters: BTreeMap<(NodeId, NodeId), LinkCounters>,
+}
+
+/// Returns the key of the link between `a` and `b`.
+fn link(a: NodeId, b: NodeId) -> (NodeId, NodeId) {
+    (a.min(b), a.max(b))
+}
+
+impl Topology {
+    /// Creates an empty `Topology`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Learns the nodes of a trace, and the links between consecutive nodes.
+    ///
+    /// # Arguments
+    /// - `path_trace`: The nodes crossed by a flood, in order.
+    /// - `now`: When the trace was seen.
+    pub fn observe(&mut self, path_trace: &[(NodeId, NodeType)], now: Instant) {
+        for (node_id, node_type) in path_trace {
+            self.nodes.insert(*node_id, (*node_type, now));
+        }
+        for hop in path_trace.windows(2) {
+            let (a, b) = (hop[0].0, hop[1].0);
+            if a != b {
+                self.edges.insert(link(a, b), now);
+            }
+        }
+    }
+
+    /// Records the current neighbors of the drone that owns the topology.
+    ///
+    /// # Arguments
+    /// - `drone_id`: The ID of the drone.
+    /// - `neighbors`: The IDs of the nodes the drone can send packets to.
+    pub fn set_local_links(
+        &mut self,
+        drone_id: NodeId,
+        neighbors: impl IntoIterator<Item = NodeId>,
+    ) {
+        self.local = Some((drone_id, neighbors.into_iter().collect()));
+    }
+
+    /// Returns the drone that owns the topology and its current neighbors, if it recorded them.
+    #[must_use]
+    pub fn local_links(&self) -> Option<(NodeId, Vec<NodeId>)> {
+        self.local
+            .as_ref()
+            .map(|(drone_id, neighbors)| (*drone_id, neighbors.iter().copied().collect()))
+    }
+
+    /// Counts a packet dropped on the link between `a` and `b`.
+    pub fn record_drop(&mut self, a: NodeId, b: NodeId) {
+        self.counters.entry(link(a, b)).or_default().drops += 1;
+    }
+
+    /// Counts a NACK that crossed the link between `a` and `b`.
+    pub fn record_nack(&mut self, a: NodeId, b: NodeId) {
+        self.counters.entry(link(a, b)).or_default().nacks += 1;
+    }
+
+    /// Returns the drops and NACKs observed on the link between `a` and `b`.
+    #[must_use]
+    pub fn counters(&self, a: NodeId, b: NodeId) -> LinkCounters {
+        self.counters.get(&link(a, b)).copied().unwrap_or_default()
+    }
+
+    /// Returns `true` if nothing has been learned yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns a node, if it has been seen.
+    #[must_use]
+    pub fn node(&self, node_id: NodeId) -> Option<LearnedNode> {
+        self.nodes
+            .get(&node_id)
+            .map(|(node_type, last_seen)| LearnedNode {
+                id: node_id,
+                node_type: *node_type,
+                last_seen: *last_seen,
+            })
+    }
+
+    /// Returns the nodes seen, in increasing order of ID.
+    #[must_use]
+    pub fn nodes(&self) -> Vec<LearnedNode> {
+        self.nodes.keys().filter_map(|id| self.node(*id)).collect()
+    }
+
+    /// Returns the links seen, in increasing order of their ends.
+    #[must_use]
+    pub fn edges(&self) -> Vec<LearnedEdge> {
+        self.edges
+            .iter()
+            .map(|((a, b), last_seen)| LearnedEdge {
+                a: *a,
+                b: *b,
+                last_seen: *last_seen,
+            })
+            .collect()
+    }
+
+    /// Returns the nodes linked to a node, in increasing order of ID.
+    #[must_use]
+    pub fn neighbors(&self, node_id: NodeId) -> Vec<NodeId> {
+        let mut neighbors: Vec<NodeId> = self
+            .edges
+            .keys()
+            .filter_map(|(a, b)| match node_id {
+                id if id == *a => Some(*b),
+                id if id == *b => Some(*a),
+                _ => None,
+            })
+            .collect();
+        neighbors.sort_unstable();
+        neighbors
+    }
+
+    /// Splits the nodes seen in the groups that are linked together, to spot the partitions of the network.
+    ///
+    /// # Returns
+    /// The IDs of the nodes of each group in increasing order, the groups by their lowest ID.
+    #[must_use]
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        let mut unvisited: BTreeSet<NodeId> = self.nodes.keys().copied().collect();
+        let mut components = Vec::new();
+
+        while let Some(start) = unvisited.pop_first() {
+            let mut component = BTreeSet::from([start]);
+            let mut frontier = vec![start];
+            while let Some(node_id) = frontier.pop() {
+                for neighbor in self.neighbors(node_id) {
+                    if unvisited.remove(&neighbor) {
+                        component.insert(neighbor);
+                        frontier.push(neighbor);
+                    }
+                }
+            }
+            components.push(component.into_iter().collect());
+        }
+
+        components
+    }
+}
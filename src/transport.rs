@@ -0,0 +1,149 @@
+//! This file contains the Rustastic Drone's transport abstraction, developed by the Group Rustastic.
+//!
+//! File:   drone/transport.rs
+//!
+//! Brief:  File for the `PacketTransport` trait, which moves packets between a drone and its neighbors,
+//!         and for its default implementation over crossbeam channels.
+//!
+//! Author: Rustastic
+
+use crossbeam_channel::{Receiver, Sender, TrySendError};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use wg_2024::{network::NodeId, packet::Packet};
+
+use crate::codec::CodecError;
+
+/// An error raised by a `PacketTransport` while sending a packet.
+#[derive(Debug)]
+pub enum TransportError {
+    /// The transport has no link to the node.
+    NotConnected(NodeId),
+    /// The link to the node exists, but the other end is gone.
+    Disconnected(NodeId),
+    /// The link to the node can't take the packet now, it may later.
+    Full(NodeId),
+    /// The packet could not be encoded for the wire.
+    Codec(CodecError),
+    /// The operating system refused to send the packet to the node.
+    Io(NodeId, io::Error),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportError::NotConnected(node_id) => {
+                write!(f, "No connection to [ Drone {node_id} ]")
+            }
+            TransportError::Disconnected(node_id) => {
+                write!(f, "[ Drone {node_id} ] is disconnected")
+            }
+            TransportError::Full(node_id) => write!(f, "The link to [ Drone {node_id} ] is full"),
+            TransportError::Codec(e) => write!(f, "Can't encode the packet: {e}"),
+            TransportError::Io(node_id, e) => write!(f, "Can't send to [ Drone {node_id} ]: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+/// Moves packets between a `RustasticDrone` and its neighbors.
+///
+/// The drone only decides where packets go: how they get there is up to the transport, so the same
+/// drone can run across threads, across processes or inside a simulator.
+///
+/// Whatever the medium, the packets received from the neighbors are delivered on a crossbeam `Receiver`,
+/// which the drone selects on together with the commands of the simulation controller.
+pub trait PacketTransport {
+    /// Sends a packet to a neighbor, without blocking.
+    ///
+    /// # Arguments
+    /// - `to`: The ID of the neighbor.
+    /// - `packet`: The `Packet` to send.
+    ///
+    /// # Errors
+    /// Returns a `TransportError` if the packet could not be handed over to the neighbor, `TransportError::Full`
+    /// if the neighbor can't take it yet.
+    fn send(&self, to: NodeId, packet: Packet) -> Result<(), TransportError>;
+
+    /// Returns the channel on which the packets received from the neighbors are delivered.
+    fn packet_recv(&self) -> &Receiver<Packet>;
+
+    /// Returns the IDs of the neighbors the transport can send packets to.
+    fn neighbors(&self) -> Vec<NodeId>;
+
+    /// Adds an in-process link to a neighbor, as requested by a `DroneCommand::AddSender`.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    /// - `sender`: The channel to send the neighbor's packets to.
+    fn add_sender(&mut self, node_id: NodeId, sender: Sender<Packet>);
+
+    /// Removes the link to a neighbor, as requested by a `DroneCommand::RemoveSender`.
+    ///
+    /// # Arguments
+    /// - `node_id`: The ID of the neighbor.
+    fn remove_sender(&mut self, node_id: NodeId);
+}
+
+/// The default `PacketTransport`, over crossbeam channels between threads of the same process.
+///
+/// # Fields
+/// - `packet_recv`: A channel for receiving incoming packets from other drones.
+/// - `packet_send`: A map that associates neighboring drone IDs to their packet-sending channels.
+#[derive(Debug, Clone)]
+pub struct ChannelTransport {
+    packet_recv: Receiver<Packet>,
+    packet_send: HashMap<NodeId, Sender<Packet>>,
+}
+
+impl ChannelTransport {
+    /// Creates a new `ChannelTransport`.
+    ///
+    /// # Arguments
+    /// - `packet_recv`: The channel to receive packets from other drones.
+    /// - `packet_send`: A map of packet-sending channels to other drones, keyed by their IDs.
+    ///
+    /// # Returns
+    /// A new instance of `ChannelTransport`.
+    #[must_use]
+    pub fn new(
+        packet_recv: Receiver<Packet>,
+        packet_send: HashMap<NodeId, Sender<Packet>>,
+    ) -> Self {
+        Self {
+            packet_recv,
+            packet_send,
+        }
+    }
+}
+
+impl PacketTransport for ChannelTransport {
+    fn send(&self, to: NodeId, packet: Packet) -> Result<(), TransportError> {
+        let sender = self
+            .packet_send
+            .get(&to)
+            .ok_or(TransportError::NotConnected(to))?;
+        sender.try_send(packet).map_err(|e| match e {
+            TrySendError::Full(_) => TransportError::Full(to),
+            TrySendError::Disconnected(_) => TransportError::Disconnected(to),
+        })
+    }
+
+    fn packet_recv(&self) -> &Receiver<Packet> {
+        &self.packet_recv
+    }
+
+    fn neighbors(&self) -> Vec<NodeId> {
+        self.packet_send.keys().copied().collect()
+    }
+
+    fn add_sender(&mut self, node_id: NodeId, sender: Sender<Packet>) {
+        self.packet_send.insert(node_id, sender);
+    }
+
+    fn remove_sender(&mut self, node_id: NodeId) {
+        self.packet_send.remove(&node_id);
+    }
+}
@@ -0,0 +1,39 @@
+//! Helpers shared by the integration tests.
+//!
+//! Each test binary only uses some of them.
+#![allow(dead_code)]
+
+use std::time::Duration;
+use wg_2024::{
+    network::{NodeId, SourceRoutingHeader},
+    packet::{Fragment, Packet, PacketType},
+};
+
+/// The size of the data of a full fragment.
+pub const FRAGMENT_DSIZE: usize = 128;
+/// How long a test waits for a drone before giving up.
+pub const TIMEOUT: Duration = Duration::from_secs(2);
+/// The session of the fragments built by `fragment_packet`.
+pub const SESSION_ID: u64 = 7;
+
+/// A full fragment of session `SESSION_ID`, routed along `hops` and arriving at `hops[1]`.
+pub fn fragment_packet(fragment_index: u64, hops: Vec<NodeId>) -> Packet {
+    Packet {
+        pack_type: PacketType::MsgFragment(Fragment {
+            fragment_index,
+            total_n_fragments: 100,
+            length: FRAGMENT_DSIZE as u8,
+            data: [1; FRAGMENT_DSIZE],
+        }),
+        routing_header: SourceRoutingHeader { hop_index: 1, hops },
+        session_id: SESSION_ID,
+    }
+}
+
+/// Returns the index of a fragment, panicking on any other packet.
+pub fn fragment_index(packet: &Packet) -> u64 {
+    match &packet.pack_type {
+        PacketType::MsgFragment(fragment) => fragment.fragment_index,
+        pack_type => panic!("unexpected packet {pack_type:?}"),
+    }
+}
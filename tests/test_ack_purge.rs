@@ -0,0 +1,117 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID};
+use rustastic_drone::{BufferStats, RustasticDrone};
+
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Ack, Nack, NackType, Packet, PacketType},
+};
+
+/// Creates drone 1 between neighbors 0 and 2, returning the controller, upstream and downstream receivers
+fn setup_drone() -> (
+    RustasticDrone,
+    Receiver<DroneEvent>,
+    Receiver<Packet>,
+    Receiver<Packet>,
+) {
+    let (controller_send, controller_recv) = unbounded();
+    let (upstream_send, upstream_recv) = unbounded();
+    let (downstream_send, downstream_recv) = unbounded();
+
+    let mut packet_send = HashMap::new();
+    packet_send.insert(0, upstream_send);
+    packet_send.insert(2, downstream_send);
+
+    let drone = RustasticDrone::new(
+        1,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        0.0,
+    );
+
+    (drone, controller_recv, upstream_recv, downstream_recv)
+}
+
+/// A packet travelling back from drone 2 to node 0
+fn backward_packet(pack_type: PacketType) -> Packet {
+    Packet {
+        pack_type,
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![2, 1, 0],
+        },
+        session_id: SESSION_ID,
+    }
+}
+
+#[test]
+fn test_ack_releases_buffered_fragment() {
+    let (mut drone, _controller_recv, upstream_recv, _downstream_recv) = setup_drone();
+
+    drone.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+    drone.handle_packet(fragment_packet(1, vec![0, 1, 2]));
+    assert_eq!(drone.buffer().len(), 2);
+
+    drone.handle_packet(backward_packet(PacketType::Ack(Ack { fragment_index: 0 })));
+
+    // The Ack is still forwarded to the source
+    assert!(upstream_recv
+        .try_recv()
+        .is_ok_and(|p| matches!(p.pack_type, PacketType::Ack(_))));
+    assert_eq!(drone.buffer().len(), 1);
+    assert!(drone.buffer_mut().get_fragment(SESSION_ID, 0).is_none());
+    assert!(drone.buffer_mut().get_fragment(SESSION_ID, 1).is_some());
+}
+
+#[test]
+fn test_acked_fragment_is_not_recovered() {
+    let (mut drone, _controller_recv, upstream_recv, downstream_recv) = setup_drone();
+
+    drone.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+    drone.handle_packet(backward_packet(PacketType::Ack(Ack { fragment_index: 0 })));
+    drone.handle_packet(backward_packet(PacketType::Nack(Nack {
+        fragment_index: 0,
+        nack_type: NackType::Dropped,
+    })));
+
+    assert_eq!(downstream_recv.try_iter().count(), 1);
+    assert_eq!(upstream_recv.try_iter().count(), 2);
+}
+
+#[test]
+fn test_buffer_stats() {
+    let (mut drone, _controller_recv, _upstream_recv, _downstream_recv) = setup_drone();
+    drone.buffer_mut().edit_max_size_buffer(2);
+
+    for fragment_index in 0..3 {
+        drone.handle_packet(fragment_packet(fragment_index, vec![0, 1, 2]));
+    }
+    drone.handle_packet(backward_packet(PacketType::Ack(Ack { fragment_index: 2 })));
+    drone.handle_packet(backward_packet(PacketType::Nack(Nack {
+        fragment_index: 1,
+        nack_type: NackType::Dropped,
+    })));
+    drone.handle_packet(backward_packet(PacketType::Nack(Nack {
+        fragment_index: 1,
+        nack_type: NackType::ErrorInRouting(3),
+    })));
+
+    assert_eq!(
+        drone.buffer().stats(),
+        BufferStats {
+            acked: 1,
+            retrieved: 1,
+            evicted: 1,
+            retransmitted: 1,
+            abandoned: 0,
+        }
+    );
+    assert!(drone.buffer().is_empty());
+}
@@ -0,0 +1,267 @@
+mod common;
+
+use common::{fragment_index, fragment_packet, TIMEOUT};
+use rustastic_drone::{OverflowPolicy, RustasticDrone, RustasticEvent};
+#[cfg(unix)]
+use rustastic_drone::{SocketAddress, SocketTransport};
+
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use std::{
+    collections::HashMap,
+    thread::{self, JoinHandle},
+    time::Instant,
+};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    drone::Drone,
+    packet::{NackType, Packet, PacketType},
+};
+
+struct CongestedDrone {
+    packet_send: Sender<Packet>,
+    command_send: Sender<DroneCommand>,
+    controller_recv: Receiver<DroneEvent>,
+    event_recv: Receiver<RustasticEvent>,
+    prev_recv: Receiver<Packet>,
+    next_recv: Receiver<Packet>,
+    thread: JoinHandle<()>,
+}
+
+impl CongestedDrone {
+    /// Drone 1, between Node 0 and Node 2, whose channel to Node 2 holds a single packet.
+    ///
+    /// The fragments of `input` are queued before the drone starts, and nobody reads Node 2's channel yet.
+    /// Its duplicate filter is enabled.
+    fn spawn(max_len: usize, policy: OverflowPolicy, input: impl Iterator<Item = u64>) -> Self {
+        let (controller_send, controller_recv) = unbounded();
+        let (command_send, command_recv) = unbounded();
+        let (packet_send, packet_recv) = unbounded();
+        let (event_send, event_recv) = unbounded();
+        let (prev_send, prev_recv) = unbounded();
+        let (next_send, next_recv) = bounded(1);
+
+        for fragment_index in input {
+            packet_send
+                .send(fragment_packet(fragment_index, vec![0, 1, 2]))
+                .unwrap();
+        }
+
+        let mut drone = RustasticDrone::new(
+            1,
+            controller_send,
+            command_recv,
+            packet_recv,
+            HashMap::from([(0, prev_send), (2, next_send)]),
+            0.0,
+        )
+        .with_event_send(event_send)
+        .with_duplicate_window(16)
+        .with_outbound_queue(max_len, policy);
+        let thread = thread::spawn(move || drone.run());
+
+        Self {
+            packet_send,
+            command_send,
+            controller_recv,
+            event_recv,
+            prev_recv,
+            next_recv,
+            thread,
+        }
+    }
+
+    /// Returns the indexes of the fragments dropped, after the neighbor got congested
+    fn overflow_drops(&self, count: usize) -> Vec<u64> {
+        assert!(matches!(
+            self.event_recv.recv_timeout(TIMEOUT),
+            Ok(RustasticEvent::NeighborCongested {
+                drone_id: 1,
+                neighbor_id: 2
+            })
+        ));
+        (0..count)
+            .map(|_| match self.event_recv.recv_timeout(TIMEOUT) {
+                Ok(RustasticEvent::OverflowDrop {
+                    drone_id: 1,
+                    neighbor_id: 2,
+                    packet,
+                }) => fragment_index(&packet),
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect()
+    }
+
+    fn delivered(&self, count: usize) -> Vec<u64> {
+        (0..count)
+            .map(|_| fragment_index(&self.next_recv.recv_timeout(TIMEOUT).unwrap()))
+            .collect()
+    }
+
+    fn crash(self) {
+        self.command_send.send(DroneCommand::Crash).unwrap();
+        self.thread.join().unwrap();
+    }
+}
+
+#[test]
+fn test_congested_neighbor_does_not_block_the_drone() {
+    let drone = CongestedDrone::spawn(64, OverflowPolicy::DropTail, 0..3);
+    assert!(drone.overflow_drops(0).is_empty());
+
+    // The drone still handles commands while Node 2 doesn't read its channel
+    let start = Instant::now();
+    drone.command_send.send(DroneCommand::Crash).unwrap();
+    drone.thread.join().unwrap();
+    assert!(start.elapsed() < TIMEOUT);
+    assert!(drone
+        .packet_send
+        .send(fragment_packet(3, vec![0, 1, 2]))
+        .is_err());
+}
+
+#[test]
+fn test_drop_tail() {
+    let drone = CongestedDrone::spawn(1, OverflowPolicy::DropTail, 0..4);
+    assert_eq!(drone.overflow_drops(2), [2, 3]);
+
+    // The queued fragment follows once Node 2 reads its channel
+    assert_eq!(drone.delivered(2), [0, 1]);
+    assert!(matches!(
+        drone.event_recv.recv_timeout(TIMEOUT),
+        Ok(RustasticEvent::NeighborRecovered {
+            drone_id: 1,
+            neighbor_id: 2
+        })
+    ));
+    assert!(drone.prev_recv.try_recv().is_err());
+    assert!(drone
+        .controller_recv
+        .try_iter()
+        .all(|event| matches!(event, DroneEvent::PacketSent(_))));
+
+    drone.crash();
+}
+
+#[test]
+fn test_dropped_fragment_is_not_a_duplicate() {
+    let drone = CongestedDrone::spawn(1, OverflowPolicy::DropTail, 0..4);
+    assert_eq!(drone.overflow_drops(2), [2, 3]);
+
+    // The source retransmits a dropped fragment: the drone forwards it again
+    drone
+        .packet_send
+        .send(fragment_packet(2, vec![0, 1, 2]))
+        .unwrap();
+    assert!(matches!(
+        drone.event_recv.recv_timeout(TIMEOUT),
+        Ok(RustasticEvent::OverflowDrop { packet, .. }) if fragment_index(&packet) == 2
+    ));
+
+    drone.crash();
+}
+
+#[test]
+fn test_drop_head() {
+    let drone = CongestedDrone::spawn(1, OverflowPolicy::DropHead, 0..4);
+    assert_eq!(drone.overflow_drops(2), [1, 2]);
+    assert_eq!(drone.delivered(2), [0, 3]);
+
+    drone.crash();
+}
+
+#[test]
+fn test_no_outbound_queue() {
+    let drone = CongestedDrone::spawn(0, OverflowPolicy::DropHead, 0..3);
+
+    // Node 2 takes the first fragment, the others are dropped without ever queuing
+    let dropped: Vec<u64> = (0..2)
+        .map(|_| match drone.event_recv.recv_timeout(TIMEOUT) {
+            Ok(RustasticEvent::OverflowDrop {
+                drone_id: 1,
+                neighbor_id: 2,
+                packet,
+            }) => fragment_index(&packet),
+            event => panic!("unexpected event {event:?}"),
+        })
+        .collect();
+    assert_eq!(dropped, [1, 2]);
+    assert_eq!(drone.delivered(1), [0]);
+
+    // Node 2 is never congested: the next fragment goes straight through
+    drone
+        .packet_send
+        .send(fragment_packet(3, vec![0, 1, 2]))
+        .unwrap();
+    assert_eq!(drone.delivered(1), [3]);
+
+    let event_recv = drone.event_recv.clone();
+    drone.crash();
+    assert!(event_recv.try_recv().is_err());
+}
+
+#[test]
+fn test_nack_upstream() {
+    let drone = CongestedDrone::spawn(1, OverflowPolicy::NackUpstream, 0..4);
+    assert_eq!(drone.overflow_drops(2), [2, 3]);
+
+    // The source is told which fragments were dropped
+    let nacks: Vec<u64> = (0..2)
+        .map(|_| {
+            let packet = drone.prev_recv.recv_timeout(TIMEOUT).unwrap();
+            assert_eq!(packet.routing_header.hops, vec![1, 0]);
+            match packet.pack_type {
+                PacketType::Nack(nack) if nack.nack_type == NackType::Dropped => {
+                    nack.fragment_index
+                }
+                pack_type => panic!("unexpected packet {pack_type:?}"),
+            }
+        })
+        .collect();
+    assert_eq!(nacks, [2, 3]);
+
+    drone.crash();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_full_socket_peer_is_congested() {
+    use std::os::unix::net::UnixDatagram;
+
+    let dir = std::env::temp_dir().join(format!("rustastic-backpressure-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let drone_path = dir.join("1.sock");
+    let next_path = dir.join("2.sock");
+    let _ = std::fs::remove_file(&drone_path);
+    let _ = std::fs::remove_file(&next_path);
+
+    // Node 2 never reads its socket: its receive buffer fills up
+    let _next = UnixDatagram::bind(&next_path).unwrap();
+    let address = |path: &std::path::Path| -> SocketAddress {
+        format!("unix:{}", path.display()).parse().unwrap()
+    };
+    let transport = SocketTransport::bind(&address(&drone_path))
+        .unwrap()
+        .with_neighbor(0, address(&dir.join("0.sock")))
+        .with_neighbor(2, address(&next_path));
+
+    let (controller_send, _controller_recv) = unbounded();
+    let (event_send, event_recv) = unbounded();
+    let mut drone =
+        RustasticDrone::with_transport(1, controller_send, unbounded().1, transport, 0.0)
+            .with_event_send(event_send)
+            .with_outbound_queue(1, OverflowPolicy::DropTail);
+
+    // The drone doesn't block on the full peer: the fragments are queued, then dropped
+    let start = Instant::now();
+    let mut fragment_index = 0;
+    while !event_recv
+        .try_iter()
+        .any(|event| matches!(event, RustasticEvent::OverflowDrop { neighbor_id: 2, .. }))
+    {
+        assert!(start.elapsed() < TIMEOUT, "the peer never got congested");
+        drone.handle_packet(fragment_packet(fragment_index, vec![0, 1, 2]));
+        fragment_index += 1;
+    }
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
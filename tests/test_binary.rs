@@ -0,0 +1,172 @@
+mod common;
+
+use common::FRAGMENT_DSIZE;
+use rustastic_drone::codec;
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, UdpSocket},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+use wg_2024::{
+    network::SourceRoutingHeader,
+    packet::{Fragment, Packet, PacketType},
+};
+
+/// Binds a socket on a free localhost port
+fn neighbor_socket() -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .unwrap();
+    socket
+}
+
+/// A running drone binary, forwarding from `upstream` (Node 0) to `downstream` (Node 2)
+struct RunningDrone {
+    child: Child,
+    upstream: UdpSocket,
+    downstream: UdpSocket,
+    drone_addr: SocketAddr,
+    config_path: PathBuf,
+}
+
+impl RunningDrone {
+    /// Writes the config of Drone 1 and starts the binary with the given stdin
+    fn spawn(name: &str, stdin: Stdio) -> Self {
+        let upstream = neighbor_socket();
+        let downstream = neighbor_socket();
+        // Reserve a free port for the drone
+        let drone_addr = UdpSocket::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let config = format!(
+            r#"
+id = 1
+address = "udp:{drone_addr}"
+pdr = 0.0
+
+[[neighbor]]
+id = 0
+address = "udp:{}"
+
+[[neighbor]]
+id = 2
+address = "udp:{}"
+
+[buffer]
+size = 4
+"#,
+            upstream.local_addr().unwrap(),
+            downstream.local_addr().unwrap()
+        );
+        let config_path =
+            std::env::temp_dir().join(format!("rustastic-{name}-{}.toml", std::process::id()));
+        std::fs::write(&config_path, config).unwrap();
+
+        let child = Command::new(env!("CARGO_BIN_EXE_rustastic-drone"))
+            .arg(&config_path)
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        Self {
+            child,
+            upstream,
+            downstream,
+            drone_addr,
+            config_path,
+        }
+    }
+
+    /// Sends a fragment until the drone is up and forwards it, and returns the forwarded packet
+    fn forward(&self, session_id: u64) -> Packet {
+        let frame = codec::encode(&Packet {
+            pack_type: PacketType::MsgFragment(Fragment {
+                fragment_index: 0,
+                total_n_fragments: 1,
+                length: FRAGMENT_DSIZE as u8,
+                data: [1; FRAGMENT_DSIZE],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![0, 1, 2],
+            },
+            session_id,
+        })
+        .unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut buf = [0; 1024];
+        let len = loop {
+            assert!(
+                Instant::now() < deadline,
+                "the drone never forwarded the fragment"
+            );
+            self.upstream.send_to(&frame, self.drone_addr).unwrap();
+            if let Ok(len) = self.downstream.recv(&mut buf) {
+                break len;
+            }
+        };
+        codec::decode(&buf[..len]).unwrap().0
+    }
+}
+
+impl Drop for RunningDrone {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_file(&self.config_path);
+    }
+}
+
+#[test]
+fn test_binary_forwards_and_reports() {
+    let mut drone = RunningDrone::spawn("reports", Stdio::piped());
+
+    let forwarded = drone.forward(5);
+    assert_eq!(forwarded.routing_header.hop_index, 2);
+
+    let mut stdout = BufReader::new(drone.child.stdout.take().unwrap());
+    let mut line = String::new();
+    stdout.read_line(&mut line).unwrap();
+    let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(event["event"], "PacketSent");
+    assert_eq!(event["packet"]["session_id"], 5);
+
+    writeln!(drone.child.stdin.take().unwrap(), "crash").unwrap();
+    assert!(drone.child.wait().unwrap().success());
+}
+
+/// Returns the CPU time used by a process so far, in clock ticks
+#[cfg(target_os = "linux")]
+fn cpu_ticks(pid: u32) -> u64 {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).unwrap();
+    // The command name may contain spaces: the fields are counted from its closing parenthesis
+    let fields: Vec<&str> = stat[stat.rfind(')').unwrap() + 2..].split(' ').collect();
+    // utime and stime are the 14th and 15th fields, the 12th and 13th after the name
+    fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap()
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_binary_idles_with_closed_stdin() {
+    // Without a control socket, stdin EOF leaves the drone with no command source
+    let drone = RunningDrone::spawn("closed-stdin", Stdio::null());
+    drone.forward(6);
+
+    let before = cpu_ticks(drone.child.id());
+    thread::sleep(Duration::from_secs(1));
+    let used = cpu_ticks(drone.child.id()) - before;
+    // A busy loop would use about 100 ticks per second
+    assert!(used < 20, "the drone used {used} ticks while idle");
+
+    // It keeps forwarding packets
+    assert_eq!(drone.forward(7).session_id, 7);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::FRAGMENT_DSIZE;
+use rustastic_drone::codec::{self, CodecError, CODEC_VERSION, HEADER_LEN, MAX_PAYLOAD_LEN};
+
+use wg_2024::{
+    network::SourceRoutingHeader,
+    packet::{
+        Ack, FloodRequest, FloodResponse, Fragment, Nack, NackType, NodeType, Packet, PacketType,
+    },
+};
+
+fn packets() -> Vec<Packet> {
+    let path_trace = vec![(0, NodeType::Client), (1, NodeType::Drone)];
+    [
+        PacketType::MsgFragment(Fragment {
+            fragment_index: 1,
+            total_n_fragments: 3,
+            length: 5,
+            data: [7; FRAGMENT_DSIZE],
+        }),
+        PacketType::Ack(Ack { fragment_index: 2 }),
+        PacketType::Nack(Nack {
+            fragment_index: 2,
+            nack_type: NackType::ErrorInRouting(4),
+        }),
+        PacketType::FloodRequest(FloodRequest {
+            flood_id: 9,
+            initiator_id: 0,
+            path_trace: path_trace.clone(),
+        }),
+        PacketType::FloodResponse(FloodResponse {
+            flood_id: 9,
+            path_trace,
+        }),
+    ]
+    .into_iter()
+    .map(|pack_type| Packet {
+        pack_type,
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![0, 1, 2],
+        },
+        session_id: 42,
+    })
+    .collect()
+}
+
+#[test]
+fn test_round_trip() {
+    for packet in packets() {
+        let frame = codec::encode(&packet).unwrap();
+        assert_eq!(frame[0], CODEC_VERSION);
+
+        let (decoded, len) = codec::decode(&frame).unwrap();
+        assert_eq!(decoded, packet);
+        assert_eq!(len, frame.len());
+    }
+}
+
+#[test]
+fn test_stream_of_frames() {
+    let packets = packets();
+    let stream: Vec<u8> = packets
+        .iter()
+        .flat_map(|packet| codec::encode(packet).unwrap())
+        .collect();
+
+    let mut offset = 0;
+    for packet in packets {
+        let (decoded, len) = codec::decode(&stream[offset..]).unwrap();
+        assert_eq!(decoded, packet);
+        offset += len;
+    }
+    assert_eq!(offset, stream.len());
+}
+
+#[test]
+fn test_invalid_frames() {
+    let frame = codec::encode(&packets()[0]).unwrap();
+
+    let mut corrupted = frame.clone();
+    *corrupted.last_mut().unwrap() ^= 0xff;
+    assert!(matches!(
+        codec::decode(&corrupted),
+        Err(CodecError::ChecksumMismatch { .. })
+    ));
+
+    let mut future = frame.clone();
+    future[0] = CODEC_VERSION + 1;
+    assert!(matches!(
+        codec::decode(&future),
+        Err(CodecError::UnsupportedVersion(_))
+    ));
+
+    assert!(matches!(
+        codec::decode(&frame[..HEADER_LEN - 1]),
+        Err(CodecError::Truncated { .. })
+    ));
+    assert!(matches!(
+        codec::decode(&frame[..frame.len() - 1]),
+        Err(CodecError::Truncated { .. })
+    ));
+
+    let mut huge = frame;
+    huge[1..5].copy_from_slice(&(MAX_PAYLOAD_LEN as u32 + 1).to_be_bytes());
+    assert!(matches!(codec::decode(&huge), Err(CodecError::TooLong(_))));
+}
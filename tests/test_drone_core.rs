@@ -0,0 +1,86 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID};
+use rustastic_drone::{DroneAction, DroneCore, DroneInput};
+
+use crossbeam_channel::unbounded;
+use wg_2024::{
+    controller::DroneCommand,
+    network::SourceRoutingHeader,
+    packet::{Ack, Nack, NackType, Packet, PacketType},
+};
+
+#[test]
+fn test_fragment_is_forwarded() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2]);
+
+    let actions = core.handle(DroneInput::Packet(fragment_packet(3, vec![0, 1, 2])));
+
+    assert_eq!(actions.len(), 1);
+    assert!(matches!(
+        &actions[0],
+        DroneAction::Send { to: 2, packet } if packet.routing_header.hop_index == 2
+    ));
+    assert_eq!(core.buffer.len(), 1);
+}
+
+#[test]
+fn test_dropped_fragment_is_nacked() {
+    let mut core = DroneCore::new(1, 1.0, [0, 2]);
+
+    let actions = core.handle_packet(fragment_packet(3, vec![0, 1, 2]));
+
+    assert_eq!(actions.len(), 2);
+    assert!(matches!(
+        &actions[0],
+        DroneAction::Send { to: 0, packet } if matches!(
+            packet.pack_type,
+            PacketType::Nack(Nack { fragment_index: 3, nack_type: NackType::Dropped })
+        )
+    ));
+    assert!(matches!(&actions[1], DroneAction::Dropped(packet) if packet.session_id == SESSION_ID));
+    assert!(core.buffer.is_empty());
+}
+
+#[test]
+fn test_unreachable_ack_is_shortcut() {
+    let mut core = DroneCore::new(1, 0.0, [0]);
+    let ack = Packet {
+        pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![0, 1, 2],
+        },
+        session_id: SESSION_ID,
+    };
+
+    let actions = core.handle_packet(ack);
+
+    assert_eq!(actions.len(), 1);
+    assert!(matches!(&actions[0], DroneAction::Shortcut(_)));
+}
+
+#[test]
+fn test_commands() {
+    let mut core = DroneCore::new(1, 0.0, [0]);
+
+    let actions = core.handle_command(DroneCommand::AddSender(2, unbounded().0));
+    assert!(matches!(actions[..], [DroneAction::Connect(2, _)]));
+    // Already connected
+    assert!(core
+        .handle_command(DroneCommand::AddSender(2, unbounded().0))
+        .is_empty());
+
+    // Node 2 is now a neighbor
+    let actions = core.handle_packet(fragment_packet(3, vec![0, 1, 2]));
+    assert!(matches!(actions[..], [DroneAction::Send { to: 2, .. }]));
+
+    let actions = core.handle_command(DroneCommand::RemoveSender(2));
+    assert!(matches!(actions[..], [DroneAction::Disconnect(2)]));
+    assert!(core
+        .handle_command(DroneCommand::SetPacketDropRate(0.5))
+        .is_empty());
+
+    let actions = core.handle(DroneInput::Command(DroneCommand::Crash));
+    assert!(matches!(actions[..], [DroneAction::Crash]));
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use common::fragment_packet;
+use rustastic_drone::{DroneAction, DroneCore, PacketBuffer, RustasticDrone, RustasticEvent};
+
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Nack, NackType, Packet, PacketType},
+};
+
+/// Creates drone 1 with neighbors 0 and 2, returning the controller, event and neighbor 2 receivers
+fn setup_drone(
+    window: usize,
+) -> (
+    RustasticDrone,
+    Receiver<DroneEvent>,
+    Receiver<RustasticEvent>,
+    Receiver<Packet>,
+) {
+    let (controller_send, controller_recv) = unbounded();
+    let (event_send, event_recv) = unbounded();
+    let (neighbor_send_0, _neighbor_recv_0) = unbounded();
+    let (neighbor_send_2, neighbor_recv_2) = unbounded();
+
+    let mut packet_send = HashMap::new();
+    packet_send.insert(0, neighbor_send_0);
+    packet_send.insert(2, neighbor_send_2);
+
+    let drone = RustasticDrone::new(
+        1,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        0.0,
+    )
+    .with_event_send(event_send)
+    .with_duplicate_window(window);
+
+    (drone, controller_recv, event_recv, neighbor_recv_2)
+}
+
+/// A fragment of `session_id`, from node 0 to drone 2
+fn session_fragment(session_id: u64, fragment_index: u64) -> Packet {
+    Packet {
+        session_id,
+        ..fragment_packet(fragment_index, vec![0, 1, 2])
+    }
+}
+
+#[test]
+fn test_duplicate_is_not_forwarded() {
+    let (mut drone, controller_recv, event_recv, neighbor_recv) = setup_drone(8);
+
+    drone.handle_packet(session_fragment(5, 0));
+    drone.handle_packet(session_fragment(5, 0));
+
+    assert_eq!(neighbor_recv.try_iter().count(), 1);
+    assert!(matches!(
+        event_recv.try_recv(),
+        Ok(RustasticEvent::DuplicateFragment {
+            drone_id: 1,
+            source_id: 0,
+            session_id: 5,
+            fragment_index: 0
+        })
+    ));
+    // The duplicate is reported to the simulation controller as dropped
+    let dropped = controller_recv
+        .try_iter()
+        .filter(|event| matches!(event, DroneEvent::PacketDropped(_)))
+        .count();
+    assert_eq!(dropped, 1);
+}
+
+#[test]
+fn test_other_source_is_not_a_duplicate() {
+    let (mut drone, _controller_recv, event_recv, neighbor_recv) = setup_drone(8);
+
+    // Node 3 picked the same session number as Node 0, its fragments reach the drone through Node 0
+    let other_source = Packet {
+        routing_header: SourceRoutingHeader {
+            hop_index: 2,
+            hops: vec![3, 0, 1, 2],
+        },
+        ..session_fragment(5, 0)
+    };
+    drone.handle_packet(session_fragment(5, 0));
+    drone.handle_packet(other_source);
+
+    assert_eq!(neighbor_recv.try_iter().count(), 2);
+    assert!(event_recv.try_recv().is_err());
+}
+
+#[test]
+fn test_duplicates_are_forwarded_by_default() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2]);
+
+    for _ in 0..2 {
+        let actions = core.handle_packet(session_fragment(5, 0));
+        assert!(matches!(&actions[..], [DroneAction::Send { to: 2, .. }]));
+    }
+}
+
+#[test]
+fn test_other_session_is_not_a_duplicate() {
+    let (mut drone, _controller_recv, event_recv, neighbor_recv) = setup_drone(8);
+
+    drone.handle_packet(session_fragment(5, 0));
+    drone.handle_packet(session_fragment(6, 0));
+
+    assert_eq!(neighbor_recv.try_iter().count(), 2);
+    assert!(event_recv.try_recv().is_err());
+}
+
+/// A Nack for fragment 0 of session 5, sent back by drone 2
+fn nack_packet(nack_type: NackType) -> Packet {
+    Packet {
+        pack_type: PacketType::Nack(Nack {
+            fragment_index: 0,
+            nack_type,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![2, 1, 0],
+        },
+        session_id: 5,
+    }
+}
+
+#[test]
+fn test_retransmission_after_nack_is_forwarded() {
+    let (mut drone, _controller_recv, event_recv, neighbor_recv) = setup_drone(8);
+
+    drone.handle_packet(session_fragment(5, 0));
+    // The route is broken after drone 2, so the Nack goes back to the source
+    drone.handle_packet(nack_packet(NackType::ErrorInRouting(3)));
+    drone.handle_packet(session_fragment(5, 0));
+
+    // The original and the retransmission from the source
+    assert_eq!(neighbor_recv.try_iter().count(), 2);
+    assert!(event_recv.try_recv().is_err());
+}
+
+#[test]
+fn test_retransmission_after_local_recovery_is_a_duplicate() {
+    let (mut drone, _controller_recv, event_recv, neighbor_recv) = setup_drone(8);
+
+    drone.handle_packet(session_fragment(5, 0));
+    // Drone 2 dropped the fragment, the drone resends it from its buffer
+    drone.handle_packet(nack_packet(NackType::Dropped));
+    drone.handle_packet(session_fragment(5, 0));
+
+    // The original and the copy resent from the buffer
+    assert_eq!(neighbor_recv.try_iter().count(), 2);
+    assert!(matches!(
+        event_recv.try_recv(),
+        Ok(RustasticEvent::DuplicateFragment { .. })
+    ));
+}
+
+#[test]
+fn test_window_eviction_and_disabled_filter() {
+    let (mut drone, _controller_recv, _event_recv, neighbor_recv) = setup_drone(1);
+
+    drone.handle_packet(session_fragment(5, 0));
+    drone.handle_packet(session_fragment(5, 1));
+    // Fragment 0 was forgotten to make space for fragment 1
+    drone.handle_packet(session_fragment(5, 0));
+    assert_eq!(neighbor_recv.try_iter().count(), 3);
+
+    let (mut drone, _controller_recv, _event_recv, neighbor_recv) = setup_drone(0);
+    drone.handle_packet(session_fragment(5, 0));
+    drone.handle_packet(session_fragment(5, 0));
+    assert_eq!(neighbor_recv.try_iter().count(), 2);
+}
+
+#[test]
+fn test_buffer_reinsert_same_key() {
+    let mut buffer = PacketBuffer::new(2);
+
+    buffer.add_fragment(5, 0, session_fragment(5, 0));
+    buffer.add_fragment(5, 1, session_fragment(5, 1));
+    // Reinserting a key must not evict another fragment, nor leave a stale key behind
+    buffer.add_fragment(5, 0, session_fragment(5, 0));
+    assert_eq!(buffer.len(), 2);
+
+    // Fragment 1 is now the oldest one
+    buffer.add_fragment(5, 2, session_fragment(5, 2));
+    assert!(buffer.get_fragment(5, 1).is_none());
+    assert!(buffer.get_fragment(5, 0).is_some());
+    assert!(buffer.get_fragment(5, 2).is_some());
+    assert!(buffer.is_empty());
+}
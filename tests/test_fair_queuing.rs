@@ -0,0 +1,126 @@
+mod common;
+
+use common::{fragment_packet, TIMEOUT};
+use rustastic_drone::{FairQueuing, FlowKey, RustasticDrone};
+
+use crossbeam_channel::unbounded;
+use std::{collections::HashMap, thread};
+use wg_2024::{
+    controller::DroneCommand,
+    drone::Drone,
+    packet::{Packet, PacketType},
+};
+
+/// Queues `input` before Drone 1 starts, and returns the sessions of the packets it forwards, in order,
+/// with the drone after its crash
+fn forwarded_sessions(fair_queuing: FairQueuing, input: Vec<Packet>) -> (Vec<u64>, RustasticDrone) {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, _prev_recv) = unbounded();
+    let (other_send, _other_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    let len = input.len();
+    for packet in input {
+        packet_send.send(packet).unwrap();
+    }
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(0, prev_send), (3, other_send), (2, next_send)]),
+        0.0,
+    )
+    .with_fair_queuing(fair_queuing);
+    let drone_thread = thread::spawn(move || {
+        drone.run();
+        drone
+    });
+
+    let sessions = (0..len)
+        .map(|_| next_recv.recv_timeout(TIMEOUT).unwrap().session_id)
+        .collect();
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    (sessions, drone_thread.join().unwrap())
+}
+
+/// A fragment of `length` bytes of `session_id`, sent by `source` to Drone 2 through Drone 1
+fn fragment(session_id: u64, source: u8, length: u8, fragment_index: u64) -> Packet {
+    let mut packet = Packet {
+        session_id,
+        ..fragment_packet(fragment_index, vec![source, 1, 2])
+    };
+    if let PacketType::MsgFragment(fragment) = &mut packet.pack_type {
+        fragment.length = length;
+    }
+    packet
+}
+
+#[test]
+fn test_heavy_session_does_not_monopolize_the_drone() {
+    let mut input: Vec<Packet> = (0..6).map(|index| fragment(1, 0, 128, index)).collect();
+    input.extend((0..3).map(|index| fragment(2, 0, 128, index)));
+
+    // Without fair queuing the light session waits for the heavy one
+    let (sessions, drone) = forwarded_sessions(FairQueuing::Off, input.clone());
+    assert_eq!(sessions, [1, 1, 1, 1, 1, 1, 2, 2, 2]);
+    assert!(drone.flow_stats().is_empty());
+
+    // With it, the sessions take turns
+    let (sessions, _drone) = forwarded_sessions(FairQueuing::PerSession, input);
+    assert_eq!(sessions, [1, 2, 1, 2, 1, 2, 1, 1, 1]);
+}
+
+#[test]
+fn test_flows_share_bytes_not_fragments() {
+    // Session 2 sends half fragments: two of them weigh as much as a full one
+    let mut input: Vec<Packet> = (0..4).map(|index| fragment(1, 0, 128, index)).collect();
+    input.extend((0..4).map(|index| fragment(2, 0, 64, index)));
+
+    let (sessions, _drone) = forwarded_sessions(FairQueuing::PerSession, input);
+    assert_eq!(sessions, [1, 2, 2, 1, 2, 2, 1, 1]);
+}
+
+#[test]
+fn test_flows_by_source() {
+    // Sessions 1 and 2 come from Node 0, session 3 from Node 3
+    let mut input: Vec<Packet> = (0..2).map(|index| fragment(1, 0, 128, index)).collect();
+    input.extend((0..2).map(|index| fragment(2, 0, 128, index)));
+    input.extend((0..2).map(|index| fragment(3, 3, 128, index)));
+
+    let (sessions, drone) = forwarded_sessions(FairQueuing::PerSource, input);
+    assert_eq!(sessions, [1, 3, 1, 3, 2, 2]);
+
+    let stats = drone.flow_stats();
+    assert_eq!(
+        stats.iter().map(|flow| flow.key).collect::<Vec<_>>(),
+        [FlowKey::Source(0), FlowKey::Source(3)]
+    );
+    assert_eq!(stats[0].served, 4);
+    assert_eq!(stats[0].served_bytes, 512);
+    assert_eq!(stats[0].queued, 0);
+    assert!((stats[0].share - 2.0 / 3.0).abs() < 1e-9);
+    assert_eq!(stats[1].served, 2);
+    assert!((stats[1].share - 1.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_idle_flows_are_forgotten() {
+    // 300 sessions of a single fragment each
+    let input: Vec<Packet> = (0..300)
+        .map(|session_id| fragment(session_id, 0, 128, 0))
+        .collect();
+
+    let (sessions, drone) = forwarded_sessions(FairQueuing::PerSession, input);
+    assert_eq!(sessions.len(), 300);
+
+    // Only the most recently served flows are still reported
+    let stats = drone.flow_stats();
+    assert_eq!(stats.len(), 256);
+    assert_eq!(stats[0].key, FlowKey::Session(44));
+    assert!((stats.iter().map(|flow| flow.share).sum::<f64>() - 1.0).abs() < 1e-9);
+}
@@ -0,0 +1,230 @@
+use rustastic_drone::{DroneAction, DroneCore, ProtocolViolation, RustasticEvent};
+
+use crossbeam_channel::unbounded;
+use wg_2024::{
+    controller::DroneCommand,
+    network::SourceRoutingHeader,
+    packet::{FloodRequest, FloodResponse, NodeType, Packet, PacketType},
+};
+
+fn flood_request(flood_id: u64, path_trace: Vec<(u8, NodeType)>) -> Packet {
+    Packet {
+        pack_type: PacketType::FloodRequest(FloodRequest {
+            flood_id,
+            initiator_id: path_trace[0].0,
+            path_trace,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![],
+        },
+        session_id: 0,
+    }
+}
+
+/// Returns the neighbors a `FloodRequest` is forwarded to
+fn forwarded_to(actions: &[DroneAction]) -> Vec<u8> {
+    actions
+        .iter()
+        .filter_map(|action| match action {
+            DroneAction::Send { to, packet }
+                if matches!(packet.pack_type, PacketType::FloodRequest(_)) =>
+            {
+                Some(*to)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_sole_neighbor_that_is_not_the_sender() {
+    // Drone 1 only knows Drone 2, but the request comes from Client 0: the path trace is inconsistent
+    let mut core = DroneCore::new(1, 0.0, [2]);
+
+    let actions = core.handle_packet(flood_request(1, vec![(0, NodeType::Client)]));
+
+    assert!(forwarded_to(&actions).is_empty());
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Event(RustasticEvent::ProtocolViolation {
+            violation: ProtocolViolation::UnknownPreviousHop { node_id: 0 },
+            ..
+        })]
+    ));
+}
+
+#[test]
+fn test_sender_removed_during_the_flood() {
+    let (sender, _receiver) = unbounded();
+    let mut core = DroneCore::new(1, 0.0, [0, 2, 3]);
+
+    // Client 0 is removed once its request has been forwarded
+    let actions = core.handle_packet(flood_request(1, vec![(0, NodeType::Client)]));
+    assert_eq!(forwarded_to(&actions), [2, 3]);
+    core.handle_command(DroneCommand::RemoveSender(0));
+
+    // The response to Client 0 can't be sent: it is shortcut to the simulation controller
+    let response = Packet {
+        pack_type: PacketType::FloodResponse(FloodResponse {
+            flood_id: 1,
+            path_trace: vec![
+                (0, NodeType::Client),
+                (1, NodeType::Drone),
+                (2, NodeType::Drone),
+            ],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![2, 1, 0],
+        },
+        session_id: 0,
+    };
+    let actions = core.handle_packet(response.clone());
+    assert_eq!(actions.len(), 1);
+    assert!(matches!(&actions[0], DroneAction::Shortcut(_)));
+
+    // Once Client 0 is back, the responses reach it again
+    core.handle_command(DroneCommand::AddSender(0, sender));
+    let actions = core.handle_packet(response);
+    assert!(matches!(&actions[..], [DroneAction::Send { to: 0, .. }]));
+}
+
+#[test]
+fn test_request_from_a_removed_sender() {
+    // The only neighbor was the sender, and it is gone before its request is handled
+    let mut core = DroneCore::new(1, 0.0, [0]);
+    core.handle_command(DroneCommand::RemoveSender(0));
+
+    let actions = core.handle_packet(flood_request(1, vec![(0, NodeType::Client)]));
+
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Event(RustasticEvent::ProtocolViolation {
+            violation: ProtocolViolation::UnknownPreviousHop { node_id: 0 },
+            ..
+        })]
+    ));
+}
+
+#[test]
+fn test_only_the_sender_is_left() {
+    let mut core = DroneCore::new(1, 0.0, [2, 3]);
+    core.handle_command(DroneCommand::RemoveSender(3));
+
+    // Drone 2 forwarded the flood of Client 0: Drone 1 answers it
+    let actions = core.handle_packet(flood_request(
+        1,
+        vec![(0, NodeType::Client), (2, NodeType::Drone)],
+    ));
+
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Send { to: 2, packet }] if packet.routing_header.hops == vec![1, 2, 0]
+            && matches!(&packet.pack_type, PacketType::FloodResponse(response)
+                if response.path_trace.last() == Some(&(1, NodeType::Drone)))
+    ));
+}
+
+#[test]
+fn test_max_flood_hops() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2, 3]).with_max_flood_hops(2);
+
+    // One hop so far: the flood goes on
+    let actions = core.handle_packet(flood_request(1, vec![(0, NodeType::Client)]));
+    assert_eq!(forwarded_to(&actions), [2, 3]);
+
+    // Two hops so far: the flood is answered
+    let actions = core.handle_packet(flood_request(
+        1,
+        vec![(9, NodeType::Client), (3, NodeType::Drone)],
+    ));
+    assert!(forwarded_to(&actions).is_empty());
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Send { to: 3, packet }] if matches!(packet.pack_type, PacketType::FloodResponse(_))
+    ));
+
+    let stats = core.flood_stats();
+    assert_eq!(stats.forwarded, 1);
+    assert_eq!(stats.hop_limited, 1);
+    assert_eq!(stats.duplicates, 0);
+}
+
+/// Returns the `ProtocolViolation` reported for a request, if it was refused
+fn violation(
+    core: &mut DroneCore,
+    initiator_id: u8,
+    path_trace: Vec<(u8, NodeType)>,
+) -> Option<ProtocolViolation> {
+    let mut packet = flood_request(1, vec![(initiator_id, NodeType::Client)]);
+    if let PacketType::FloodRequest(request) = &mut packet.pack_type {
+        request.path_trace = path_trace;
+    }
+
+    match &core.handle_packet(packet)[..] {
+        [DroneAction::Event(RustasticEvent::ProtocolViolation {
+            drone_id: 1,
+            violation,
+            ..
+        })] => Some(*violation),
+        actions => {
+            assert!(!forwarded_to(actions).is_empty());
+            None
+        }
+    }
+}
+
+#[test]
+fn test_inconsistent_path_traces_are_refused() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2, 3]);
+    let client = |id| (id, NodeType::Client);
+    let drone = |id| (id, NodeType::Drone);
+
+    assert_eq!(
+        violation(&mut core, 0, vec![]),
+        Some(ProtocolViolation::EmptyPathTrace)
+    );
+    assert_eq!(
+        violation(&mut core, 0, vec![client(9), drone(2)]),
+        Some(ProtocolViolation::InitiatorNotFirst {
+            initiator_id: 0,
+            first: 9
+        })
+    );
+    assert_eq!(
+        violation(&mut core, 0, vec![client(0), drone(2), drone(4), drone(2)]),
+        Some(ProtocolViolation::DuplicateInPathTrace { node_id: 2 })
+    );
+    assert_eq!(
+        violation(&mut core, 0, vec![client(0), drone(5)]),
+        Some(ProtocolViolation::UnknownPreviousHop { node_id: 5 })
+    );
+
+    // A refused request doesn't count as received: a consistent copy is still forwarded
+    assert_eq!(violation(&mut core, 0, vec![client(0), drone(2)]), None);
+}
+
+#[test]
+fn test_flood_looping_back_is_answered() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2, 3]);
+
+    // The flood of Client 0 went through Drone 1, then came back from Drone 2: it is answered without the loop
+    let actions = core.handle_packet(flood_request(
+        1,
+        vec![
+            (0, NodeType::Client),
+            (1, NodeType::Drone),
+            (2, NodeType::Drone),
+        ],
+    ));
+
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Send { to: 0, packet }] if packet.routing_header.hops == vec![1, 0]
+            && matches!(&packet.pack_type, PacketType::FloodResponse(response)
+                if response.path_trace.len() == 4
+                    && response.path_trace.last() == Some(&(1, NodeType::Drone)))
+    ));
+    assert_eq!(core.flood_stats().duplicates, 1);
+}
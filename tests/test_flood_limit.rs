@@ -0,0 +1,129 @@
+use rustastic_drone::{DroneAction, DroneCore, FloodLimitAction, FloodRateLimit, RustasticEvent};
+
+use std::time::Duration;
+use wg_2024::{
+    network::SourceRoutingHeader,
+    packet::{FloodRequest, NodeType, Packet, PacketType},
+};
+
+fn flood_request(flood_id: u64, initiator_id: u8) -> Packet {
+    Packet {
+        pack_type: PacketType::FloodRequest(FloodRequest {
+            flood_id,
+            initiator_id,
+            path_trace: vec![(initiator_id, NodeType::Client)],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![],
+        },
+        session_id: 0,
+    }
+}
+
+/// Drone 1, between Client 0, Client 5 and Drone 2
+fn limited_core(limit: FloodRateLimit) -> DroneCore {
+    DroneCore::new(1, 0.0, [0, 2, 5]).with_flood_rate_limit(limit)
+}
+
+fn is_forwarded(actions: &[DroneAction]) -> bool {
+    actions.iter().any(|action| {
+        matches!(
+            action,
+            DroneAction::Send { to: 2, packet } if matches!(packet.pack_type, PacketType::FloodRequest(_))
+        )
+    })
+}
+
+fn is_answered(actions: &[DroneAction], initiator_id: u8) -> bool {
+    actions.iter().any(|action| {
+        matches!(
+            action,
+            DroneAction::Send { to, packet }
+                if *to == initiator_id && matches!(packet.pack_type, PacketType::FloodResponse(_))
+        )
+    })
+}
+
+fn limited_initiator(actions: &[DroneAction]) -> Option<u8> {
+    actions.iter().find_map(|action| match action {
+        DroneAction::Event(RustasticEvent::FloodRateLimited {
+            drone_id: 1,
+            initiator_id,
+            ..
+        }) => Some(*initiator_id),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_floods_over_the_rate_are_answered() {
+    let mut core = limited_core(FloodRateLimit::per_second(1.0).with_burst(2.0));
+
+    // The burst is forwarded, the third flood is answered and reported
+    assert!(is_forwarded(&core.handle_packet(flood_request(1, 0))));
+    assert!(is_forwarded(&core.handle_packet(flood_request(2, 0))));
+    let actions = core.handle_packet(flood_request(3, 0));
+    assert!(!is_forwarded(&actions));
+    assert!(is_answered(&actions, 0));
+    assert_eq!(limited_initiator(&actions), Some(0));
+
+    // Another initiator has its own bucket
+    assert!(is_forwarded(&core.handle_packet(flood_request(1, 5))));
+
+    // A second later, the bucket holds a token again
+    core.set_clock(Duration::from_secs(1));
+    assert!(is_forwarded(&core.handle_packet(flood_request(4, 0))));
+    assert!(!is_forwarded(&core.handle_packet(flood_request(5, 0))));
+}
+
+#[test]
+fn test_floods_over_the_rate_are_dropped() {
+    let mut core = limited_core(
+        FloodRateLimit::per_second(10.0)
+            .with_burst(1.0)
+            .with_action(FloodLimitAction::Drop),
+    );
+
+    assert!(is_forwarded(&core.handle_packet(flood_request(1, 0))));
+    let actions = core.handle_packet(flood_request(2, 0));
+    assert_eq!(actions.len(), 1);
+    assert_eq!(limited_initiator(&actions), Some(0));
+
+    core.set_clock(Duration::from_millis(100));
+    assert!(is_forwarded(&core.handle_packet(flood_request(3, 0))));
+}
+
+#[test]
+fn test_known_floods_take_no_token() {
+    let mut core = limited_core(FloodRateLimit::per_second(1.0).with_burst(1.0));
+
+    assert!(is_forwarded(&core.handle_packet(flood_request(1, 0))));
+
+    // The same flood coming back is answered as usual, without being reported
+    let actions = core.handle_packet(flood_request(1, 0));
+    assert!(is_answered(&actions, 0));
+    assert_eq!(limited_initiator(&actions), None);
+}
+
+#[test]
+fn test_dropped_flood_is_not_received() {
+    let mut core = limited_core(
+        FloodRateLimit::per_second(10.0)
+            .with_burst(1.0)
+            .with_action(FloodLimitAction::Drop),
+    );
+
+    assert!(is_forwarded(&core.handle_packet(flood_request(1, 0))));
+    assert_eq!(
+        limited_initiator(&core.handle_packet(flood_request(2, 0))),
+        Some(0)
+    );
+
+    // Once a token is back, another copy of the dropped flood is forwarded as a new one
+    core.set_clock(Duration::from_millis(100));
+    let actions = core.handle_packet(flood_request(2, 0));
+    assert!(is_forwarded(&actions));
+    assert!(!is_answered(&actions, 0));
+    assert_eq!(core.flood_stats().duplicates, 0);
+}
@@ -134,7 +134,7 @@ fn test_handle_nack() {
 
     {
         let mut drone = drone.lock().unwrap();
-        drone.buffer.add_fragment(
+        drone.buffer_mut().add_fragment(
             123,
             fragment.fragment_index,
             Packet {
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{fragment_packet, TIMEOUT};
+use rustastic_drone::{parse_config, LinkDelay, RustasticDrone, Simulation, SimulationEvent};
+
+use crossbeam_channel::unbounded;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    collections::HashMap,
+    thread,
+    time::{Duration, Instant},
+};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    drone::Drone,
+    packet::{NackType, Packet, PacketType},
+};
+
+#[test]
+fn test_delay_samples_stay_in_their_distribution() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let millis = Duration::from_millis;
+
+    assert_eq!(LinkDelay::None.sample(&mut rng), Duration::ZERO);
+    assert_eq!(LinkDelay::Fixed(millis(7)).sample(&mut rng), millis(7));
+
+    let uniform = LinkDelay::Uniform {
+        min: millis(10),
+        max: millis(20),
+    };
+    let normal = LinkDelay::Normal {
+        mean: millis(5),
+        std_dev: millis(10),
+    };
+    let mut normal_samples = Vec::new();
+    for _ in 0..1000 {
+        let delay = uniform.sample(&mut rng);
+        assert!(millis(10) <= delay && delay <= millis(20));
+        normal_samples.push(normal.sample(&mut rng));
+    }
+
+    // Negative delays are cut to zero, the others are spread around the mean
+    assert!(normal_samples.contains(&Duration::ZERO));
+    assert!(normal_samples.iter().any(|delay| *delay > millis(15)));
+}
+
+#[test]
+fn test_drone_delays_packets_per_link() {
+    let (controller_send, controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (slow_send, slow_recv) = unbounded();
+    let (fast_send, fast_recv) = unbounded();
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(2, slow_send), (3, fast_send)]),
+        0.0,
+    )
+    .with_link_delay(2, LinkDelay::Fixed(Duration::from_millis(200)));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    let start = Instant::now();
+    packet_send.send(fragment_packet(0, vec![0, 1, 2])).unwrap();
+    packet_send.send(fragment_packet(1, vec![0, 1, 3])).unwrap();
+
+    // The packet for Drone 3 overtakes the one for Drone 2
+    assert!(fast_recv.recv_timeout(TIMEOUT).is_ok());
+    assert!(start.elapsed() < Duration::from_millis(200));
+    assert!(slow_recv.recv_timeout(TIMEOUT).is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    // `PacketSent` is reported when the packet actually leaves
+    let sent: Vec<u64> = controller_recv
+        .try_iter()
+        .map(|event| match event {
+            DroneEvent::PacketSent(Packet {
+                pack_type: PacketType::MsgFragment(fragment),
+                ..
+            }) => fragment.fragment_index,
+            event => panic!("unexpected event {event:?}"),
+        })
+        .collect();
+    assert_eq!(sent, vec![1, 0]);
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+}
+
+#[test]
+fn test_crash_drops_the_delayed_packets() {
+    let (controller_send, controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, prev_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(0, prev_send), (2, next_send)]),
+        0.0,
+    )
+    .with_default_delay(LinkDelay::Fixed(Duration::from_secs(60)));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    packet_send.send(fragment_packet(0, vec![0, 1, 2])).unwrap();
+    thread::sleep(Duration::from_millis(100));
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+
+    // The fragment doesn't skip its delay: it is dropped, and NACKed right away
+    assert!(next_recv.try_recv().is_err());
+    assert!(matches!(
+        prev_recv.try_recv().unwrap().pack_type,
+        PacketType::Nack(nack) if nack.nack_type == NackType::Dropped
+    ));
+    assert!(controller_recv
+        .try_iter()
+        .any(|event| matches!(event, DroneEvent::PacketDropped(_))));
+}
+
+#[test]
+fn test_simulation_draws_link_delays() {
+    let config = parse_config(
+        r"
+        [[drone]]
+        id = 11
+        connected_node_ids = [1, 21]
+        pdr = 0.0
+
+        [[client]]
+        id = 1
+        connected_drone_ids = [11]
+
+        [[server]]
+        id = 21
+        connected_drone_ids = [11]
+        ",
+    )
+    .unwrap();
+    let mut simulation = Simulation::new(&config, 3).unwrap();
+    simulation.set_link_delay(
+        11,
+        21,
+        LinkDelay::Uniform {
+            min: Duration::from_millis(10),
+            max: Duration::from_millis(30),
+        },
+    );
+
+    for fragment_index in 0..20 {
+        simulation.send_packet(11, fragment_packet(fragment_index, vec![1, 11, 21]));
+    }
+    simulation.run();
+
+    let arrivals: Vec<Duration> = simulation
+        .events()
+        .iter()
+        .filter(|event| matches!(event, SimulationEvent::Delivered { .. }))
+        .map(SimulationEvent::time)
+        .collect();
+    assert_eq!(arrivals.len(), 20);
+    assert!(arrivals
+        .iter()
+        .all(|time| Duration::from_millis(10) <= *time && *time <= Duration::from_millis(30)));
+    assert!(arrivals.iter().any(|time| *time != arrivals[0]));
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use common::{fragment_packet, TIMEOUT};
+use rustastic_drone::{DroneAction, DroneCore, LivenessPolicy, RustasticDrone, RustasticEvent};
+
+use crossbeam_channel::unbounded;
+use std::{collections::HashMap, thread};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    drone::Drone,
+    packet::{NackType, Packet, PacketType},
+};
+
+fn policy(max_failures: u32, restore_on_add_sender: bool) -> LivenessPolicy {
+    LivenessPolicy {
+        max_failures,
+        restore_on_add_sender,
+    }
+}
+
+#[test]
+fn test_neighbor_dead_after_failures_in_a_row() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2]).with_liveness(policy(2, true));
+
+    // A successful send starts the count over
+    assert!(core.report_send_failure(2).is_empty());
+    core.report_send_success(2);
+    assert!(core.report_send_failure(2).is_empty());
+
+    let actions = core.report_send_failure(2);
+    assert!(matches!(
+        &actions[..],
+        [
+            DroneAction::Disconnect(2),
+            DroneAction::Event(RustasticEvent::NeighborDead {
+                drone_id: 1,
+                neighbor_id: 2,
+                failures: 2
+            })
+        ]
+    ));
+    assert_eq!(core.dead_neighbors(), [2]);
+
+    // The drone doesn't route to the dead neighbor anymore
+    let actions = core.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+    assert!(matches!(
+        &actions[..],
+        [DroneAction::Send { to: 0, packet }] if matches!(&packet.pack_type,
+            PacketType::Nack(nack) if nack.nack_type == NackType::ErrorInRouting(2))
+    ));
+
+    // Failures of a node that is not a neighbor are ignored
+    assert!(core.report_send_failure(2).is_empty());
+    assert!(core.report_send_failure(5).is_empty());
+}
+
+#[test]
+fn test_dead_neighbor_restored_by_add_sender() {
+    let (sender, _receiver) = unbounded();
+    let mut core = DroneCore::new(1, 0.0, [0, 2]).with_liveness(policy(1, true));
+    core.report_send_failure(2);
+
+    let actions = core.handle_command(DroneCommand::AddSender(2, sender));
+    assert!(matches!(
+        &actions[..],
+        [
+            DroneAction::Event(RustasticEvent::NeighborRestored {
+                drone_id: 1,
+                neighbor_id: 2
+            }),
+            DroneAction::Connect(2, _)
+        ]
+    ));
+    assert!(core.dead_neighbors().is_empty());
+
+    let actions = core.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+    assert!(matches!(&actions[..], [DroneAction::Send { to: 2, .. }]));
+}
+
+#[test]
+fn test_dead_neighbor_not_restored() {
+    let (sender, _receiver) = unbounded();
+    let mut core = DroneCore::new(1, 0.0, [0, 2]).with_liveness(policy(1, false));
+    core.report_send_failure(2);
+
+    assert!(core
+        .handle_command(DroneCommand::AddSender(2, sender.clone()))
+        .is_empty());
+    assert_eq!(core.dead_neighbors(), [2]);
+
+    // Once the simulation controller has removed it, the neighbor can be added again
+    assert!(core
+        .handle_command(DroneCommand::RemoveSender(2))
+        .is_empty());
+    let actions = core.handle_command(DroneCommand::AddSender(2, sender));
+    assert!(matches!(&actions[..], [DroneAction::Connect(2, _)]));
+}
+
+#[test]
+fn test_liveness_disabled() {
+    let mut core = DroneCore::new(1, 0.0, [0, 2]).with_liveness(policy(0, true));
+
+    for _ in 0..10 {
+        assert!(core.report_send_failure(2).is_empty());
+    }
+    assert!(core.dead_neighbors().is_empty());
+}
+
+#[test]
+fn test_drone_gives_up_on_a_dropped_receiver() {
+    let (controller_send, controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (event_send, event_recv) = unbounded();
+    let (prev_send, prev_recv) = unbounded();
+    let (next_send, next_recv) = unbounded::<Packet>();
+    drop(next_recv);
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(0, prev_send), (2, next_send)]),
+        0.0,
+    )
+    .with_event_send(event_send)
+    .with_liveness(policy(3, true));
+    let thread = thread::spawn(move || drone.run());
+
+    // The first fragments are NACKed to their source, until Drone 2 is declared dead
+    for fragment_index in 0..3 {
+        packet_send
+            .send(fragment_packet(fragment_index, vec![0, 1, 2]))
+            .unwrap();
+    }
+    assert!(matches!(
+        event_recv.recv_timeout(TIMEOUT),
+        Ok(RustasticEvent::NeighborDead {
+            drone_id: 1,
+            neighbor_id: 2,
+            failures: 3
+        })
+    ));
+
+    // The next one is NACKed by the core, without trying the dead link
+    packet_send.send(fragment_packet(3, vec![0, 1, 2])).unwrap();
+    for _ in 0..4 {
+        let nack = prev_recv.recv_timeout(TIMEOUT).unwrap();
+        assert!(matches!(
+            nack.pack_type,
+            PacketType::Nack(nack) if nack.nack_type == NackType::ErrorInRouting(2)
+        ));
+    }
+    assert!(!controller_recv
+        .try_iter()
+        .any(|event| matches!(event, DroneEvent::ControllerShortcut(_))));
+
+    // A new channel brings Drone 2 back
+    let (next_send, next_recv) = unbounded();
+    command_send
+        .send(DroneCommand::AddSender(2, next_send))
+        .unwrap();
+    assert!(matches!(
+        event_recv.recv_timeout(TIMEOUT),
+        Ok(RustasticEvent::NeighborRestored {
+            drone_id: 1,
+            neighbor_id: 2
+        })
+    ));
+    packet_send.send(fragment_packet(4, vec![0, 1, 2])).unwrap();
+    assert!(next_recv.recv_timeout(TIMEOUT).is_ok());
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    thread.join().unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_restarting_socket_neighbor_is_not_dead() {
+    use rustastic_drone::{SocketAddress, SocketTransport};
+    use std::os::unix::net::UnixDatagram;
+
+    let dir = std::env::temp_dir().join(format!("rustastic-liveness-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let drone_path = dir.join("1.sock");
+    let next_path = dir.join("2.sock");
+    let _ = std::fs::remove_file(&drone_path);
+    let _ = std::fs::remove_file(&next_path);
+
+    let address = |path: &std::path::Path| -> SocketAddress {
+        format!("unix:{}", path.display()).parse().unwrap()
+    };
+    let transport = SocketTransport::bind(&address(&drone_path))
+        .unwrap()
+        .with_neighbor(0, address(&dir.join("0.sock")))
+        .with_neighbor(2, address(&next_path));
+    let (controller_send, _controller_recv) = unbounded();
+    let mut drone =
+        RustasticDrone::with_transport(1, controller_send, unbounded().1, transport, 0.0)
+            .with_liveness(policy(2, true));
+
+    // Node 2 is down: its socket doesn't exist yet, the sends fail
+    for fragment_index in 0..4 {
+        drone.handle_packet(fragment_packet(fragment_index, vec![0, 1, 2]));
+    }
+    assert!(drone.dead_neighbors().is_empty());
+
+    // Once it is back at the same address, it is sent to again
+    let next = UnixDatagram::bind(&next_path).unwrap();
+    next.set_read_timeout(Some(TIMEOUT)).unwrap();
+    drone.handle_packet(fragment_packet(4, vec![0, 1, 2]));
+    let mut buf = [0; 256];
+    assert!(next.recv(&mut buf).unwrap() > 0);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::fragment_packet;
+use rustastic_drone::{ProtocolViolation, RustasticDrone, RustasticEvent};
+
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Ack, Packet, PacketType},
+};
+
+/// Creates drone 1 with neighbors 0 and 2, returning the controller, event and neighbor 2 receivers
+fn setup_drone() -> (
+    RustasticDrone,
+    Receiver<DroneEvent>,
+    Receiver<RustasticEvent>,
+    Receiver<Packet>,
+) {
+    let (controller_send, controller_recv) = unbounded();
+    let (event_send, event_recv) = unbounded();
+    let (neighbor_send_0, _neighbor_recv_0) = unbounded();
+    let (neighbor_send_2, neighbor_recv_2) = unbounded();
+
+    let mut packet_send = HashMap::new();
+    packet_send.insert(0, neighbor_send_0);
+    packet_send.insert(2, neighbor_send_2);
+
+    let drone = RustasticDrone::new(
+        1,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        0.0,
+    )
+    .with_event_send(event_send);
+
+    (drone, controller_recv, event_recv, neighbor_recv_2)
+}
+
+/// A fragment routed along `hops`, arriving at `hops[hop_index]`
+fn routed_fragment(hops: Vec<u8>, hop_index: usize) -> Packet {
+    let mut packet = fragment_packet(0, hops);
+    packet.routing_header.hop_index = hop_index;
+    packet
+}
+
+#[test]
+fn test_empty_route() {
+    let (mut drone, controller_recv, event_recv, neighbor_recv) = setup_drone();
+
+    drone.handle_packet(routed_fragment(vec![], 0));
+
+    match event_recv.try_recv() {
+        Ok(RustasticEvent::ProtocolViolation { violation, .. }) => {
+            assert_eq!(violation, ProtocolViolation::EmptyRoute);
+        }
+        other => panic!("Expected a ProtocolViolation, got {other:?}"),
+    }
+    assert!(neighbor_recv.try_recv().is_err());
+    assert!(controller_recv.try_recv().is_err());
+}
+
+#[test]
+fn test_hop_index_out_of_range() {
+    let (mut drone, controller_recv, event_recv, neighbor_recv) = setup_drone();
+
+    let ack = Packet {
+        pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 5,
+            hops: vec![0, 1, 2],
+        },
+        session_id: 7,
+    };
+    drone.handle_packet(ack);
+
+    match event_recv.try_recv() {
+        Ok(RustasticEvent::ProtocolViolation { violation, .. }) => assert_eq!(
+            violation,
+            ProtocolViolation::HopIndexOutOfRange {
+                hop_index: 5,
+                hops_len: 3
+            }
+        ),
+        other => panic!("Expected a ProtocolViolation, got {other:?}"),
+    }
+    assert!(matches!(
+        controller_recv.try_recv(),
+        Ok(DroneEvent::ControllerShortcut(_))
+    ));
+    assert!(neighbor_recv.try_recv().is_err());
+}
+
+#[test]
+fn test_missing_previous_hop() {
+    let (mut drone, _controller_recv, event_recv, neighbor_recv) = setup_drone();
+
+    // The drone is the only hop, so it is the destination and has nobody to Nack
+    drone.handle_packet(routed_fragment(vec![1], 0));
+
+    match event_recv.try_recv() {
+        Ok(RustasticEvent::ProtocolViolation { violation, .. }) => {
+            assert_eq!(violation, ProtocolViolation::MissingPreviousHop);
+        }
+        other => panic!("Expected a ProtocolViolation, got {other:?}"),
+    }
+    assert!(neighbor_recv.try_recv().is_err());
+}
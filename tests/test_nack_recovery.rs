@@ -0,0 +1,156 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID};
+use rustastic_drone::{NackRecoveryPolicy, RustasticDrone};
+
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Nack, NackType, Packet, PacketType},
+};
+
+/// Receivers of a drone built by `setup_drone`
+struct Receivers {
+    _controller: Receiver<DroneEvent>,
+    upstream: Receiver<Packet>,
+    downstream: Receiver<Packet>,
+}
+
+/// Creates drone 1 between the upstream neighbor 0 and the downstream neighbor 2
+fn setup_drone(nack_recovery: NackRecoveryPolicy) -> (RustasticDrone, Receivers) {
+    let (controller_send, controller) = unbounded();
+    let (upstream_send, upstream) = unbounded();
+    let (downstream_send, downstream) = unbounded();
+
+    let mut packet_send = HashMap::new();
+    packet_send.insert(0, upstream_send);
+    packet_send.insert(2, downstream_send);
+
+    let drone = RustasticDrone::new(
+        1,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        0.0,
+    )
+    .with_nack_recovery(nack_recovery);
+
+    (
+        drone,
+        Receivers {
+            _controller: controller,
+            upstream,
+            downstream,
+        },
+    )
+}
+
+/// A Nack for the fragment, sent back by drone 2
+fn nack_packet(nack_type: NackType) -> Packet {
+    Packet {
+        pack_type: PacketType::Nack(Nack {
+            fragment_index: 2,
+            nack_type,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![2, 1, 0],
+        },
+        session_id: SESSION_ID,
+    }
+}
+
+fn is_fragment(packet: &Packet) -> bool {
+    matches!(packet.pack_type, PacketType::MsgFragment(_))
+}
+
+fn is_nack(packet: &Packet) -> bool {
+    matches!(packet.pack_type, PacketType::Nack(_))
+}
+
+#[test]
+fn test_dropped_nack_is_recovered_and_absorbed() {
+    let (mut drone, receivers) = setup_drone(NackRecoveryPolicy::default());
+
+    drone.handle_packet(fragment_packet(2, vec![0, 1, 2, 3]));
+    drone.handle_packet(nack_packet(NackType::Dropped));
+
+    let resent: Vec<_> = receivers.downstream.try_iter().collect();
+    assert_eq!(resent.len(), 2);
+    assert!(resent.iter().all(is_fragment));
+    assert!(receivers.upstream.try_recv().is_err());
+}
+
+#[test]
+fn test_routing_error_nack_is_forwarded() {
+    let (mut drone, receivers) = setup_drone(NackRecoveryPolicy::default());
+
+    drone.handle_packet(fragment_packet(2, vec![0, 1, 2, 3]));
+    drone.handle_packet(nack_packet(NackType::ErrorInRouting(3)));
+
+    assert_eq!(receivers.downstream.try_iter().count(), 1);
+    assert!(receivers.upstream.try_recv().is_ok_and(|p| is_nack(&p)));
+    // The buffered copy was discarded, a later Dropped Nack can't be recovered
+    drone.handle_packet(nack_packet(NackType::Dropped));
+    assert!(receivers.downstream.try_recv().is_err());
+    assert!(receivers.upstream.try_recv().is_ok_and(|p| is_nack(&p)));
+}
+
+#[test]
+fn test_local_retries_are_capped() {
+    let (mut drone, receivers) = setup_drone(NackRecoveryPolicy {
+        max_local_retries: 2,
+        absorb_recovered: true,
+    });
+
+    drone.handle_packet(fragment_packet(2, vec![0, 1, 2, 3]));
+    for _ in 0..3 {
+        drone.handle_packet(nack_packet(NackType::Dropped));
+    }
+
+    // The original and two local retransmissions, then the third Nack goes back to the source
+    assert_eq!(receivers.downstream.try_iter().count(), 3);
+    let upstream: Vec<_> = receivers.upstream.try_iter().collect();
+    assert_eq!(upstream.len(), 1);
+    assert!(is_nack(&upstream[0]));
+
+    // The fragment was given up on, not retrieved
+    let stats = drone.buffer().stats();
+    assert_eq!(
+        (stats.retransmitted, stats.abandoned, stats.retrieved),
+        (2, 1, 0)
+    );
+    assert!(drone.buffer().is_empty());
+}
+
+#[test]
+fn test_recovered_nack_is_forwarded_when_not_absorbed() {
+    let (mut drone, receivers) = setup_drone(NackRecoveryPolicy {
+        max_local_retries: 3,
+        absorb_recovered: false,
+    });
+
+    drone.handle_packet(fragment_packet(2, vec![0, 1, 2, 3]));
+    drone.handle_packet(nack_packet(NackType::Dropped));
+
+    assert_eq!(receivers.downstream.try_iter().count(), 2);
+    assert!(receivers.upstream.try_recv().is_ok_and(|p| is_nack(&p)));
+}
+
+#[test]
+fn test_local_recovery_disabled() {
+    let (mut drone, receivers) = setup_drone(NackRecoveryPolicy {
+        max_local_retries: 0,
+        absorb_recovered: true,
+    });
+
+    drone.handle_packet(fragment_packet(2, vec![0, 1, 2, 3]));
+    drone.handle_packet(nack_packet(NackType::Dropped));
+
+    assert_eq!(receivers.downstream.try_iter().count(), 1);
+    assert!(receivers.upstream.try_recv().is_ok_and(|p| is_nack(&p)));
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID, TIMEOUT};
+use rustastic_drone::{parse_config, Network, NetworkError};
+
+use std::time::Duration;
+use wg_2024::controller::{DroneCommand, DroneEvent};
+
+/// Client 1 - Drone 11 - Drone 12 - Server 21, with a spare Drone 13 linked to Drone 11 only
+const LINE_NETWORK: &str = r"
+[[drone]]
+id = 11
+connected_node_ids = [1, 12]
+pdr = 0.0
+
+[[drone]]
+id = 12
+connected_node_ids = [11, 21]
+pdr = 0.0
+
+[[drone]]
+id = 13
+connected_node_ids = [11]
+pdr = 0.0
+
+[[client]]
+id = 1
+connected_drone_ids = [11]
+
+[[server]]
+id = 21
+connected_drone_ids = [12]
+";
+
+#[test]
+fn test_network_forwards_from_client_to_server() {
+    let network = Network::launch(&parse_config(LINE_NETWORK).unwrap()).unwrap();
+    assert_eq!(network.drone_ids(), vec![11, 12, 13]);
+
+    let packet = fragment_packet(0, vec![1, 11, 12, 21]);
+    network.packet_send(11).unwrap().send(packet).unwrap();
+
+    let received = network
+        .packet_recv(21)
+        .unwrap()
+        .recv_timeout(TIMEOUT)
+        .unwrap();
+    assert_eq!(received.routing_header.hop_index, 3);
+    assert_eq!(received.session_id, SESSION_ID);
+
+    // Both drones report the fragment they sent
+    let mut senders = Vec::new();
+    while senders.len() < 2 {
+        let (drone_id, event) = network.recv_event(TIMEOUT).unwrap();
+        assert!(matches!(event, DroneEvent::PacketSent(_)));
+        senders.push(drone_id);
+    }
+    senders.sort_unstable();
+    assert_eq!(senders, vec![11, 12]);
+
+    network.shutdown();
+}
+
+#[test]
+fn test_network_commands() {
+    let network = Network::launch(&parse_config(LINE_NETWORK).unwrap()).unwrap();
+
+    // Drone 13 is connected to Server 21, so the route through it becomes valid
+    network.connect(13, 21).unwrap();
+    network
+        .packet_send(13)
+        .unwrap()
+        .send(fragment_packet(0, vec![11, 13, 21]))
+        .unwrap();
+    assert!(network
+        .packet_recv(21)
+        .unwrap()
+        .recv_timeout(TIMEOUT)
+        .is_ok());
+
+    // Once disconnected, the fragment is NACKed back towards Drone 11
+    network.disconnect(13, 21).unwrap();
+    network
+        .packet_send(13)
+        .unwrap()
+        .send(fragment_packet(0, vec![11, 13, 21]))
+        .unwrap();
+    assert!(network
+        .packet_recv(21)
+        .unwrap()
+        .recv_timeout(TIMEOUT)
+        .is_err());
+
+    network.send_command(12, DroneCommand::Crash).unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(matches!(
+        network.send_command(12, DroneCommand::Crash),
+        Err(NetworkError::Crashed(12))
+    ));
+    assert!(matches!(
+        network.send_command(1, DroneCommand::Crash),
+        Err(NetworkError::UnknownDrone(1))
+    ));
+    assert!(matches!(
+        network.connect(1, 21),
+        Err(NetworkError::InvalidLink {
+            node_id: 1,
+            neighbor_id: 21
+        })
+    ));
+}
+
+#[test]
+fn test_invalid_networks_are_refused() {
+    let launch = |content: &str| Network::launch(&parse_config(content).unwrap());
+
+    assert!(matches!(
+        launch(&LINE_NETWORK.replace("id = 13", "id = 12")),
+        Err(NetworkError::DuplicateId(12))
+    ));
+    assert!(matches!(
+        launch(&LINE_NETWORK.replace("[11, 21]", "[11, 22]")),
+        Err(NetworkError::UnknownNeighbor {
+            node_id: 12,
+            neighbor_id: 22
+        })
+    ));
+    assert!(matches!(
+        launch(&LINE_NETWORK.replace("connected_drone_ids = [12]", "connected_drone_ids = [1]")),
+        Err(NetworkError::InvalidLink {
+            node_id: 21,
+            neighbor_id: 1
+        })
+    ));
+    assert!(matches!(
+        launch(&LINE_NETWORK.replacen("pdr = 0.0", "pdr = 1.5", 1)),
+        Err(NetworkError::InvalidPdr { drone_id: 11, .. })
+    ));
+    assert!(matches!(
+        parse_config("[[drone]]\nid = 1"),
+        Err(NetworkError::Parse(_))
+    ));
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::{fragment_packet, TIMEOUT};
+use rustastic_drone::{PriorityWeights, RustasticDrone, TrafficClass};
+
+use crossbeam_channel::unbounded;
+use std::{collections::HashMap, thread};
+use wg_2024::{
+    controller::DroneCommand,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Ack, Nack, NackType, Packet, PacketType},
+};
+
+fn packet(pack_type: PacketType) -> Packet {
+    Packet {
+        pack_type,
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![0, 1, 2],
+        },
+        session_id: 2,
+    }
+}
+
+fn fragment(fragment_index: u64) -> Packet {
+    fragment_packet(fragment_index, vec![0, 1, 2])
+}
+
+fn ack(fragment_index: u64) -> Packet {
+    packet(PacketType::Ack(Ack { fragment_index }))
+}
+
+/// Queues `input` before Drone 1 starts, and returns the classes of the packets it forwards, in order
+fn forwarded_classes(weights: PriorityWeights, input: Vec<Packet>) -> Vec<TrafficClass> {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, _prev_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    let len = input.len();
+    for packet in input {
+        packet_send.send(packet).unwrap();
+    }
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(0, prev_send), (2, next_send)]),
+        0.0,
+    )
+    .with_priority_weights(weights);
+    let drone_thread = thread::spawn(move || drone.run());
+
+    let classes = (0..len)
+        .map(|_| TrafficClass::of(&next_recv.recv_timeout(TIMEOUT).unwrap()))
+        .collect();
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+    classes
+}
+
+#[test]
+fn test_traffic_classes() {
+    assert_eq!(TrafficClass::of(&fragment(0)), TrafficClass::Data);
+    assert_eq!(TrafficClass::of(&ack(0)), TrafficClass::Control);
+    assert_eq!(
+        TrafficClass::of(&packet(PacketType::Nack(Nack {
+            fragment_index: 0,
+            nack_type: NackType::Dropped,
+        }))),
+        TrafficClass::Control
+    );
+}
+
+#[test]
+fn test_control_packets_overtake_fragments() {
+    let mut input: Vec<Packet> = (0..10).map(fragment).collect();
+    input.extend([ack(0), ack(1)]);
+
+    let classes = forwarded_classes(PriorityWeights::default(), input);
+    assert_eq!(classes[..2], [TrafficClass::Control; 2]);
+    assert_eq!(classes[2..], [TrafficClass::Data; 10]);
+}
+
+#[test]
+fn test_fragments_are_not_starved() {
+    let mut input: Vec<Packet> = (0..10).map(ack).collect();
+    input.extend((0..10).map(fragment));
+
+    // Four control packets for every fragment, while both classes are waiting
+    let classes = forwarded_classes(PriorityWeights::default(), input);
+    let pattern: String = classes
+        .iter()
+        .map(|class| match class {
+            TrafficClass::Control => 'C',
+            TrafficClass::Data => 'D',
+        })
+        .collect();
+    assert_eq!(pattern, "CCCCDCCCCDCCDDDDDDDD");
+
+    // With equal weights the classes alternate
+    let mut input: Vec<Packet> = (0..3).map(fragment).collect();
+    input.extend((0..3).map(ack));
+    let classes = forwarded_classes(
+        PriorityWeights {
+            control: 1,
+            data: 1,
+        },
+        input,
+    );
+    assert_eq!(
+        classes,
+        [TrafficClass::Control, TrafficClass::Data].repeat(3)
+    );
+}
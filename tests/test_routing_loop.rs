@@ -0,0 +1,145 @@
+mod common;
+
+use common::FRAGMENT_DSIZE;
+use rustastic_drone::{LoopPolicy, ProtocolViolation, RustasticDrone, RustasticEvent};
+
+use crossbeam_channel::{unbounded, Receiver};
+use std::collections::HashMap;
+use wg_2024::{
+    controller::DroneEvent,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Ack, Fragment, NackType, Packet, PacketType},
+};
+
+/// Receivers of a drone built by `setup_drone`
+struct Receivers {
+    controller: Receiver<DroneEvent>,
+    events: Receiver<RustasticEvent>,
+    neighbor_0: Receiver<Packet>,
+    neighbor_2: Receiver<Packet>,
+}
+
+/// Creates drone 1 with neighbors 0 and 2 and the given `LoopPolicy`
+fn setup_drone(loop_policy: LoopPolicy) -> (RustasticDrone, Receivers) {
+    let (controller_send, controller) = unbounded();
+    let (event_send, events) = unbounded();
+    let (neighbor_send_0, neighbor_0) = unbounded();
+    let (neighbor_send_2, neighbor_2) = unbounded();
+
+    let mut packet_send = HashMap::new();
+    packet_send.insert(0, neighbor_send_0);
+    packet_send.insert(2, neighbor_send_2);
+
+    let drone = RustasticDrone::new(
+        1,
+        controller_send,
+        unbounded().1,
+        unbounded().1,
+        packet_send,
+        0.0,
+    )
+    .with_event_send(event_send)
+    .with_loop_policy(loop_policy);
+
+    (
+        drone,
+        Receivers {
+            controller,
+            events,
+            neighbor_0,
+            neighbor_2,
+        },
+    )
+}
+
+/// A fragment whose route goes back through drone 1 after drone 2
+fn looping_fragment() -> Packet {
+    Packet {
+        pack_type: PacketType::MsgFragment(Fragment {
+            fragment_index: 3,
+            total_n_fragments: 4,
+            length: FRAGMENT_DSIZE as u8,
+            data: [1; FRAGMENT_DSIZE],
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![0, 1, 2, 1, 5],
+        },
+        session_id: 11,
+    }
+}
+
+fn assert_loop_reported(events: &Receiver<RustasticEvent>) {
+    match events.try_recv() {
+        Ok(RustasticEvent::ProtocolViolation { violation, .. }) => {
+            assert_eq!(violation, ProtocolViolation::RoutingLoop { node_id: 1 });
+        }
+        other => panic!("Expected a RoutingLoop violation, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_nacks_looping_fragment() {
+    let (mut drone, receivers) = setup_drone(LoopPolicy::Strict);
+
+    drone.handle_packet(looping_fragment());
+
+    assert_loop_reported(&receivers.events);
+    assert!(receivers.neighbor_2.try_recv().is_err());
+
+    let nack = receivers
+        .neighbor_0
+        .try_recv()
+        .expect("Previous hop did not receive the Nack");
+    assert_eq!(nack.routing_header.hops, vec![1, 0]);
+    if let PacketType::Nack(nack) = nack.pack_type {
+        assert_eq!(nack.fragment_index, 3);
+        assert_eq!(nack.nack_type, NackType::ErrorInRouting(1));
+    } else {
+        panic!("Expected a Nack, but got something else.");
+    }
+}
+
+#[test]
+fn test_strict_shortcuts_looping_ack() {
+    let (mut drone, receivers) = setup_drone(LoopPolicy::Strict);
+
+    let ack = Packet {
+        pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 1,
+            hops: vec![2, 1, 0, 1, 5],
+        },
+        session_id: 11,
+    };
+    drone.handle_packet(ack);
+
+    assert_loop_reported(&receivers.events);
+    assert!(receivers.neighbor_0.try_recv().is_err());
+    assert!(matches!(
+        receivers.controller.try_recv(),
+        Ok(DroneEvent::ControllerShortcut(_))
+    ));
+}
+
+#[test]
+fn test_warn_forwards_looping_fragment() {
+    let (mut drone, receivers) = setup_drone(LoopPolicy::Warn);
+
+    drone.handle_packet(looping_fragment());
+
+    assert_loop_reported(&receivers.events);
+    assert!(receivers.neighbor_2.try_recv().is_ok());
+    assert!(receivers.neighbor_0.try_recv().is_err());
+}
+
+#[test]
+fn test_off_ignores_loops() {
+    let (mut drone, receivers) = setup_drone(LoopPolicy::Off);
+
+    drone.handle_packet(looping_fragment());
+
+    assert!(receivers.events.try_recv().is_err());
+    assert!(receivers.neighbor_2.try_recv().is_ok());
+}
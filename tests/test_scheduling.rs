@@ -0,0 +1,172 @@
+mod common;
+
+use common::{fragment_packet, TIMEOUT};
+use rustastic_drone::{RustasticDrone, SchedulingPolicy};
+
+use crossbeam_channel::unbounded;
+use std::{collections::HashMap, thread};
+use wg_2024::{
+    controller::DroneCommand,
+    drone::Drone,
+    packet::{NackType, Packet, PacketType},
+};
+
+/// Queues `fragments` fragments and `no_ops` commands that change nothing, followed by `last`, before
+/// Drone 1 starts, and returns how many fragments it forwarded to Node 2 and how many it NACKed
+fn run_drone(
+    policy: SchedulingPolicy,
+    batch: usize,
+    fragments: u64,
+    no_ops: usize,
+    last: DroneCommand,
+) -> (usize, usize) {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, prev_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    for fragment_index in 0..fragments {
+        packet_send
+            .send(fragment_packet(fragment_index, vec![0, 1, 2]))
+            .unwrap();
+    }
+    for _ in 0..no_ops {
+        command_send
+            .send(DroneCommand::SetPacketDropRate(0.0))
+            .unwrap();
+    }
+    let crashes = matches!(last, DroneCommand::Crash);
+    command_send.send(last).unwrap();
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(0, prev_send), (2, next_send)]),
+        0.0,
+    )
+    .with_scheduling(policy, batch);
+    let drone_thread = thread::spawn(move || drone.run());
+
+    let is_nack = |packet: &Packet| {
+        matches!(
+            &packet.pack_type,
+            PacketType::Nack(nack) if nack.nack_type == NackType::ErrorInRouting(2)
+        )
+    };
+    let (mut forwarded, mut nacked) = (0, 0);
+    if !crashes {
+        // Every fragment is either forwarded or NACKed
+        while forwarded + nacked < fragments as usize {
+            crossbeam_channel::select! {
+                recv(next_recv) -> packet => forwarded += usize::from(packet.is_ok()),
+                recv(prev_recv) -> packet => nacked += usize::from(is_nack(&packet.unwrap())),
+                default(TIMEOUT) => panic!("the drone is stuck"),
+            }
+        }
+        command_send.send(DroneCommand::Crash).unwrap();
+    }
+    drone_thread.join().unwrap();
+
+    forwarded += next_recv.try_iter().count();
+    nacked += prev_recv.try_iter().filter(is_nack).count();
+    (forwarded, nacked)
+}
+
+#[test]
+fn test_strict_commands_go_first() {
+    let (forwarded, _) = run_drone(SchedulingPolicy::Strict, 1, 10, 100, DroneCommand::Crash);
+    assert_eq!(forwarded, 0);
+}
+
+#[test]
+fn test_commands_do_not_starve_packets() {
+    // Every fragment gets its turn before the crash, however many commands come first
+    let (forwarded, _) = run_drone(
+        SchedulingPolicy::RoundRobin,
+        1,
+        10,
+        100,
+        DroneCommand::Crash,
+    );
+    assert_eq!(forwarded, 10);
+
+    let (forwarded, _) = run_drone(
+        SchedulingPolicy::Weighted {
+            commands: 10,
+            packets: 1,
+        },
+        1,
+        10,
+        100,
+        DroneCommand::Crash,
+    );
+    assert_eq!(forwarded, 10);
+}
+
+#[test]
+fn test_packets_do_not_starve_commands() {
+    // The link to Node 2 is removed by the 4th command: the fragments handled after it are NACKed
+    let remove = || DroneCommand::RemoveSender(2);
+
+    let (forwarded, nacked) = run_drone(SchedulingPolicy::Strict, 1, 20, 3, remove());
+    assert_eq!((forwarded, nacked), (0, 20));
+
+    let (forwarded, nacked) = run_drone(SchedulingPolicy::RoundRobin, 1, 20, 3, remove());
+    assert_eq!((forwarded, nacked), (3, 17));
+
+    let (forwarded, nacked) = run_drone(
+        SchedulingPolicy::Weighted {
+            commands: 1,
+            packets: 2,
+        },
+        1,
+        20,
+        3,
+        remove(),
+    );
+    assert_eq!((forwarded, nacked), (6, 14));
+}
+
+#[test]
+fn test_batch_draining() {
+    // Each turn of packets handles up to 4 of them
+    let (forwarded, nacked) = run_drone(
+        SchedulingPolicy::RoundRobin,
+        4,
+        20,
+        3,
+        DroneCommand::RemoveSender(2),
+    );
+    assert_eq!((forwarded, nacked), (12, 8));
+}
+
+#[test]
+fn test_inbox_is_bounded() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (next_send, _next_recv) = unbounded();
+
+    for fragment_index in 0..1000 {
+        packet_send
+            .send(fragment_packet(fragment_index, vec![0, 1, 2]))
+            .unwrap();
+    }
+    command_send.send(DroneCommand::Crash).unwrap();
+
+    let mut drone = RustasticDrone::new(
+        1,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(2, next_send)]),
+        0.0,
+    );
+    drone.run();
+
+    // The drone crashed after pulling a bounded number of packets, the others are still in the channel
+    assert!(packet_send.len() >= 900);
+}
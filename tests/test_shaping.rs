@@ -0,0 +1,218 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID, TIMEOUT};
+use rustastic_drone::{LinkShaping, RustasticDrone, RustasticEvent, SaturationPolicy};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::{
+    collections::HashMap,
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Ack, NackType, Packet, PacketType},
+};
+
+struct ShapedDrone {
+    packet_send: Sender<Packet>,
+    command_send: Sender<DroneCommand>,
+    controller_recv: Receiver<DroneEvent>,
+    event_recv: Receiver<RustasticEvent>,
+    prev_recv: Receiver<Packet>,
+    next_recv: Receiver<Packet>,
+    thread: JoinHandle<()>,
+}
+
+impl ShapedDrone {
+    /// Drone 1, between Node 0 and Node 2, with a shaped link to Node 2
+    fn spawn(shaping: LinkShaping, duplicate_window: usize) -> Self {
+        let (controller_send, controller_recv) = unbounded();
+        let (command_send, command_recv) = unbounded();
+        let (packet_send, packet_recv) = unbounded();
+        let (event_send, event_recv) = unbounded();
+        let (prev_send, prev_recv) = unbounded();
+        let (next_send, next_recv) = unbounded();
+
+        let mut drone = RustasticDrone::new(
+            1,
+            controller_send,
+            command_recv,
+            packet_recv,
+            HashMap::from([(0, prev_send), (2, next_send)]),
+            0.0,
+        )
+        .with_event_send(event_send)
+        .with_duplicate_window(duplicate_window)
+        .with_link_shaping(2, shaping);
+        let thread = thread::spawn(move || drone.run());
+
+        Self {
+            packet_send,
+            command_send,
+            controller_recv,
+            event_recv,
+            prev_recv,
+            next_recv,
+            thread,
+        }
+    }
+
+    fn crash(self) {
+        self.command_send.send(DroneCommand::Crash).unwrap();
+        self.thread.join().unwrap();
+    }
+}
+
+#[test]
+fn test_saturated_link_queues_packets() {
+    let drone = ShapedDrone::spawn(LinkShaping::packets_per_second(10.0).with_burst(2.0), 0);
+
+    let start = Instant::now();
+    for fragment_index in 0..5 {
+        drone
+            .packet_send
+            .send(fragment_packet(fragment_index, vec![0, 1, 2]))
+            .unwrap();
+    }
+
+    // The burst leaves at once, then one packet every 100ms
+    for fragment_index in 0..5 {
+        let packet = drone.next_recv.recv_timeout(TIMEOUT).unwrap();
+        assert!(matches!(
+            packet.pack_type,
+            PacketType::MsgFragment(fragment) if fragment.fragment_index == fragment_index
+        ));
+        if fragment_index == 1 {
+            assert!(start.elapsed() < Duration::from_millis(100));
+        }
+    }
+    assert!(start.elapsed() >= Duration::from_millis(250));
+    assert!(drone.event_recv.try_recv().is_err());
+
+    drone.crash();
+}
+
+#[test]
+fn test_saturated_link_drops_packets() {
+    let drone = ShapedDrone::spawn(
+        LinkShaping::packets_per_second(0.1)
+            .with_burst(1.0)
+            .with_policy(SaturationPolicy::Drop),
+        0,
+    );
+
+    drone
+        .packet_send
+        .send(fragment_packet(0, vec![0, 1, 2]))
+        .unwrap();
+    assert!(drone.next_recv.recv_timeout(TIMEOUT).is_ok());
+
+    // The bucket is now empty
+    drone
+        .packet_send
+        .send(fragment_packet(1, vec![0, 1, 2]))
+        .unwrap();
+    drone
+        .packet_send
+        .send(Packet {
+            pack_type: PacketType::Ack(Ack { fragment_index: 0 }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![0, 1, 2],
+            },
+            session_id: SESSION_ID,
+        })
+        .unwrap();
+
+    // The dropped fragment is NACKed and reported, the dropped Ack is shortcut to the controller
+    let mut dropped: Vec<bool> = (0..2)
+        .map(|_| match drone.event_recv.recv_timeout(TIMEOUT) {
+            Ok(RustasticEvent::ShapingDrop {
+                drone_id: 1,
+                neighbor_id: 2,
+                packet,
+            }) => matches!(packet.pack_type, PacketType::Ack(_)),
+            event => panic!("unexpected event {event:?}"),
+        })
+        .collect();
+    dropped.sort_unstable();
+    assert_eq!(dropped, vec![false, true]);
+    let events: Vec<DroneEvent> = drone.controller_recv.try_iter().collect();
+    let shortcuts = events
+        .iter()
+        .filter(|event| matches!(event, DroneEvent::ControllerShortcut(_)))
+        .count();
+    assert_eq!(shortcuts, 1);
+    assert!(events.iter().any(|event| matches!(event,
+        DroneEvent::PacketDropped(packet) if packet.routing_header.hop_index == 1
+            && matches!(&packet.pack_type, PacketType::MsgFragment(fragment) if fragment.fragment_index == 1))));
+    assert!(matches!(
+        drone.prev_recv.recv_timeout(TIMEOUT).unwrap().pack_type,
+        PacketType::Nack(nack) if nack.fragment_index == 1 && nack.nack_type == NackType::Dropped
+    ));
+    assert!(drone.next_recv.try_recv().is_err());
+
+    drone.crash();
+}
+
+#[test]
+fn test_bytes_per_second_counts_frames() {
+    // The bucket holds a single fragment frame
+    let drone = ShapedDrone::spawn(
+        LinkShaping::bytes_per_second(1.0)
+            .with_burst(200.0)
+            .with_policy(SaturationPolicy::Drop),
+        0,
+    );
+
+    drone
+        .packet_send
+        .send(fragment_packet(0, vec![0, 1, 2]))
+        .unwrap();
+    drone
+        .packet_send
+        .send(fragment_packet(1, vec![0, 1, 2]))
+        .unwrap();
+
+    assert!(drone.next_recv.recv_timeout(TIMEOUT).is_ok());
+    assert!(matches!(
+        drone.event_recv.recv_timeout(TIMEOUT),
+        Ok(RustasticEvent::ShapingDrop { .. })
+    ));
+
+    drone.crash();
+}
+
+#[test]
+fn test_dropped_fragment_is_not_a_duplicate() {
+    let drone = ShapedDrone::spawn(
+        LinkShaping::packets_per_second(0.1)
+            .with_burst(1.0)
+            .with_policy(SaturationPolicy::Drop),
+        16,
+    );
+
+    drone
+        .packet_send
+        .send(fragment_packet(0, vec![0, 1, 2]))
+        .unwrap();
+    assert!(drone.next_recv.recv_timeout(TIMEOUT).is_ok());
+
+    // The source retransmits the fragment the drone dropped: it is not discarded as a duplicate
+    for _ in 0..2 {
+        drone
+            .packet_send
+            .send(fragment_packet(1, vec![0, 1, 2]))
+            .unwrap();
+        assert!(matches!(
+            drone.event_recv.recv_timeout(TIMEOUT),
+            Ok(RustasticEvent::ShapingDrop { .. })
+        ));
+        assert!(drone.prev_recv.recv_timeout(TIMEOUT).is_ok());
+    }
+
+    drone.crash();
+}
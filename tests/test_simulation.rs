@@ -0,0 +1,261 @@
+mod common;
+
+use common::fragment_packet;
+use rustastic_drone::{
+    parse_config, DroneInput, FloodStats, RustasticEvent, Simulation, SimulationEvent,
+};
+
+use std::fmt::Write;
+use std::time::Duration;
+use wg_2024::{
+    controller::{DroneCommand, DroneEvent},
+    network::SourceRoutingHeader,
+    packet::{FloodRequest, NackType, NodeType, Packet, PacketType},
+};
+
+/// Client 1 - Drone 11 - Drone 12 - Server 21
+const LINE_NETWORK: &str = r"
+[[drone]]
+id = 11
+connected_node_ids = [1, 12]
+pdr = 0.0
+
+[[drone]]
+id = 12
+connected_node_ids = [11, 21]
+pdr = 0.0
+
+[[client]]
+id = 1
+connected_drone_ids = [11]
+
+[[server]]
+id = 21
+connected_drone_ids = [12]
+";
+
+/// A ring of `n` drones, with IDs from 1 to `n`, and a client with ID 0 connected to the first drone
+fn ring_network(n: u8, pdr: f32) -> String {
+    let mut config = String::from("server = []\n\n");
+    for id in 1..=n {
+        let prev = if id == 1 { n } else { id - 1 };
+        let next = if id == n { 1 } else { id + 1 };
+        let client = if id == 1 { "0, " } else { "" };
+        writeln!(
+            config,
+            "[[drone]]\nid = {id}\nconnected_node_ids = [{client}{prev}, {next}]\npdr = {pdr:?}\n"
+        )
+        .unwrap();
+    }
+    config.push_str("[[client]]\nid = 0\nconnected_drone_ids = [1]\n");
+    config
+}
+
+#[test]
+fn test_packets_take_the_latency_of_their_links() {
+    let mut simulation = Simulation::new(&parse_config(LINE_NETWORK).unwrap(), 0)
+        .unwrap()
+        .with_default_latency(Duration::from_millis(2));
+    simulation.set_latency(12, 11, Duration::from_millis(5));
+
+    simulation.send_packet(11, fragment_packet(0, vec![1, 11, 12, 21]));
+    assert_eq!(simulation.run(), Duration::from_millis(7));
+
+    let events = simulation.take_events();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(
+        &events[0],
+        SimulationEvent::Controller {
+            drone_id: 11,
+            event: DroneEvent::PacketSent(_),
+            ..
+        }
+    ));
+    assert_eq!(events[1].time(), Duration::from_millis(5));
+    assert!(matches!(
+        &events[2],
+        SimulationEvent::Delivered { host_id: 21, packet, .. } if packet.routing_header.hop_index == 3
+    ));
+    assert_eq!(events[2].time(), Duration::from_millis(7));
+}
+
+#[test]
+fn test_commands_come_before_packets() {
+    let mut simulation = Simulation::new(&parse_config(LINE_NETWORK).unwrap(), 0).unwrap();
+
+    // The packet is scheduled first, but the crash is handled first
+    simulation.send_packet(11, fragment_packet(0, vec![1, 11, 12, 21]));
+    simulation.send_command(11, DroneCommand::Crash);
+    simulation.run();
+
+    assert!(simulation.drone(11).is_none());
+    assert!(simulation.events().is_empty());
+}
+
+#[test]
+fn test_crashed_neighbor_is_declared_dead() {
+    let mut simulation = Simulation::new(&parse_config(LINE_NETWORK).unwrap(), 0).unwrap();
+    simulation.send_command(12, DroneCommand::Crash);
+    for fragment_index in 0..4 {
+        simulation.schedule(
+            Duration::from_millis(fragment_index),
+            11,
+            DroneInput::Packet(fragment_packet(fragment_index, vec![1, 11, 12, 21])),
+        );
+    }
+    simulation.run();
+
+    // Three failed sends make drone 12 dead, then the fragment for it is NACKed without trying it
+    assert_eq!(simulation.drone(11).unwrap().dead_neighbors(), [12]);
+    let dead = simulation
+        .events()
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                SimulationEvent::Rustastic {
+                    event: RustasticEvent::NeighborDead {
+                        drone_id: 11,
+                        neighbor_id: 12,
+                        failures: 3
+                    },
+                    ..
+                }
+            )
+        })
+        .count();
+    assert_eq!(dead, 1);
+    let nacks = simulation
+        .events()
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                SimulationEvent::Delivered { host_id: 1, packet, .. }
+                    if matches!(&packet.pack_type, PacketType::Nack(nack) if nack.nack_type == NackType::ErrorInRouting(12))
+            )
+        })
+        .count();
+    assert_eq!(nacks, 4);
+    assert!(!simulation.events().iter().any(|event| matches!(
+        event,
+        SimulationEvent::Controller {
+            event: DroneEvent::ControllerShortcut(_),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_runs_with_the_same_seed_are_identical() {
+    let run = |seed: u64| {
+        let config = parse_config(&ring_network(10, 0.3)).unwrap();
+        let mut simulation = Simulation::new(&config, seed).unwrap();
+        for fragment_index in 0..50 {
+            simulation.schedule(
+                Duration::from_micros(fragment_index * 100),
+                1,
+                DroneInput::Packet(fragment_packet(fragment_index, vec![0, 1, 2, 3, 4, 5, 6])),
+            );
+        }
+        simulation.run();
+        format!("{:?}", simulation.events())
+    };
+
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(7));
+}
+
+#[test]
+fn test_flooding_a_large_network() {
+    let config = parse_config(&ring_network(200, 0.0)).unwrap();
+    let mut simulation = Simulation::new(&config, 0).unwrap();
+
+    simulation.send_packet(
+        1,
+        Packet {
+            pack_type: PacketType::FloodRequest(FloodRequest {
+                flood_id: 1,
+                initiator_id: 0,
+                path_trace: vec![(0, NodeType::Client)],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 0,
+                hops: vec![],
+            },
+            session_id: 0,
+        },
+    );
+    simulation.run();
+
+    // The request travels around the ring both ways, until the two halves meet
+    let responses = simulation
+        .events()
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                SimulationEvent::Delivered {
+                    host_id: 0,
+                    packet: Packet {
+                        pack_type: PacketType::FloodResponse(_),
+                        ..
+                    },
+                    ..
+                }
+            )
+        })
+        .count();
+    assert_eq!(responses, 2);
+}
+
+#[test]
+fn test_flood_hop_limit_bounds_discovery() {
+    let config = parse_config(&ring_network(200, 0.0)).unwrap();
+    let mut simulation =
+        Simulation::new_with(&config, 0, |core| core.with_max_flood_hops(10)).unwrap();
+
+    simulation.send_packet(
+        1,
+        Packet {
+            pack_type: PacketType::FloodRequest(FloodRequest {
+                flood_id: 1,
+                initiator_id: 0,
+                path_trace: vec![(0, NodeType::Client)],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 0,
+                hops: vec![],
+            },
+            session_id: 0,
+        },
+    );
+    simulation.run();
+
+    // Each half of the ring is explored up to 10 hops from the client
+    let traces: Vec<usize> = simulation
+        .events()
+        .iter()
+        .filter_map(|event| match event {
+            SimulationEvent::Delivered {
+                host_id: 0,
+                packet:
+                    Packet {
+                        pack_type: PacketType::FloodResponse(response),
+                        ..
+                    },
+                ..
+            } => Some(response.path_trace.len()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(traces, [11, 11]);
+
+    let stats: Vec<FloodStats> = (1..=200)
+        .map(|id| simulation.drone(id).unwrap().flood_stats())
+        .collect();
+    assert_eq!(stats.iter().map(|stats| stats.hop_limited).sum::<u64>(), 2);
+    assert_eq!(stats.iter().map(|stats| stats.forwarded).sum::<u64>(), 17);
+    assert_eq!(stats[9].hop_limited, 1);
+    assert_eq!(stats[191].hop_limited, 1);
+}
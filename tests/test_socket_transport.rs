@@ -0,0 +1,118 @@
+mod common;
+
+use common::{fragment_packet, SESSION_ID, TIMEOUT};
+use rustastic_drone::{
+    codec, PacketTransport, RustasticDrone, SocketAddress, SocketTransport, TransportError,
+};
+
+use crossbeam_channel::unbounded;
+use std::net::UdpSocket;
+
+fn localhost() -> SocketAddress {
+    "udp:127.0.0.1:0".parse().unwrap()
+}
+
+#[test]
+fn test_udp_transports_exchange_packets() {
+    let receiver = SocketTransport::bind(&localhost()).unwrap();
+    let sender = SocketTransport::bind(&localhost())
+        .unwrap()
+        .with_neighbor(2, receiver.local_address().unwrap());
+
+    let packet = fragment_packet(0, vec![1, 2]);
+    sender.send(2, packet.clone()).unwrap();
+    assert_eq!(
+        receiver.packet_recv().recv_timeout(TIMEOUT).unwrap(),
+        packet
+    );
+
+    assert!(matches!(
+        sender.send(3, packet),
+        Err(TransportError::NotConnected(3))
+    ));
+}
+
+#[test]
+fn test_invalid_datagrams_are_discarded() {
+    let receiver = SocketTransport::bind(&localhost()).unwrap();
+    let SocketAddress::Udp(addr) = receiver.local_address().unwrap() else {
+        unreachable!()
+    };
+
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut frame = codec::encode(&fragment_packet(0, vec![1, 2])).unwrap();
+    frame[0] ^= 0xff;
+    socket.send_to(&frame, addr).unwrap();
+    socket.send_to(b"not a frame", addr).unwrap();
+
+    let packet = fragment_packet(0, vec![3, 2]);
+    socket
+        .send_to(&codec::encode(&packet).unwrap(), addr)
+        .unwrap();
+
+    // Only the valid frame is delivered
+    assert_eq!(
+        receiver.packet_recv().recv_timeout(TIMEOUT).unwrap(),
+        packet
+    );
+    assert!(receiver.packet_recv().try_recv().is_err());
+}
+
+#[test]
+fn test_drone_forwards_over_udp() {
+    let next_hop = SocketTransport::bind(&localhost()).unwrap();
+    let transport = SocketTransport::bind(&localhost())
+        .unwrap()
+        .with_neighbor(0, localhost())
+        .with_neighbor(2, next_hop.local_address().unwrap());
+
+    let (controller_send, _controller_recv) = unbounded();
+    let mut drone =
+        RustasticDrone::with_transport(1, controller_send, unbounded().1, transport, 0.0);
+    drone.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+
+    let forwarded = next_hop.packet_recv().recv_timeout(TIMEOUT).unwrap();
+    assert_eq!(forwarded.routing_header.hop_index, 2);
+    assert_eq!(forwarded.session_id, SESSION_ID);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unix_transports_exchange_packets() {
+    let dir = std::env::temp_dir().join(format!("rustastic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = |name: &str| {
+        let path = dir.join(name);
+        let _ = std::fs::remove_file(&path);
+        format!("unix:{}", path.display())
+            .parse::<SocketAddress>()
+            .unwrap()
+    };
+
+    let receiver = SocketTransport::bind(&path("2.sock")).unwrap();
+    let sender = SocketTransport::bind(&path("1.sock"))
+        .unwrap()
+        .with_neighbor(2, receiver.local_address().unwrap());
+
+    let packet = fragment_packet(0, vec![1, 2]);
+    sender.send(2, packet.clone()).unwrap();
+    assert_eq!(
+        receiver.packet_recv().recv_timeout(TIMEOUT).unwrap(),
+        packet
+    );
+
+    // An address of the wrong kind can't be reached from a Unix socket
+    let sender = sender.with_neighbor(3, localhost());
+    assert!(matches!(
+        sender.send(3, packet),
+        Err(TransportError::Io(3, _))
+    ));
+
+    // The socket file is removed with the transport, a new one can be bound to the same path
+    let address = receiver.local_address().unwrap();
+    drop(receiver);
+    assert!(!dir.join("2.sock").exists());
+    assert!(SocketTransport::bind(&address).is_ok());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,240 @@
+mod common;
+
+use common::TIMEOUT;
+use rustastic_drone::{RustasticDrone, Topology};
+
+use crossbeam_channel::unbounded;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+use wg_2024::{
+    controller::DroneCommand,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{FloodRequest, FloodResponse, NodeType, Packet, PacketType},
+};
+
+#[test]
+fn test_topology_learns_traces() {
+    let mut topology = Topology::new();
+    assert!(topology.is_empty());
+
+    let first = Instant::now();
+    topology.observe(
+        &[
+            (1, NodeType::Client),
+            (11, NodeType::Drone),
+            (12, NodeType::Drone),
+            (21, NodeType::Server),
+        ],
+        first,
+    );
+    let later = first + Duration::from_secs(1);
+    topology.observe(&[(2, NodeType::Client), (12, NodeType::Drone)], later);
+    topology.observe(&[(3, NodeType::Client), (13, NodeType::Drone)], later);
+
+    let node = topology.node(21).unwrap();
+    assert_eq!(node.node_type, NodeType::Server);
+    assert_eq!(node.last_seen, first);
+    assert_eq!(topology.node(12).unwrap().last_seen, later);
+    assert!(topology.node(4).is_none());
+
+    let edges: Vec<(u8, u8)> = topology
+        .edges()
+        .iter()
+        .map(|edge| (edge.a, edge.b))
+        .collect();
+    assert_eq!(edges, [(1, 11), (2, 12), (3, 13), (11, 12), (12, 21)]);
+    assert_eq!(topology.neighbors(12), [2, 11, 21]);
+
+    // Client 3 and Drone 13 are cut off from the rest
+    assert_eq!(topology.components(), [vec![1, 2, 11, 12, 21], vec![3, 13]]);
+}
+
+#[test]
+fn test_drone_learns_from_floods() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (client_send, client_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    let topology = Arc::new(Mutex::new(Topology::new()));
+    let mut drone = RustasticDrone::new(
+        11,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(1, client_send), (12, next_send)]),
+        0.0,
+    )
+    .with_topology(Arc::clone(&topology));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    // A flood from Client 1, then the response of Server 21 through Drone 12
+    packet_send
+        .send(Packet {
+            pack_type: PacketType::FloodRequest(FloodRequest {
+                flood_id: 1,
+                initiator_id: 1,
+                path_trace: vec![(1, NodeType::Client)],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 0,
+                hops: vec![],
+            },
+            session_id: 0,
+        })
+        .unwrap();
+    assert!(next_recv.recv_timeout(TIMEOUT).is_ok());
+    assert_eq!(topology.lock().unwrap().neighbors(11), [1]);
+
+    packet_send
+        .send(Packet {
+            pack_type: PacketType::FloodResponse(FloodResponse {
+                flood_id: 1,
+                path_trace: vec![
+                    (1, NodeType::Client),
+                    (11, NodeType::Drone),
+                    (12, NodeType::Drone),
+                    (21, NodeType::Server),
+                ],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 2,
+                hops: vec![21, 12, 11, 1],
+            },
+            session_id: 0,
+        })
+        .unwrap();
+    assert!(client_recv.recv_timeout(TIMEOUT).is_ok());
+
+    let learned = topology.lock().unwrap().clone();
+    assert_eq!(learned.neighbors(12), [11, 21]);
+    assert_eq!(learned.node(21).unwrap().node_type, NodeType::Server);
+    assert_eq!(learned.components(), [vec![1, 11, 12, 21]]);
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+}
+
+#[test]
+fn test_drone_does_not_learn_refused_floods() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (client_send, _client_recv) = unbounded();
+    let (next_send, next_recv) = unbounded();
+
+    let topology = Arc::new(Mutex::new(Topology::new()));
+    let mut drone = RustasticDrone::new(
+        11,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(1, client_send), (12, next_send)]),
+        0.0,
+    )
+    .with_topology(Arc::clone(&topology));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    let flood_request = |flood_id, path_trace| Packet {
+        pack_type: PacketType::FloodRequest(FloodRequest {
+            flood_id,
+            initiator_id: 1,
+            path_trace,
+        }),
+        routing_header: SourceRoutingHeader {
+            hop_index: 0,
+            hops: vec![],
+        },
+        session_id: 0,
+    };
+
+    // Drone 5 is not a neighbor: the trace is refused, then a consistent flood goes through
+    packet_send
+        .send(flood_request(
+            1,
+            vec![(1, NodeType::Client), (5, NodeType::Drone)],
+        ))
+        .unwrap();
+    packet_send
+        .send(flood_request(2, vec![(1, NodeType::Client)]))
+        .unwrap();
+    assert!(next_recv.recv_timeout(TIMEOUT).is_ok());
+
+    let learned = topology.lock().unwrap().clone();
+    assert!(learned.node(5).is_none());
+    assert_eq!(learned.neighbors(11), [1]);
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+}
+
+#[test]
+fn test_drone_does_not_learn_misrouted_responses() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (client_send, client_recv) = unbounded();
+    let (next_send, _next_recv) = unbounded();
+
+    let topology = Arc::new(Mutex::new(Topology::new()));
+    let mut drone = RustasticDrone::new(
+        11,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(1, client_send), (12, next_send)]),
+        0.0,
+    )
+    .with_topology(Arc::clone(&topology));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    let flood_response = |flood_id, path_trace, hop_index, hops| Packet {
+        pack_type: PacketType::FloodResponse(FloodResponse {
+            flood_id,
+            path_trace,
+        }),
+        routing_header: SourceRoutingHeader { hop_index, hops },
+        session_id: 0,
+    };
+
+    // The first response is meant for drone 5, not for this drone: it is shortcut, then one for it is forwarded
+    packet_send
+        .send(flood_response(
+            1,
+            vec![
+                (1, NodeType::Client),
+                (5, NodeType::Drone),
+                (21, NodeType::Server),
+            ],
+            1,
+            vec![21, 5, 1],
+        ))
+        .unwrap();
+    packet_send
+        .send(flood_response(
+            2,
+            vec![
+                (1, NodeType::Client),
+                (11, NodeType::Drone),
+                (12, NodeType::Drone),
+            ],
+            1,
+            vec![12, 11, 1],
+        ))
+        .unwrap();
+    assert!(client_recv.recv_timeout(TIMEOUT).is_ok());
+
+    let learned = topology.lock().unwrap().clone();
+    assert!(learned.node(5).is_none());
+    assert!(learned.node(21).is_none());
+    assert_eq!(learned.neighbors(11), [1, 12]);
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+}
@@ -0,0 +1,174 @@
+mod common;
+
+use common::{FRAGMENT_DSIZE, TIMEOUT};
+use rustastic_drone::{RustasticDrone, Topology};
+
+use crossbeam_channel::unbounded;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::Instant,
+};
+use wg_2024::{
+    controller::DroneCommand,
+    drone::Drone,
+    network::SourceRoutingHeader,
+    packet::{Fragment, Nack, NackType, NodeType, Packet, PacketType},
+};
+
+fn sample_topology() -> Topology {
+    let mut topology = Topology::new();
+    topology.observe(
+        &[
+            (1, NodeType::Client),
+            (11, NodeType::Drone),
+            (12, NodeType::Drone),
+            (21, NodeType::Server),
+        ],
+        Instant::now(),
+    );
+    topology.set_local_links(11, [1, 12, 13]);
+    topology.record_drop(11, 12);
+    topology.record_nack(12, 11);
+    topology.record_nack(11, 12);
+    topology
+}
+
+#[test]
+fn test_dot_export() {
+    let dot = sample_topology().to_dot();
+
+    assert!(dot.starts_with("graph topology {"));
+    assert!(dot.contains("1 [label=\"Client 1\", fillcolor=lightblue];"));
+    assert!(dot.contains("11 [label=\"Drone 11\", fillcolor=orange, shape=doublecircle];"));
+    assert!(dot.contains("21 [label=\"Server 21\", fillcolor=palegreen];"));
+    // Drone 13 is a neighbor that never appeared in a trace
+    assert!(dot.contains("13 [label=\"Node 13\", fillcolor=lightgray];"));
+
+    assert!(dot.contains("11 -- 12 [label=\"drops: 1, nacks: 2\"];"));
+    assert!(dot.contains("11 -- 13 [label=\"drops: 0, nacks: 0\"];"));
+    assert!(dot.contains("12 -- 21 [label=\"drops: 0, nacks: 0\", style=dashed];"));
+    assert!(dot.trim_end().ends_with('}'));
+}
+
+#[test]
+fn test_json_export() {
+    let json = sample_topology().to_json();
+
+    assert_eq!(json["drone"], 11);
+    let nodes = json["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 5);
+    assert_eq!(nodes[0]["id"], 1);
+    assert_eq!(nodes[0]["type"], "Client");
+    assert!(nodes[0]["seen_ms_ago"].is_u64());
+    assert_eq!(nodes[3]["id"], 13);
+    assert!(nodes[3]["type"].is_null());
+    assert!(nodes[3]["seen_ms_ago"].is_null());
+
+    let edges = json["edges"].as_array().unwrap();
+    let edge = edges
+        .iter()
+        .find(|edge| edge["a"] == 11 && edge["b"] == 12)
+        .unwrap();
+    assert_eq!(edge["local"], true);
+    assert_eq!(edge["drops"], 1);
+    assert_eq!(edge["nacks"], 2);
+    let edge = edges
+        .iter()
+        .find(|edge| edge["a"] == 12 && edge["b"] == 21)
+        .unwrap();
+    assert_eq!(edge["local"], false);
+}
+
+#[test]
+fn test_drone_counts_drops_and_nacks() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, prev_recv) = unbounded();
+    let (next_send, _next_recv) = unbounded();
+
+    let topology = Arc::new(Mutex::new(Topology::new()));
+    let mut drone = RustasticDrone::new(
+        11,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(1, prev_send), (12, next_send)]),
+        1.0,
+    )
+    .with_topology(Arc::clone(&topology));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    // Every fragment is dropped and NACKed to Client 1
+    packet_send
+        .send(Packet {
+            pack_type: PacketType::MsgFragment(Fragment {
+                fragment_index: 0,
+                total_n_fragments: 1,
+                length: FRAGMENT_DSIZE as u8,
+                data: [1; FRAGMENT_DSIZE],
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![1, 11, 12],
+            },
+            session_id: 5,
+        })
+        .unwrap();
+    assert!(prev_recv.recv_timeout(TIMEOUT).is_ok());
+
+    command_send.send(DroneCommand::RemoveSender(12)).unwrap();
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+
+    let topology = topology.lock().unwrap();
+    assert_eq!(topology.counters(11, 12).drops, 1);
+    assert_eq!(topology.counters(1, 11).nacks, 1);
+    assert_eq!(topology.local_links(), Some((11, vec![1])));
+}
+
+#[test]
+fn test_forwarded_nack_is_counted_once() {
+    let (controller_send, _controller_recv) = unbounded();
+    let (command_send, command_recv) = unbounded();
+    let (packet_send, packet_recv) = unbounded();
+    let (prev_send, prev_recv) = unbounded();
+    let (next_send, _next_recv) = unbounded();
+
+    let topology = Arc::new(Mutex::new(Topology::new()));
+    let mut drone = RustasticDrone::new(
+        11,
+        controller_send,
+        command_recv,
+        packet_recv,
+        HashMap::from([(1, prev_send), (12, next_send)]),
+        0.0,
+    )
+    .with_topology(Arc::clone(&topology));
+    let drone_thread = thread::spawn(move || drone.run());
+
+    // Drone 12 sent the NACK, it counted it on its own link to Drone 11
+    packet_send
+        .send(Packet {
+            pack_type: PacketType::Nack(Nack {
+                fragment_index: 0,
+                nack_type: NackType::Dropped,
+            }),
+            routing_header: SourceRoutingHeader {
+                hop_index: 1,
+                hops: vec![12, 11, 1],
+            },
+            session_id: 5,
+        })
+        .unwrap();
+    assert!(prev_recv.recv_timeout(TIMEOUT).is_ok());
+
+    command_send.send(DroneCommand::Crash).unwrap();
+    drone_thread.join().unwrap();
+
+    let topology = topology.lock().unwrap();
+    assert_eq!(topology.counters(11, 12).nacks, 0);
+    assert_eq!(topology.counters(1, 11).nacks, 1);
+}
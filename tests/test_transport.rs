@@ -0,0 +1,83 @@
+mod common;
+
+use common::fragment_packet;
+use rustastic_drone::{ChannelTransport, PacketTransport, RustasticDrone, TransportError};
+
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::{cell::RefCell, collections::HashMap};
+use wg_2024::{controller::DroneEvent, network::NodeId, packet::Packet};
+
+/// A transport that records the packets instead of delivering them
+struct RecordingTransport {
+    packet_recv: Receiver<Packet>,
+    neighbors: Vec<NodeId>,
+    sent: RefCell<Vec<(NodeId, Packet)>>,
+}
+
+impl PacketTransport for RecordingTransport {
+    fn send(&self, to: NodeId, packet: Packet) -> Result<(), TransportError> {
+        if !self.neighbors.contains(&to) {
+            return Err(TransportError::NotConnected(to));
+        }
+        self.sent.borrow_mut().push((to, packet));
+        Ok(())
+    }
+
+    fn packet_recv(&self) -> &Receiver<Packet> {
+        &self.packet_recv
+    }
+
+    fn neighbors(&self) -> Vec<NodeId> {
+        self.neighbors.clone()
+    }
+
+    fn add_sender(&mut self, node_id: NodeId, _sender: Sender<Packet>) {
+        self.neighbors.push(node_id);
+    }
+
+    fn remove_sender(&mut self, node_id: NodeId) {
+        self.neighbors.retain(|&id| id != node_id);
+    }
+}
+
+#[test]
+fn test_drone_over_custom_transport() {
+    let (controller_send, controller_recv) = unbounded();
+    let transport = RecordingTransport {
+        packet_recv: unbounded().1,
+        neighbors: vec![0, 2],
+        sent: RefCell::new(Vec::new()),
+    };
+
+    let mut drone =
+        RustasticDrone::with_transport(1, controller_send, unbounded().1, transport, 0.0);
+    drone.handle_packet(fragment_packet(0, vec![0, 1, 2]));
+
+    assert!(matches!(
+        controller_recv.try_recv(),
+        Ok(DroneEvent::PacketSent(_))
+    ));
+    assert_eq!(drone.buffer().len(), 1);
+}
+
+#[test]
+fn test_channel_transport() {
+    let (neighbor_send, neighbor_recv) = unbounded();
+    let mut transport = ChannelTransport::new(unbounded().1, HashMap::from([(2, neighbor_send)]));
+
+    assert!(transport.send(2, fragment_packet(0, vec![0, 1, 2])).is_ok());
+    assert!(neighbor_recv.try_recv().is_ok());
+    assert!(matches!(
+        transport.send(3, fragment_packet(0, vec![0, 1, 2])),
+        Err(TransportError::NotConnected(3))
+    ));
+
+    drop(neighbor_recv);
+    assert!(matches!(
+        transport.send(2, fragment_packet(0, vec![0, 1, 2])),
+        Err(TransportError::Disconnected(2))
+    ));
+
+    transport.remove_sender(2);
+    assert!(transport.neighbors().is_empty());
+}